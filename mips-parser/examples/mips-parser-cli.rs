@@ -14,20 +14,36 @@ use pest::{error::Error as PestError, iterators::Pairs};
 
 use mips_parser::prelude::*;
 use mips_parser::ast::nodes::Line;
+use mips_parser::diagnostic::diagnose_line;
 
 #[derive(Debug)]
 enum CliError {
     IOError(IOError),
     PestError(PestError<Rule>),
     AstError(AstError),
+    Diagnostic(Diagnostic),
 }
 
 fn main() {
-    if let Err(err) = cli() {
-        println!("Error: {:?}", err);
+    match cli() {
+        Err(CliError::Diagnostic(_)) => std::process::exit(1),
+        Err(err) => println!("Error: {:?}", err),
+        Ok(_) => {}
     }
 }
 
+// Print the diagnostics of all invalid lines, returning the first as a CLI error.
+fn report(path: &str, source: &str, err: MipsParserError) -> CliError {
+    let mut diagnostics = Program::parse_lossy(&source).diagnostics;
+    if diagnostics.is_empty() {
+        diagnostics.push(err.diagnostic(source));
+    }
+    for diagnostic in diagnostics.iter() {
+        eprintln!("{}\n", diagnostic.render(path, source));
+    }
+    CliError::Diagnostic(diagnostics.remove(0))
+}
+
 fn cli() -> Result<(), CliError> {
     let yaml = load_yaml!("./mips-parser-cli.yaml");
     let matches = App::from_yaml(yaml).get_matches();
@@ -39,10 +55,35 @@ fn cli() -> Result<(), CliError> {
 
     let as_peg = (output == "peg");
     let as_ast = (output == "ast");
+    let as_fmt = (output == "fmt");
+    let as_dot = (output == "dot");
+
+    if as_fmt || as_dot {
+        // Formatting and control flow need the whole program, so stdin is read to the end
+        let input = match file {
+            Some(path) => read_to_string(path).map_err(CliError::IOError)?,
+            None => {
+                let mut input = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut input)
+                    .map_err(CliError::IOError)?;
+                input
+            }
+        };
+        if as_fmt {
+            print!("{}", Formatter::new().format(&input));
+        } else {
+            let path = file.unwrap_or("<stdin>");
+            let program = Program::try_from_str(&input).map_err(|e| report(path, &input, e))?;
+            print!("{}", Cfg::new(&program).to_dot());
+        }
+        return Ok(());
+    }
 
     if let Some(path) = file {
         let input = read_to_string(path).map_err(CliError::IOError)?;
-        let peg = MipsParser::parse(Rule::program, &input).map_err(CliError::PestError)?;
+        let peg = MipsParser::parse(Rule::program, &input)
+            .map_err(|e| report(path, &input, MipsParserError::ParserError(e)))?;
         let pair = peg.first_inner().map_err(CliError::AstError)?;
         if as_peg {
             if pretty {
@@ -52,7 +93,8 @@ fn cli() -> Result<(), CliError> {
             }
             return Ok(());
         }
-        let ast = Program::try_from_pair(pair).map_err(CliError::AstError)?;
+        let ast = Program::try_from_pair(pair)
+            .map_err(|e| report(path, &input, MipsParserError::AstError(e)))?;
         if as_ast {
             if pretty {
                 println!("{:#?}", ast);
@@ -75,6 +117,10 @@ fn cli() -> Result<(), CliError> {
 }
 
 fn exec_line(line: &String, as_peg: bool, as_ast: bool, pretty: bool) {
+    if let Some(diagnostic) = diagnose_line(0, line) {
+        println!("{}", diagnostic.render("<stdin>", line));
+        return;
+    }
     let pairs = MipsParser::parse(Rule::line, &line);
     if let Err(e) = &pairs {
         println!("{:?}", e);
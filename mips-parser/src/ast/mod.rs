@@ -37,6 +37,27 @@ pub enum AstError {
     InsufficientPairs,
     /// Wrong argument kind.
     WrongArg(String),
+    /// Arguments not matching the signature of their function.
+    Signature(String),
+}
+
+impl std::fmt::Display for AstError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AstError::Program => write!(f, "failed to construct program"),
+            AstError::Expr(s) => write!(f, "failed to construct expression from {}", s),
+            AstError::Func(s) => write!(f, "failed to construct function from {}", s),
+            AstError::Arg(s) => write!(f, "failed to construct argument from {}", s),
+            AstError::Mem(s) => write!(f, "failed to construct memory register from {}", s),
+            AstError::Dev(s) => write!(f, "failed to construct device register from {}", s),
+            AstError::Val(s) => write!(f, "failed to construct value from {}", s),
+            AstError::ParseInt(e) => write!(f, "invalid integer ({})", e),
+            AstError::ParseFloat(e) => write!(f, "invalid number ({})", e),
+            AstError::InsufficientPairs => write!(f, "not enough pairs"),
+            AstError::WrongArg(s) => write!(f, "{}", s),
+            AstError::Signature(s) => write!(f, "{}", s),
+        }
+    }
 }
 
 /// Shortcut type for AST error results.
@@ -18,6 +18,23 @@ pub enum Arg {
     ArgToken(String),
 }
 
+/// Function argument kind, as expected by a function signature.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ArgKind {
+    /// Memory register (literal or alias).
+    Mem,
+    /// Device register (literal or alias).
+    Dev,
+    /// Value (number or memory register).
+    Val,
+    /// Token (e.g. a logic type or alias name).
+    Tkn,
+    /// Memory or device register.
+    Reg,
+    /// Number literal.
+    Num,
+}
+
 impl Arg {
     #[rustfmt::skip]
     is_as_inner!(Arg, AstError, AstError::WrongArg, [
@@ -28,6 +45,20 @@ impl Arg {
     ]);
 }
 
+impl ArgKind {
+    /// Does an argument node have this kind.
+    pub fn accepts(&self, arg: &Arg) -> bool {
+        match self {
+            ArgKind::Mem => matches!(arg, Arg::ArgMem(_)),
+            ArgKind::Dev => matches!(arg, Arg::ArgDev(_)),
+            ArgKind::Val => matches!(arg, Arg::ArgVal(_)),
+            ArgKind::Tkn => matches!(arg, Arg::ArgToken(_)),
+            ArgKind::Reg => matches!(arg, Arg::ArgMem(_) | Arg::ArgDev(_)),
+            ArgKind::Num => matches!(arg, Arg::ArgVal(val) if val.lit().is_some()),
+        }
+    }
+}
+
 impl Node for Arg {
     /// Rule [`Rule::arg`].
     const RULE: Rule = Rule::arg;
@@ -40,13 +71,28 @@ impl Node for Arg {
             Rule::dev | Rule::dev_lit => Arg::ArgDev(Dev::try_from_pair(pair)?),
             Rule::val => Arg::ArgVal(Val::try_from_pair(pair)?),
             Rule::tkn => Arg::ArgToken(pair.as_str().into()),
-            Rule::num => Arg::ArgVal(Val::ValLit(pair_to_float(pair)?)),
+            Rule::num => Arg::ArgVal(Val::ValLit(pair_to_float(pair)?, None)),
+            Rule::lit => Arg::ArgVal(Val::try_from_pair(pair)?),
             _ => return Err(AstError::Arg(format!("{:?}", pair))),
         };
         Ok(arg)
     }
 }
 
+impl Display for ArgKind {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            ArgKind::Mem => "mem",
+            ArgKind::Dev => "dev",
+            ArgKind::Val => "val",
+            ArgKind::Tkn => "tkn",
+            ArgKind::Reg => "reg",
+            ArgKind::Num => "num",
+        };
+        fmt.write_str(s)
+    }
+}
+
 impl Display for Arg {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -38,6 +38,7 @@ impl Node for Expr {
                     .into_inner()
                     .map(Arg::try_from_pair)
                     .collect::<AstResult<Vec<Arg>>>()?;
+                check_signature(&func, &args)?;
                 Expr(func, args)
             }
             _ => return Err(AstError::Expr(format!("{:?}", pair))),
@@ -46,6 +47,31 @@ impl Node for Expr {
     }
 }
 
+/// Check arguments against the signature of their function.
+fn check_signature(func: &Func, args: &[Arg]) -> AstResult<()> {
+    let signature = func.signature();
+    if args.len() != signature.len() {
+        return Err(AstError::Signature(format!(
+            "`{}` expects {} arguments, found {}",
+            func,
+            signature.len(),
+            args.len()
+        )));
+    }
+    for (i, (kind, arg)) in signature.iter().zip(args.iter()).enumerate() {
+        if !kind.accepts(arg) {
+            return Err(AstError::Signature(format!(
+                "`{}` expects {} as argument {}, found {:?}",
+                func,
+                kind,
+                i + 1,
+                arg
+            )));
+        }
+    }
+    Ok(())
+}
+
 impl Display for Expr {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         let func = &self.0;
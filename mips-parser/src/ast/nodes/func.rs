@@ -3,28 +3,169 @@ use std::{fmt, fmt::Display};
 use crate::Rule;
 use crate::ast::{AstError, AstResult};
 
+use super::ArgKind;
+
+/// Function category (the instruction groups of the grammar).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Category {
+    Io,
+    Branch,
+    Select,
+    Math,
+    Logic,
+    Stack,
+    Misc,
+    Label,
+}
+
+impl Display for Category {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Category::Io     => "Device IO",
+            Category::Branch => "Flow Control, Branches and Jumps",
+            Category::Select => "Variable Selection",
+            Category::Math   => "Mathematical Operations",
+            Category::Logic  => "Logic",
+            Category::Stack  => "Stack",
+            Category::Misc   => "Misc",
+            Category::Label  => "Label",
+        };
+        fmt.write_str(s)
+    }
+}
+
+/// Control flow of a function.
+///
+/// Jumping functions take the jump target (a line, or an offset for relative jumps)
+/// as their last argument.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Flow {
+    /// Continues to the next line.
+    Next,
+    /// Branches to a line if a condition holds.
+    Branch,
+    /// Branches to a line if a condition holds, storing the next line in `ra`.
+    BranchAl,
+    /// Branches by an offset if a condition holds.
+    BranchRel,
+    /// Jumps to a line.
+    Jump,
+    /// Jumps to a line, storing the next line in `ra`.
+    JumpAl,
+    /// Jumps by an offset.
+    JumpRel,
+}
+
+impl Flow {
+    /// Can the function jump.
+    pub fn jumps(&self) -> bool {
+        *self != Flow::Next
+    }
+
+    /// Is the jump target an offset from the current line.
+    pub fn is_relative(&self) -> bool {
+        matches!(self, Flow::BranchRel | Flow::JumpRel)
+    }
+
+    /// Does the function store the next line in `ra` when jumping.
+    pub fn stores_ra(&self) -> bool {
+        matches!(self, Flow::BranchAl | Flow::JumpAl)
+    }
+
+    /// Does the function always jump (without returning to the next line through `ra`).
+    pub fn is_unconditional(&self) -> bool {
+        matches!(self, Flow::Jump | Flow::JumpRel)
+    }
+}
+
+/// Function metadata.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FuncInfo {
+    /// Argument kinds the function expects.
+    pub signature: &'static [ArgKind],
+    /// Category of the function.
+    pub category: Category,
+    /// Control flow of the function.
+    pub flow: Flow,
+    /// Description of the function (naming the arguments `a`, `b`, `c`, ...).
+    pub description: &'static str,
+}
+
 macro_rules! functions {
-    {$(($enum_variant:ident, $rule:ident)),*$(,)*} => {
+    {$(
+        $category:ident: [
+            $( ($enum_variant:ident, $rule:ident, [$($arg_kind:ident),*], $flow:ident, $doc:literal) ),*
+            $(,)*
+        ]
+    ),*$(,)*} => {
         /// Function node.
         ///
         /// Contains variants for all functions available in Stationeers MIPS.
         #[derive(Clone, PartialEq, Debug)]
         pub enum Func {
-            $( $enum_variant, )*
+            $( $( #[doc = $doc] $enum_variant, )* )*
         }
 
         impl Func {
+            /// All function variants.
+            pub const ALL: &'static [Func] = &[ $( $( Func::$enum_variant, )* )* ];
+
             pub fn try_from_rule(rule: Rule) -> AstResult<Self> {
                 let func = match rule {
-                    $( Rule::$rule => Func::$enum_variant, )*
+                    $( $( Rule::$rule => Func::$enum_variant, )* )*
                     _ => return Err(AstError::Func(format!("{:?}", rule))),
                 };
                 Ok(func)
             }
+
+            /// Metadata of this function.
+            pub fn info(&self) -> &'static FuncInfo {
+                match self {
+                    $( $( Func::$enum_variant => &FuncInfo {
+                        signature: &[ $( ArgKind::$arg_kind, )* ],
+                        category: Category::$category,
+                        flow: Flow::$flow,
+                        description: $doc,
+                    }, )* )*
+                }
+            }
         }
     };
 }
 
+impl Func {
+    /// Try to get the function for an instruction name (e.g. `"add"`).
+    ///
+    /// Labels are not instructions, so are never returned.
+    pub fn try_from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .filter(|func| **func != Func::Label)
+            .find(|func| func.to_string() == name)
+            .cloned()
+    }
+
+    /// Argument kinds this function expects.
+    pub fn signature(&self) -> &'static [ArgKind] {
+        self.info().signature
+    }
+
+    /// Category of this function.
+    pub fn category(&self) -> Category {
+        self.info().category
+    }
+
+    /// Control flow of this function.
+    pub fn flow(&self) -> Flow {
+        self.info().flow
+    }
+
+    /// Description of this function.
+    pub fn description(&self) -> &'static str {
+        self.info().description
+    }
+}
+
 impl Display for Func {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         write!(fmt, "{}", format!("{:?}", self).to_lowercase())
@@ -32,131 +173,157 @@ impl Display for Func {
 }
 
 functions!{
-    // Device IO
-    ( Bdns,   f_bdns   ),
-    ( Bdnsal, f_bdnsal ),
-    ( Bdse,   f_bdse   ),
-    ( Bdseal, f_bdseal ),
-    ( Brdns,  f_brdns  ),
-    ( Brdse,  f_brdse  ),
-    ( L,      f_l      ),
-    ( Lb,     f_lb     ),
-    ( Lr,     f_lr     ),
-    ( Ls,     f_ls     ),
-    ( S,      f_s      ),
-    ( Sb,     f_sb     ),
-    // Flow Control, Branches and Jumps
-    ( Bap,    f_bap    ),
-    ( Bapal,  f_bapal  ),
-    ( Bapz,   f_bapz   ),
-    ( Bapzal, f_bapzal ),
-    ( Beq,    f_beq    ),
-    ( Beqal,  f_beqal  ),
-    ( Beqz,   f_beqz   ),
-    ( Beqzal, f_beqzal ),
-    ( Bge,    f_bge    ),
-    ( Bgeal,  f_bgeal  ),
-    ( Bgez,   f_bgez   ),
-    ( Bgezal, f_bgezal ),
-    ( Bgt,    f_bgt    ),
-    ( Bgtal,  f_bgtal  ),
-    ( Bgtz,   f_bgtz   ),
-    ( Bgtzal, f_bgtzal ),
-    ( Ble,    f_ble    ),
-    ( Bleal,  f_bleal  ),
-    ( Blez,   f_blez   ),
-    ( Blezal, f_blezal ),
-    ( Blt,    f_blt    ),
-    ( Bltal,  f_bltal  ),
-    ( Bltz,   f_bltz   ),
-    ( Bltzal, f_bltzal ),
-    ( Bna,    f_bna    ),
-    ( Bnaal,  f_bnaal  ),
-    ( Bnaz,   f_bnaz   ),
-    ( Bnazal, f_bnazal ),
-    ( Bne,    f_bne    ),
-    ( Bneal,  f_bneal  ),
-    ( Bnez,   f_bnez   ),
-    ( Bnezal, f_bnezal ),
-    ( Brap,   f_brap   ),
-    ( Brapz,  f_brapz  ),
-    ( Breq,   f_breq   ),
-    ( Breqz,  f_breqz  ),
-    ( Brge,   f_brge   ),
-    ( Brgez,  f_brgez  ),
-    ( Brgt,   f_brgt   ),
-    ( Brgtz,  f_brgtz  ),
-    ( Brle,   f_brle   ),
-    ( Brlez,  f_brlez  ),
-    ( Brlt,   f_brlt   ),
-    ( Brltz,  f_brltz  ),
-    ( Brna,   f_brna   ),
-    ( Brnaz,  f_brnaz  ),
-    ( Brne,   f_brne   ),
-    ( Brnez,  f_brnez  ),
-    ( J,      f_j      ),
-    ( Jal,    f_jal    ),
-    ( Jr,     f_jr     ),
-    // Variable Selection
-    ( Sap,    f_sap    ),
-    ( Sapz,   f_sapz   ),
-    ( Sdns,   f_sdns   ),
-    ( Sdse,   f_sdse   ),
-    ( Select, f_select ),
-    ( Seq,    f_seq    ),
-    ( Seqz,   f_seqz   ),
-    ( Sge,    f_sge    ),
-    ( Sgez,   f_sgez   ),
-    ( Sgt,    f_sgt    ),
-    ( Sgtz,   f_sgtz   ),
-    ( Sle,    f_sle    ),
-    ( Slez,   f_slez   ),
-    ( Slt,    f_slt    ),
-    ( Sltz,   f_sltz   ),
-    ( Sna,    f_sna    ),
-    ( Snaz,   f_snaz   ),
-    ( Sne,    f_sne    ),
-    ( Snez,   f_snez   ),
-    // Mathematical Operations
-    ( Abs,    f_abs    ),
-    ( Acos,   f_acos   ),
-    ( Add,    f_add    ),
-    ( Asin,   f_asin   ),
-    ( Atan,   f_atan   ),
-    ( Ceil,   f_ceil   ),
-    ( Cos,    f_cos    ),
-    ( Div,    f_div    ),
-    ( Exp,    f_exp    ),
-    ( Floor,  f_floor  ),
-    ( Log,    f_log    ),
-    ( Max,    f_max    ),
-    ( Min,    f_min    ),
-    ( Mod,    f_mod    ),
-    ( Mul,    f_mul    ),
-    ( Rand,   f_rand   ),
-    ( Round,  f_round  ),
-    ( Sin,    f_sin    ),
-    ( Sqrt,   f_sqrt   ),
-    ( Sub,    f_sub    ),
-    ( Tan,    f_tan    ),
-    ( Trunc,  f_trunc  ),
-    // Logic
-    ( And,    f_and    ),
-    ( Nor,    f_nor    ),
-    ( Or,     f_or     ),
-    ( Xor,    f_xor    ),
-    // Stack
-    ( Peek,   f_peek   ),
-    ( Pop,    f_pop    ),
-    ( Push,   f_push   ),
-    // Misc
-    ( Alias,  f_alias  ),
-    ( Define, f_define ),
-    ( Hcf,    f_hcf    ),
-    ( Move,   f_move   ),
-    ( Sleep,  f_sleep  ),
-    ( Yield,  f_yield  ),
-    // Label
-    ( Label,  f_label  ),
+    Io: [
+        ( Bdns,   f_bdns,   [Dev, Val], Branch,   "Branch to line b if device a is not set" ),
+        ( Bdnsal, f_bdnsal, [Dev, Val], BranchAl, "Branch to line b if device a is not set, storing the next line in ra" ),
+        ( Bdse,   f_bdse,   [Dev, Val], Branch,   "Branch to line b if device a is set" ),
+        ( Bdseal, f_bdseal, [Dev, Val], BranchAl, "Branch to line b if device a is set, storing the next line in ra" ),
+        ( Brdns,  f_brdns,  [Dev, Val], BranchRel, "Branch by b lines if device a is not set" ),
+        ( Brdse,  f_brdse,  [Dev, Val], BranchRel, "Branch by b lines if device a is set" ),
+        ( L,      f_l,      [Mem, Dev, Tkn], Next, "Load logic type c of device b into register a" ),
+        ( Lb,     f_lb,     [Mem, Val, Tkn, Val], Next, "Load logic type c of all devices of hash b into register a, reduced by batch mode d" ),
+        ( Lbn,    f_lbn,    [Mem, Val, Val, Tkn, Val], Next, "Load logic type d of all devices of hash b named with hash c into register a, reduced by batch mode e" ),
+        ( Lbns,   f_lbns,   [Mem, Val, Val, Val, Tkn, Val], Next, "Load slot logic type e of slot d of all devices of hash b named with hash c into register a, reduced by batch mode f" ),
+        ( Lbs,    f_lbs,    [Mem, Val, Val, Tkn, Val], Next, "Load slot logic type d of slot c of all devices of hash b into register a, reduced by batch mode e" ),
+        ( Ld,     f_ld,     [Mem, Val, Tkn], Next, "Load logic type c of the device with id b into register a" ),
+        ( Lr,     f_lr,     [Mem, Dev, Val, Val], Next, "Load the reagent of hash d of device b into register a, by reagent mode c" ),
+        ( Ls,     f_ls,     [Mem, Dev, Val, Tkn], Next, "Load slot logic type d of slot c of device b into register a" ),
+        ( S,      f_s,      [Dev, Tkn, Val], Next, "Store c in logic type b of device a" ),
+        ( Sb,     f_sb,     [Val, Tkn, Val], Next, "Store c in logic type b of all devices of hash a" ),
+        ( Sbn,    f_sbn,    [Val, Val, Tkn, Val], Next, "Store d in logic type c of all devices of hash a named with hash b" ),
+        ( Sbs,    f_sbs,    [Val, Val, Tkn, Val], Next, "Store d in slot logic type c of slot b of all devices of hash a" ),
+        ( Sd,     f_sd,     [Val, Tkn, Val], Next, "Store c in logic type b of the device with id a" ),
+        ( Ss,     f_ss,     [Dev, Val, Tkn, Val], Next, "Store d in slot logic type c of slot b of device a" ),
+    ],
+    Branch: [
+        ( Bap,    f_bap,    [Val, Val, Val, Val], Branch,   "Branch to line d if a is approximately equal to b (by relative precision c)" ),
+        ( Bapal,  f_bapal,  [Val, Val, Val, Val], BranchAl, "Branch to line d if a is approximately equal to b (by relative precision c), storing the next line in ra" ),
+        ( Bapz,   f_bapz,   [Val, Val, Val], Branch,   "Branch to line c if a is approximately zero (by precision b)" ),
+        ( Bapzal, f_bapzal, [Val, Val, Val], BranchAl, "Branch to line c if a is approximately zero (by precision b), storing the next line in ra" ),
+        ( Beq,    f_beq,    [Val, Val, Val], Branch,   "Branch to line c if a == b" ),
+        ( Beqal,  f_beqal,  [Val, Val, Val], BranchAl, "Branch to line c if a == b, storing the next line in ra" ),
+        ( Beqz,   f_beqz,   [Val, Val], Branch,   "Branch to line b if a == 0" ),
+        ( Beqzal, f_beqzal, [Val, Val], BranchAl, "Branch to line b if a == 0, storing the next line in ra" ),
+        ( Bge,    f_bge,    [Val, Val, Val], Branch,   "Branch to line c if a >= b" ),
+        ( Bgeal,  f_bgeal,  [Val, Val, Val], BranchAl, "Branch to line c if a >= b, storing the next line in ra" ),
+        ( Bgez,   f_bgez,   [Val, Val], Branch,   "Branch to line b if a >= 0" ),
+        ( Bgezal, f_bgezal, [Val, Val], BranchAl, "Branch to line b if a >= 0, storing the next line in ra" ),
+        ( Bgt,    f_bgt,    [Val, Val, Val], Branch,   "Branch to line c if a > b" ),
+        ( Bgtal,  f_bgtal,  [Val, Val, Val], BranchAl, "Branch to line c if a > b, storing the next line in ra" ),
+        ( Bgtz,   f_bgtz,   [Val, Val], Branch,   "Branch to line b if a > 0" ),
+        ( Bgtzal, f_bgtzal, [Val, Val], BranchAl, "Branch to line b if a > 0, storing the next line in ra" ),
+        ( Ble,    f_ble,    [Val, Val, Val], Branch,   "Branch to line c if a <= b" ),
+        ( Bleal,  f_bleal,  [Val, Val, Val], BranchAl, "Branch to line c if a <= b, storing the next line in ra" ),
+        ( Blez,   f_blez,   [Val, Val], Branch,   "Branch to line b if a <= 0" ),
+        ( Blezal, f_blezal, [Val, Val], BranchAl, "Branch to line b if a <= 0, storing the next line in ra" ),
+        ( Blt,    f_blt,    [Val, Val, Val], Branch,   "Branch to line c if a < b" ),
+        ( Bltal,  f_bltal,  [Val, Val, Val], BranchAl, "Branch to line c if a < b, storing the next line in ra" ),
+        ( Bltz,   f_bltz,   [Val, Val], Branch,   "Branch to line b if a < 0" ),
+        ( Bltzal, f_bltzal, [Val, Val], BranchAl, "Branch to line b if a < 0, storing the next line in ra" ),
+        ( Bna,    f_bna,    [Val, Val, Val, Val], Branch,   "Branch to line d if a is not approximately equal to b (by relative precision c)" ),
+        ( Bnaal,  f_bnaal,  [Val, Val, Val, Val], BranchAl, "Branch to line d if a is not approximately equal to b (by relative precision c), storing the next line in ra" ),
+        ( Bnaz,   f_bnaz,   [Val, Val, Val], Branch,   "Branch to line c if a is not approximately zero (by precision b)" ),
+        ( Bnazal, f_bnazal, [Val, Val, Val], BranchAl, "Branch to line c if a is not approximately zero (by precision b), storing the next line in ra" ),
+        ( Bne,    f_bne,    [Val, Val, Val], Branch,   "Branch to line c if a != b" ),
+        ( Bneal,  f_bneal,  [Val, Val, Val], BranchAl, "Branch to line c if a != b, storing the next line in ra" ),
+        ( Bnez,   f_bnez,   [Val, Val], Branch,   "Branch to line b if a != 0" ),
+        ( Bnezal, f_bnezal, [Val, Val], BranchAl, "Branch to line b if a != 0, storing the next line in ra" ),
+        ( Brap,   f_brap,   [Val, Val, Val, Val], BranchRel, "Branch by d lines if a is approximately equal to b (by relative precision c)" ),
+        ( Brapz,  f_brapz,  [Val, Val, Val], BranchRel, "Branch by c lines if a is approximately zero (by precision b)" ),
+        ( Breq,   f_breq,   [Val, Val, Val], BranchRel, "Branch by c lines if a == b" ),
+        ( Breqz,  f_breqz,  [Val, Val], BranchRel, "Branch by b lines if a == 0" ),
+        ( Brge,   f_brge,   [Val, Val, Val], BranchRel, "Branch by c lines if a >= b" ),
+        ( Brgez,  f_brgez,  [Val, Val], BranchRel, "Branch by b lines if a >= 0" ),
+        ( Brgt,   f_brgt,   [Val, Val, Val], BranchRel, "Branch by c lines if a > b" ),
+        ( Brgtz,  f_brgtz,  [Val, Val], BranchRel, "Branch by b lines if a > 0" ),
+        ( Brle,   f_brle,   [Val, Val, Val], BranchRel, "Branch by c lines if a <= b" ),
+        ( Brlez,  f_brlez,  [Val, Val], BranchRel, "Branch by b lines if a <= 0" ),
+        ( Brlt,   f_brlt,   [Val, Val, Val], BranchRel, "Branch by c lines if a < b" ),
+        ( Brltz,  f_brltz,  [Val, Val], BranchRel, "Branch by b lines if a < 0" ),
+        ( Brna,   f_brna,   [Val, Val, Val, Val], BranchRel, "Branch by d lines if a is not approximately equal to b (by relative precision c)" ),
+        ( Brnaz,  f_brnaz,  [Val, Val, Val], BranchRel, "Branch by c lines if a is not approximately zero (by precision b)" ),
+        ( Brne,   f_brne,   [Val, Val, Val], BranchRel, "Branch by c lines if a != b" ),
+        ( Brnez,  f_brnez,  [Val, Val], BranchRel, "Branch by b lines if a != 0" ),
+        ( J,      f_j,      [Val], Jump,    "Jump to line a" ),
+        ( Jal,    f_jal,    [Val], JumpAl,  "Jump to line a, storing the next line in ra" ),
+        ( Jr,     f_jr,     [Val], JumpRel, "Jump by a lines" ),
+    ],
+    Select: [
+        ( Sap,    f_sap,    [Mem, Val, Val, Val], Next, "Register a = 1 if b is approximately equal to c (by relative precision d), else 0" ),
+        ( Sapz,   f_sapz,   [Mem, Val, Val], Next, "Register a = 1 if b is approximately zero (by precision c), else 0" ),
+        ( Sdns,   f_sdns,   [Mem, Dev], Next, "Register a = 1 if device b is not set, else 0" ),
+        ( Sdse,   f_sdse,   [Mem, Dev], Next, "Register a = 1 if device b is set, else 0" ),
+        ( Select, f_select, [Mem, Val, Val, Val], Next, "Register a = c if b is non-zero, else d" ),
+        ( Seq,    f_seq,    [Mem, Val, Val], Next, "Register a = 1 if b == c, else 0" ),
+        ( Seqz,   f_seqz,   [Mem, Val], Next, "Register a = 1 if b == 0, else 0" ),
+        ( Sge,    f_sge,    [Mem, Val, Val], Next, "Register a = 1 if b >= c, else 0" ),
+        ( Sgez,   f_sgez,   [Mem, Val], Next, "Register a = 1 if b >= 0, else 0" ),
+        ( Sgt,    f_sgt,    [Mem, Val, Val], Next, "Register a = 1 if b > c, else 0" ),
+        ( Sgtz,   f_sgtz,   [Mem, Val], Next, "Register a = 1 if b > 0, else 0" ),
+        ( Sle,    f_sle,    [Mem, Val, Val], Next, "Register a = 1 if b <= c, else 0" ),
+        ( Slez,   f_slez,   [Mem, Val], Next, "Register a = 1 if b <= 0, else 0" ),
+        ( Slt,    f_slt,    [Mem, Val, Val], Next, "Register a = 1 if b < c, else 0" ),
+        ( Sltz,   f_sltz,   [Mem, Val], Next, "Register a = 1 if b < 0, else 0" ),
+        ( Sna,    f_sna,    [Mem, Val, Val, Val], Next, "Register a = 1 if b is not approximately equal to c (by relative precision d), else 0" ),
+        ( Snaz,   f_snaz,   [Mem, Val, Val], Next, "Register a = 1 if b is not approximately zero (by precision c), else 0" ),
+        ( Sne,    f_sne,    [Mem, Val, Val], Next, "Register a = 1 if b != c, else 0" ),
+        ( Snez,   f_snez,   [Mem, Val], Next, "Register a = 1 if b != 0, else 0" ),
+    ],
+    Math: [
+        ( Abs,    f_abs,    [Mem, Val], Next, "Register a = |b|" ),
+        ( Acos,   f_acos,   [Mem, Val], Next, "Register a = acos(b)" ),
+        ( Add,    f_add,    [Mem, Val, Val], Next, "Register a = b + c" ),
+        ( Asin,   f_asin,   [Mem, Val], Next, "Register a = asin(b)" ),
+        ( Atan,   f_atan,   [Mem, Val], Next, "Register a = atan(b)" ),
+        ( Ceil,   f_ceil,   [Mem, Val], Next, "Register a = b rounded up" ),
+        ( Cos,    f_cos,    [Mem, Val], Next, "Register a = cos(b)" ),
+        ( Div,    f_div,    [Mem, Val, Val], Next, "Register a = b / c" ),
+        ( Exp,    f_exp,    [Mem, Val], Next, "Register a = exp(b)" ),
+        ( Floor,  f_floor,  [Mem, Val], Next, "Register a = b rounded down" ),
+        ( Log,    f_log,    [Mem, Val], Next, "Register a = ln(b)" ),
+        ( Max,    f_max,    [Mem, Val, Val], Next, "Register a = the greater of b and c" ),
+        ( Min,    f_min,    [Mem, Val, Val], Next, "Register a = the lesser of b and c" ),
+        ( Mod,    f_mod,    [Mem, Val, Val], Next, "Register a = b mod c" ),
+        ( Mul,    f_mul,    [Mem, Val, Val], Next, "Register a = b * c" ),
+        ( Rand,   f_rand,   [Mem], Next, "Register a = a random value in [0, 1)" ),
+        ( Round,  f_round,  [Mem, Val], Next, "Register a = b rounded to the nearest integer" ),
+        ( Sin,    f_sin,    [Mem, Val], Next, "Register a = sin(b)" ),
+        ( Sqrt,   f_sqrt,   [Mem, Val], Next, "Register a = sqrt(b)" ),
+        ( Sub,    f_sub,    [Mem, Val, Val], Next, "Register a = b - c" ),
+        ( Tan,    f_tan,    [Mem, Val], Next, "Register a = tan(b)" ),
+        ( Trunc,  f_trunc,  [Mem, Val], Next, "Register a = b with its fractional part removed" ),
+    ],
+    Logic: [
+        ( And,    f_and,    [Mem, Val, Val], Next, "Register a = b and c (bitwise)" ),
+        ( Nor,    f_nor,    [Mem, Val, Val], Next, "Register a = b nor c (bitwise)" ),
+        ( Not,    f_not,    [Mem, Val], Next, "Register a = not b (bitwise)" ),
+        ( Or,     f_or,     [Mem, Val, Val], Next, "Register a = b or c (bitwise)" ),
+        ( Sla,    f_sla,    [Mem, Val, Val], Next, "Register a = b shifted left by c bits (arithmetic)" ),
+        ( Sll,    f_sll,    [Mem, Val, Val], Next, "Register a = b shifted left by c bits (logical)" ),
+        ( Sra,    f_sra,    [Mem, Val, Val], Next, "Register a = b shifted right by c bits (arithmetic)" ),
+        ( Srl,    f_srl,    [Mem, Val, Val], Next, "Register a = b shifted right by c bits (logical)" ),
+        ( Xor,    f_xor,    [Mem, Val, Val], Next, "Register a = b xor c (bitwise)" ),
+    ],
+    Stack: [
+        ( Peek,   f_peek,   [Mem], Next, "Register a = the value on top of the stack" ),
+        ( Pop,    f_pop,    [Mem], Next, "Register a = the value popped off the stack" ),
+        ( Push,   f_push,   [Val], Next, "Push a onto the stack" ),
+        ( Clr,    f_clr,    [Dev], Next, "Clear the stack memory of device a" ),
+        ( Clrd,   f_clrd,   [Val], Next, "Clear the stack memory of the device with id a" ),
+        ( Get,    f_get,    [Mem, Dev, Val], Next, "Register a = the value at address c of the stack of device b" ),
+        ( Getd,   f_getd,   [Mem, Val, Val], Next, "Register a = the value at address c of the stack of the device with id b" ),
+        ( Put,    f_put,    [Dev, Val, Val], Next, "Store c at address b of the stack of device a" ),
+        ( Putd,   f_putd,   [Val, Val, Val], Next, "Store c at address b of the stack of the device with id a" ),
+    ],
+    Misc: [
+        ( Alias,  f_alias,  [Tkn, Reg], Next, "Name register or device b as a" ),
+        ( Define, f_define, [Tkn, Num], Next, "Define a as the constant b" ),
+        ( Hcf,    f_hcf,    [], Next, "Halt and catch fire" ),
+        ( Move,   f_move,   [Mem, Val], Next, "Register a = b" ),
+        ( Sleep,  f_sleep,  [Val], Next, "Pause execution for a seconds" ),
+        ( Yield,  f_yield,  [], Next, "Pause execution until the next tick" ),
+    ],
+    Label: [
+        ( Label,  f_label,  [Tkn], Next, "Label a, naming the line it is on" ),
+    ],
 }
-
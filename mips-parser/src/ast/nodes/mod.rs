@@ -9,10 +9,10 @@ mod val;
 mod line;
 
 pub use line::Line;
-pub use arg::Arg;
+pub use arg::{Arg, ArgKind};
 pub use dev::Dev;
 pub use expr::Expr;
-pub use func::Func;
+pub use func::{Category, Flow, Func, FuncInfo};
 pub use mem::Mem;
-pub use program::Program;
+pub use program::{LossyLine, LossyProgram, Program};
 pub use val::Val;
@@ -5,6 +5,7 @@ use itertools::join;
 use pest::iterators::Pair;
 
 use crate::ast::{AstError, AstResult, Node};
+use crate::diagnostic::{diagnose_line, Diagnostic};
 use crate::Rule;
 
 use super::{Expr, line::Line};
@@ -56,6 +57,28 @@ impl Program {
         self.0.into_iter()
     }
 
+    /// Parse a program line by line, keeping every valid expression.
+    ///
+    /// Unlike [`Node::try_from_str`], parsing does not stop at the first invalid line;
+    /// each invalid line is recorded as a [`LossyLine::Error`] node with its diagnostic.
+    pub fn parse_lossy<S: AsRef<str>>(source: &S) -> LossyProgram {
+        let mut lines = Vec::new();
+        let mut diagnostics = Vec::new();
+        for (i, text) in source.as_ref().lines().enumerate() {
+            let line = if let Some(diagnostic) = diagnose_line(i, text) {
+                diagnostics.push(diagnostic);
+                LossyLine::Error(text.to_string())
+            } else {
+                match Line::try_from_str(&text).ok().flatten() {
+                    Some(expr) => LossyLine::Expr(expr),
+                    None => LossyLine::Blank,
+                }
+            };
+            lines.push(line);
+        }
+        LossyProgram { lines, diagnostics }
+    }
+
     pub fn push(&mut self, expr: Expr) {
         let i = self.0.len();
         self.0.push((i, expr));
@@ -68,3 +91,44 @@ impl Display for Program {
         writeln!(fmt, "{}", lines)
     }
 }
+
+/// Line of a lossily parsed program.
+#[derive(Clone, PartialEq, Debug)]
+pub enum LossyLine {
+    /// Valid expression.
+    Expr(Expr),
+    /// Blank line (or one with comments only).
+    Blank,
+    /// Invalid line (containing the source text).
+    Error(String),
+}
+
+/// Lossily parsed program (see [`Program::parse_lossy`]).
+#[derive(Clone, PartialEq, Debug)]
+pub struct LossyProgram {
+    /// One node per source line.
+    pub lines: Vec<LossyLine>,
+    /// Diagnostics of all invalid lines.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LossyProgram {
+    /// Were all lines valid.
+    pub fn is_ok(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Program of the valid expressions (invalid lines are left out).
+    pub fn program(&self) -> Program {
+        let expressions = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| match line {
+                LossyLine::Expr(expr) => Some((i, expr.clone())),
+                _ => None,
+            })
+            .collect();
+        Program(expressions)
+    }
+}
@@ -5,20 +5,41 @@ use super::mem::Mem;
 use pest::iterators::Pair;
 
 use crate::ast::{Node, pair_to_float, FirstInner, AstError, AstResult};
+use crate::hash::hash;
 use crate::Rule;
 
+/// Helper to convert a prefixed (e.g. `$FF`) hexadecimal or binary literal into a float,
+/// via its 64-bit integer representation.
+fn pair_to_radix(s: &str, radix: u32) -> AstResult<f64> {
+    let digits = &s[1..];
+    let n = u64::from_str_radix(digits, radix).map_err(AstError::ParseInt)?;
+    Ok(n as i64 as f64)
+}
+
 /// Value node.
 ///
 /// Values in MIPS expressions can be floating-point literals or those stored in state memory.
-/// The former is encapsulated in [`ValLit(f64)`](Val::ValLit),
+/// The former is encapsulated in [`ValLit(f64, Option<String>)`](Val::ValLit),
 /// while the later involves reducing a [`Mem`] node to a `StateIndex::Mem(i)`
 /// with which the ith value from state memory is obtained.
+/// Literals spelled other than as a decimal number (`HASH("...")`, `$FF` hexadecimal or `%1010`
+/// binary) are evaluated into a `ValLit` which keeps the original spelling for display.
 #[derive(Clone, PartialEq, Debug)]
 pub enum Val {
-    ValLit(f64),
+    ValLit(f64, Option<String>),
     ValMem(Mem),
 }
 
+impl Val {
+    /// Literal value, if this is a literal.
+    pub fn lit(&self) -> Option<f64> {
+        match self {
+            Val::ValLit(x, _) => Some(*x),
+            Val::ValMem(_) => None,
+        }
+    }
+}
+
 impl Node for Val {
     /// Rule [`Rule::val`].
     const RULE: Rule = Rule::val;
@@ -26,7 +47,18 @@ impl Node for Val {
     fn try_from_pair(pair: Pair<Rule>) -> AstResult<Self> {
         let val = match pair.as_rule() {
             Rule::val => Val::try_from_pair(pair.first_inner()?)?,
-            Rule::num => Val::ValLit(pair_to_float(pair)?),
+            Rule::num => Val::ValLit(pair_to_float(pair)?, None),
+            Rule::lit => {
+                let spelling = pair.as_str().to_string();
+                let inner = pair.first_inner()?;
+                let x = match inner.as_rule() {
+                    Rule::hash => hash(inner.first_inner()?.as_str()) as f64,
+                    Rule::hex => pair_to_radix(inner.as_str(), 16)?,
+                    Rule::bin => pair_to_radix(inner.as_str(), 2)?,
+                    _ => return Err(AstError::Val(format!("{:?}", inner))),
+                };
+                Val::ValLit(x, Some(spelling))
+            }
             Rule::mem => Val::ValMem(Mem::try_from_pair(pair)?),
             _ => return Err(AstError::Val(format!("{:?}", pair))),
         };
@@ -37,7 +69,8 @@ impl Node for Val {
 impl Display for Val {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Val::ValLit(x) => write!(fmt, "{}", x),
+            Val::ValLit(_, Some(s)) => write!(fmt, "{}", s),
+            Val::ValLit(x, None) => write!(fmt, "{}", x),
             Val::ValMem(m) => write!(fmt, "{}", m),
         }
     }
@@ -0,0 +1,454 @@
+//! Control flow graph of MIPS programs.
+//!
+//! A [`Cfg`] splits a program into basic blocks (runs of lines entered only at the first line and
+//! left only at the last) joined by edges for:
+//!
+//! - falling through to the next line,
+//! - absolute jumps and branches (`j`, `beq`, ...) to labels, defines and literal lines,
+//! - relative jumps and branches (`jr`, `brne`, ...) by literal or defined offsets,
+//! - calls (`jal`, `beqal`, ...), and returns (`j ra`) to the line after each call.
+//!
+//! Halting (`hcf`) leaves the program. Any other jump target (e.g. `j r0`) is computed at
+//! runtime, which is recorded on the block rather than as an edge.
+//!
+//! Lines are indexed from 0 (as jump targets are, rounded half to even like the game does), and
+//! blank lines are part of the blocks around them, since they take a step to execute all the
+//! same.
+use std::collections::HashMap;
+use std::ops::Range;
+use std::{fmt, fmt::Display};
+
+use util::round_index;
+
+use crate::ast::nodes::{Arg, Expr, Flow, Func, Mem, Program, Val};
+
+/// Index of the return address register (`ra`).
+const RA: usize = 17;
+
+/// Kind of a control flow edge.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum EdgeKind {
+    /// Fall through to the next line.
+    Next,
+    /// Conditional branch taken.
+    Branch,
+    /// Unconditional jump.
+    Jump,
+    /// Jump or branch storing the next line in `ra`.
+    Call,
+    /// Jump back to the line after a call.
+    Return,
+}
+
+impl Display for EdgeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EdgeKind::Next => f.write_str("next"),
+            EdgeKind::Branch => f.write_str("branch"),
+            EdgeKind::Jump => f.write_str("jump"),
+            EdgeKind::Call => f.write_str("call"),
+            EdgeKind::Return => f.write_str("return"),
+        }
+    }
+}
+
+/// Control flow edge between two blocks (by index).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+/// Basic block.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Block {
+    /// Lines of the block.
+    pub lines: Range<usize>,
+    /// Does the block end in a jump to a computed target.
+    pub computed: bool,
+    /// Can the block leave the program (by running past the last line, or jumping outside it).
+    pub exits: bool,
+}
+
+/// Natural loop (a cycle entered only through its header).
+#[derive(Clone, PartialEq, Debug)]
+pub struct Loop {
+    /// Block through which the loop is entered.
+    pub header: usize,
+    /// Blocks of the loop (including the header), in order.
+    pub blocks: Vec<usize>,
+}
+
+/// Target of a jump.
+enum Target {
+    Line(f64),
+    Return,
+    Computed,
+}
+
+/// Control flow graph.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Cfg {
+    code: Vec<Option<String>>,
+    blocks: Vec<Block>,
+    edges: Vec<Edge>,
+}
+
+impl Cfg {
+    /// Build the control flow graph of a program.
+    pub fn new(program: &Program) -> Self {
+        let n_lines = program.iter().map(|(i, _)| i + 1).max().unwrap_or(0);
+        let mut exprs: Vec<Option<&Expr>> = vec![None; n_lines];
+        for (i, expr) in program.iter() {
+            exprs[*i] = Some(expr);
+        }
+
+        // Values of the names a jump can target
+        let mut names: HashMap<&str, f64> = HashMap::new();
+        for (i, Expr(func, args)) in program.iter() {
+            match (func, args.as_slice()) {
+                (Func::Label, [Arg::ArgToken(name)]) => {
+                    names.entry(name).or_insert(*i as f64);
+                }
+                (Func::Define, [Arg::ArgToken(name), Arg::ArgVal(val)]) => {
+                    if let Some(x) = val.lit() {
+                        names.entry(name).or_insert(x);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let flows: Vec<Flow> = exprs
+            .iter()
+            .map(|expr| expr.map_or(Flow::Next, |Expr(func, _)| func.flow()))
+            .collect();
+        let targets: Vec<Option<Target>> = exprs
+            .iter()
+            .zip(flows.iter())
+            .enumerate()
+            .map(|(i, (expr, flow))| match expr {
+                Some(Expr(_, args)) if flow.jumps() => Some(target(i, *flow, args, &names)),
+                _ => None,
+            })
+            .collect();
+        let calls: Vec<usize> = (0..n_lines).filter(|i| flows[*i].stores_ra()).collect();
+        let halts: Vec<bool> = exprs
+            .iter()
+            .map(|expr| matches!(expr, Some(Expr(Func::Hcf, _))))
+            .collect();
+
+        // Blocks start at the first line, after each jump or halt, and at each target
+        let in_program = |x: f64| x >= 0.0 && x < n_lines as f64;
+        let mut leaders = vec![false; n_lines];
+        for (i, target) in targets.iter().enumerate() {
+            if (target.is_some() || halts[i]) && i + 1 < n_lines {
+                leaders[i + 1] = true;
+            }
+            if let Some(Target::Line(x)) = target {
+                if in_program(*x) {
+                    leaders[*x as usize] = true;
+                }
+            }
+        }
+        if n_lines > 0 {
+            leaders[0] = true;
+        }
+        let starts: Vec<usize> = (0..n_lines).filter(|i| leaders[*i]).collect();
+        let mut block_of = vec![0; n_lines];
+        let mut blocks = Vec::new();
+        for (b, start) in starts.iter().enumerate() {
+            let end = starts.get(b + 1).copied().unwrap_or(n_lines);
+            block_of[*start..end].fill(b);
+            blocks.push(Block {
+                lines: *start..end,
+                computed: false,
+                exits: false,
+            });
+        }
+
+        let mut edges = Vec::new();
+        for (b, block) in blocks.iter_mut().enumerate() {
+            let i = block.lines.end - 1;
+            let flow = flows[i];
+            if halts[i] {
+                block.exits = true;
+                continue;
+            }
+            if !matches!(flow, Flow::Jump | Flow::JumpAl | Flow::JumpRel) {
+                match block_of.get(i + 1) {
+                    Some(to) => edges.push(Edge {
+                        from: b,
+                        to: *to,
+                        kind: EdgeKind::Next,
+                    }),
+                    None => block.exits = true,
+                }
+            }
+            let kind = match flow {
+                Flow::Next => continue,
+                Flow::Branch | Flow::BranchRel => EdgeKind::Branch,
+                Flow::Jump | Flow::JumpRel => EdgeKind::Jump,
+                Flow::BranchAl | Flow::JumpAl => EdgeKind::Call,
+            };
+            match &targets[i] {
+                Some(Target::Line(x)) if in_program(*x) => edges.push(Edge {
+                    from: b,
+                    to: block_of[*x as usize],
+                    kind,
+                }),
+                Some(Target::Line(_)) => block.exits = true,
+                // Without calls, `ra` holds whatever the program put there
+                Some(Target::Return) if !calls.is_empty() => {
+                    for call in calls.iter() {
+                        match block_of.get(call + 1) {
+                            Some(to) => edges.push(Edge {
+                                from: b,
+                                to: *to,
+                                kind: EdgeKind::Return,
+                            }),
+                            None => block.exits = true,
+                        }
+                    }
+                }
+                _ => block.computed = true,
+            }
+        }
+
+        let code = exprs.iter().map(|expr| expr.map(Expr::to_string)).collect();
+        Self {
+            code,
+            blocks,
+            edges,
+        }
+    }
+
+    /// Basic blocks, in line order (the first being the entry).
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    /// Edges between blocks.
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// Index of the block containing a line.
+    pub fn block_of(&self, line: usize) -> Option<usize> {
+        self.blocks.iter().position(|b| b.lines.contains(&line))
+    }
+
+    /// Blocks that can follow a block.
+    pub fn successors(&self, block: usize) -> Vec<usize> {
+        let mut successors = Vec::new();
+        for edge in self.edges.iter().filter(|e| e.from == block) {
+            if !successors.contains(&edge.to) {
+                successors.push(edge.to);
+            }
+        }
+        successors
+    }
+
+    /// Blocks that can precede a block.
+    pub fn predecessors(&self, block: usize) -> Vec<usize> {
+        let mut predecessors = Vec::new();
+        for edge in self.edges.iter().filter(|e| e.to == block) {
+            if !predecessors.contains(&edge.from) {
+                predecessors.push(edge.from);
+            }
+        }
+        predecessors
+    }
+
+    /// Predecessors of each block within its procedure
+    /// (stepping over calls to the line after them, and leaving out returns).
+    fn local_predecessors(&self) -> Vec<Vec<usize>> {
+        let n = self.blocks.len();
+        let mut predecessors = vec![Vec::new(); n];
+        for edge in self.edges.iter() {
+            let (from, to) = match edge.kind {
+                EdgeKind::Return => continue,
+                // The block after a call starts at the line after it
+                EdgeKind::Call if edge.from + 1 < n => (edge.from, edge.from + 1),
+                EdgeKind::Call => continue,
+                _ => (edge.from, edge.to),
+            };
+            if !predecessors[to].contains(&from) {
+                predecessors[to].push(from);
+            }
+        }
+        predecessors
+    }
+
+    /// Dominators of each block within its procedure
+    /// (`None` for blocks unreachable from the entry or any call target).
+    fn dominators(&self, predecessors: &[Vec<usize>]) -> Vec<Option<Vec<bool>>> {
+        let n = self.blocks.len();
+        let mut roots = vec![false; n];
+        if n > 0 {
+            roots[0] = true;
+        }
+        for edge in self.edges.iter().filter(|e| e.kind == EdgeKind::Call) {
+            roots[edge.to] = true;
+        }
+        let mut doms: Vec<Option<Vec<bool>>> = (0..n)
+            .map(|b| {
+                roots[b].then(|| {
+                    let mut dom = vec![false; n];
+                    dom[b] = true;
+                    dom
+                })
+            })
+            .collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for b in (0..n).filter(|b| !roots[*b]) {
+                let mut dom: Option<Vec<bool>> = None;
+                for p in predecessors[b].iter() {
+                    if let Some(p_dom) = &doms[*p] {
+                        dom = Some(match dom {
+                            Some(dom) => dom.iter().zip(p_dom).map(|(x, y)| *x && *y).collect(),
+                            None => p_dom.clone(),
+                        });
+                    }
+                }
+                if let Some(dom) = dom.as_mut() {
+                    dom[b] = true;
+                }
+                if dom != doms[b] {
+                    doms[b] = dom;
+                    changed = true;
+                }
+            }
+        }
+        doms
+    }
+
+    /// Natural loops, by header.
+    ///
+    /// Loops are found within each procedure (the program entry, and each call target),
+    /// with calls stepping over to the line after them, so that a subroutine called from
+    /// several places does not make a loop of its callers.
+    /// Loops sharing a header are merged, and cycles entered at more than one block
+    /// (which only arise from jumps into the middle of a loop) are not loops.
+    pub fn loops(&self) -> Vec<Loop> {
+        let predecessors = self.local_predecessors();
+        let doms = self.dominators(&predecessors);
+        let mut bodies: Vec<Option<Vec<bool>>> = vec![None; self.blocks.len()];
+        for (header, latches) in predecessors.iter().enumerate() {
+            for latch in latches.iter() {
+                if !doms[*latch].as_ref().is_some_and(|dom| dom[header]) {
+                    continue;
+                }
+                let body = bodies[header].get_or_insert_with(|| {
+                    let mut body = vec![false; self.blocks.len()];
+                    body[header] = true;
+                    body
+                });
+                let mut stack = vec![*latch];
+                while let Some(b) = stack.pop() {
+                    if body[b] || doms[b].is_none() {
+                        continue;
+                    }
+                    body[b] = true;
+                    stack.extend(predecessors[b].iter().copied());
+                }
+            }
+        }
+        bodies
+            .into_iter()
+            .enumerate()
+            .filter_map(|(header, body)| {
+                body.map(|body| Loop {
+                    header,
+                    blocks: (0..body.len()).filter(|b| body[*b]).collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// Graphviz DOT representation of the graph.
+    ///
+    /// Blocks are labelled with their code, loop headers are drawn with a double border,
+    /// and computed jumps and exits from the program lead to their own nodes.
+    pub fn to_dot(&self) -> String {
+        let headers: Vec<usize> = self.loops().iter().map(|l| l.header).collect();
+        let mut dot = String::from("digraph cfg {\n");
+        dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        for (b, block) in self.blocks.iter().enumerate() {
+            let mut label = String::new();
+            for i in block.lines.clone() {
+                if let Some(code) = &self.code[i] {
+                    label.push_str(&format!("{}: {}\\l", i, escape(code)));
+                }
+            }
+            if label.is_empty() {
+                label = format!("{}:\\l", block.lines.start);
+            }
+            let peripheries = if headers.contains(&b) {
+                ", peripheries=2"
+            } else {
+                ""
+            };
+            dot.push_str(&format!(
+                "    b{} [label=\"{}\"{}];\n",
+                b, label, peripheries
+            ));
+        }
+        if self.blocks.iter().any(|b| b.exits) {
+            dot.push_str("    exit [shape=doublecircle, label=\"exit\"];\n");
+        }
+        if self.blocks.iter().any(|b| b.computed) {
+            dot.push_str("    computed [shape=diamond, label=\"?\"];\n");
+        }
+        for edge in self.edges.iter() {
+            let style = match edge.kind {
+                EdgeKind::Next | EdgeKind::Jump => "",
+                EdgeKind::Branch => " [label=\"branch\"]",
+                EdgeKind::Call => " [label=\"call\", style=dashed]",
+                EdgeKind::Return => " [label=\"return\", style=dotted]",
+            };
+            dot.push_str(&format!("    b{} -> b{}{};\n", edge.from, edge.to, style));
+        }
+        for (b, block) in self.blocks.iter().enumerate() {
+            if block.exits {
+                dot.push_str(&format!("    b{} -> exit;\n", b));
+            }
+            if block.computed {
+                dot.push_str(&format!("    b{} -> computed [style=dashed];\n", b));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Target of the jump on line `i`.
+fn target(i: usize, flow: Flow, args: &[Arg], names: &HashMap<&str, f64>) -> Target {
+    let val = match args.last().map(Arg::val) {
+        Some(Ok(val)) => val,
+        _ => return Target::Computed,
+    };
+    let x = match &val {
+        Val::ValMem(Mem::MemAlias(name)) if name == "ra" && !flow.is_relative() => {
+            return Target::Return
+        }
+        Val::ValMem(Mem::MemLit(RA, 0)) if !flow.is_relative() => return Target::Return,
+        Val::ValMem(Mem::MemAlias(name)) => match names.get(name.as_str()) {
+            Some(x) => *x,
+            None => return Target::Computed,
+        },
+        Val::ValMem(_) => return Target::Computed,
+        _ => val.lit().unwrap_or_default(),
+    };
+    // Rounded as by the simulator with the game's semantics (the default)
+    let x = if flow.is_relative() { i as f64 + x } else { x };
+    Target::Line(round_index(x))
+}
+
+/// Escape a string for a quoted DOT label.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,264 @@
+//! Static semantic checks of MIPS programs.
+//!
+//! Many mistakes in a program that parses only surface when simulated (e.g. an unset alias or an
+//! out of bounds register). The [`Checker`] finds these before simulation:
+//!
+//! - undefined labels and aliases,
+//! - aliases used as the wrong kind (e.g. a device alias in a value position),
+//! - register indices out of range (`r0`-`r17`, `d0`-`d5` by default),
+//! - unreachable code after unconditional jumps,
+//! - duplicate labels, and
+//! - relative jumps that leave the program.
+use std::collections::{HashMap, HashSet};
+use std::{fmt, fmt::Display};
+
+use crate::ast::nodes::{Arg, ArgKind, Dev, Expr, Func, Mem, Program, Val};
+use crate::diagnostic::{tokenize_columns, Diagnostic};
+
+/// Default number of memory registers (`r0`-`r17`).
+pub const MEM_SIZE: usize = 18;
+/// Default number of device registers (`d0`-`d5`).
+pub const DEV_SIZE: usize = 6;
+
+/// Kind of a defined name.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum DefKind {
+    Mem,
+    Dev,
+    Define,
+    Label,
+}
+
+impl Display for DefKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DefKind::Mem => f.write_str("memory alias"),
+            DefKind::Dev => f.write_str("device alias"),
+            DefKind::Define => f.write_str("define"),
+            DefKind::Label => f.write_str("label"),
+        }
+    }
+}
+
+/// Static semantic checker.
+#[derive(Clone, Debug)]
+pub struct Checker {
+    mem_size: usize,
+    dev_size: usize,
+}
+
+impl Default for Checker {
+    fn default() -> Self {
+        Self {
+            mem_size: MEM_SIZE,
+            dev_size: DEV_SIZE,
+        }
+    }
+}
+
+impl Checker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder helper to set the number of memory registers.
+    pub fn with_mem_size(mut self, mem_size: usize) -> Self {
+        self.mem_size = mem_size;
+        self
+    }
+
+    /// Builder helper to set the number of device registers.
+    pub fn with_dev_size(mut self, dev_size: usize) -> Self {
+        self.dev_size = dev_size;
+        self
+    }
+
+    /// Check a program, returning diagnostics located by line (without column spans).
+    pub fn check(&self, program: &Program) -> Vec<Diagnostic> {
+        let n_lines = program.iter().map(|(i, _)| i + 1).max().unwrap_or(0);
+        self.check_lines(program, n_lines)
+    }
+
+    /// Check a program from source, returning diagnostics with column spans.
+    ///
+    /// Only the lines that parse are checked
+    /// (see [`Program::parse_lossy`] for the diagnostics of those that do not).
+    pub fn check_source<S: AsRef<str>>(&self, source: &S) -> Vec<Diagnostic> {
+        let source = source.as_ref();
+        let program = Program::parse_lossy(&source).program();
+        let lines: Vec<&str> = source.lines().collect();
+        self.check_lines(&program, lines.len())
+            .into_iter()
+            .map(|d| match (d.line, &d.token) {
+                (Some(i), Some(token)) => {
+                    let span = tokenize_columns(lines[i])
+                        .into_iter()
+                        .find(|(_, t)| t == token || t.strip_suffix(':') == Some(token))
+                        .map(|(span, _)| span);
+                    match span {
+                        Some(span) => d.with_span(i, span),
+                        None => d,
+                    }
+                }
+                // Diagnostics without a token span the whole line
+                (Some(i), None) => {
+                    let code = lines[i].split('#').next().unwrap_or("").trim_end();
+                    d.with_span(i, 0..code.chars().count())
+                }
+                _ => d,
+            })
+            .collect()
+    }
+
+    fn check_lines(&self, program: &Program, n_lines: usize) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        // Definitions by name, and duplicate labels
+        let mut defs: HashMap<&str, Vec<DefKind>> = HashMap::new();
+        defs.insert("sp", vec![DefKind::Mem]);
+        defs.insert("ra", vec![DefKind::Mem]);
+        defs.insert("db", vec![DefKind::Dev]);
+        let mut labels: HashMap<&str, usize> = HashMap::new();
+        for (i, Expr(func, args)) in program.iter() {
+            let (name, kind) = match (func, args.as_slice()) {
+                (Func::Label, [Arg::ArgToken(name)]) => {
+                    if let Some(first) = labels.get(name.as_str()) {
+                        diagnostics.push(
+                            Diagnostic::error(format!(
+                                "label `{}` is already defined on line {}",
+                                name,
+                                first + 1
+                            ))
+                            .with_span(*i, 0..0)
+                            .with_token(name),
+                        );
+                    } else {
+                        labels.insert(name, *i);
+                    }
+                    (name, DefKind::Label)
+                }
+                (Func::Define, [Arg::ArgToken(name), _]) => (name, DefKind::Define),
+                (Func::Alias, [Arg::ArgToken(name), Arg::ArgMem(_)]) => (name, DefKind::Mem),
+                (Func::Alias, [Arg::ArgToken(name), Arg::ArgDev(_)]) => (name, DefKind::Dev),
+                _ => continue,
+            };
+            defs.entry(name).or_default().push(kind);
+        }
+
+        // Literal relative jump targets
+        let mut targets = HashSet::new();
+        for (i, Expr(func, args)) in program.iter() {
+            if !func.flow().is_relative() {
+                continue;
+            }
+            let val = match args.last().map(Arg::val) {
+                Some(Ok(val)) => val,
+                _ => continue,
+            };
+            if let Some(n) = val.lit() {
+                let target = *i as f64 + n.trunc();
+                if target < 0.0 || target >= n_lines as f64 {
+                    diagnostics.push(
+                        Diagnostic::error(format!(
+                            "relative jump by {} leaves the program (to line {})",
+                            n,
+                            target + 1.0
+                        ))
+                        .with_span(*i, 0..0)
+                        .with_token(val.to_string()),
+                    );
+                } else {
+                    targets.insert(target as usize);
+                }
+            }
+        }
+
+        // Arguments
+        for (i, Expr(func, args)) in program.iter() {
+            for (kind, arg) in func.signature().iter().zip(args.iter()) {
+                self.check_arg(*i, *kind, arg, &defs, &mut diagnostics);
+            }
+        }
+
+        // Unreachable code
+        let mut jump: Option<usize> = None;
+        for (i, Expr(func, _)) in program.iter() {
+            if *func == Func::Label || targets.contains(i) {
+                jump = None;
+            } else if let Some(j) = jump.take() {
+                diagnostics.push(
+                    Diagnostic::warning(format!(
+                        "unreachable code after unconditional jump on line {}",
+                        j + 1
+                    ))
+                    .with_span(*i, 0..0),
+                );
+            }
+            if func.flow().is_unconditional() {
+                jump = Some(*i);
+            }
+        }
+
+        diagnostics.sort_by_key(|d| d.line);
+        diagnostics
+    }
+
+    fn check_arg(
+        &self,
+        i: usize,
+        kind: ArgKind,
+        arg: &Arg,
+        defs: &HashMap<&str, Vec<DefKind>>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let (name, allowed): (&String, &[DefKind]) = match arg {
+            Arg::ArgMem(Mem::MemLit(r, _)) | Arg::ArgVal(Val::ValMem(Mem::MemLit(r, _))) => {
+                if *r >= self.mem_size {
+                    diagnostics.push(self.out_of_range(i, arg, "r", self.mem_size));
+                }
+                return;
+            }
+            Arg::ArgDev(Dev::DevLit(d, j)) => {
+                if *j == 0 && *d >= self.dev_size {
+                    diagnostics.push(self.out_of_range(i, arg, "d", self.dev_size));
+                } else if *j > 0 && *d >= self.mem_size {
+                    diagnostics.push(self.out_of_range(i, arg, "r", self.mem_size));
+                }
+                return;
+            }
+            Arg::ArgMem(Mem::MemAlias(a)) => (a, &[DefKind::Mem]),
+            Arg::ArgDev(Dev::DevAlias(a)) => (a, &[DefKind::Dev]),
+            Arg::ArgVal(Val::ValMem(Mem::MemAlias(a))) => {
+                (a, &[DefKind::Mem, DefKind::Define, DefKind::Label])
+            }
+            _ => return,
+        };
+        match defs.get(name.as_str()) {
+            None => diagnostics.push(
+                Diagnostic::error(format!("`{}` is not defined", name))
+                    .with_span(i, 0..0)
+                    .with_token(name),
+            ),
+            Some(kinds) if !kinds.iter().any(|k| allowed.contains(k)) => diagnostics.push(
+                Diagnostic::error(format!("{} `{}` used as {}", kinds[0], name, kind))
+                    .with_span(i, 0..0)
+                    .with_token(name)
+                    .with_expected(format!("expected {}", kind)),
+            ),
+            _ => {}
+        }
+    }
+
+    fn out_of_range(&self, i: usize, arg: &Arg, prefix: &str, size: usize) -> Diagnostic {
+        let token = arg.to_string();
+        Diagnostic::error(format!(
+            "register {} is out of range ({}0-{}{})",
+            token,
+            prefix,
+            prefix,
+            size - 1
+        ))
+        .with_span(i, 0..0)
+        .with_token(token)
+    }
+}
@@ -0,0 +1,283 @@
+//! Lossless concrete syntax tree (CST) of MIPS programs.
+//!
+//! The AST ([`Program`]) keeps only the expressions of a program, so printing it drops comments
+//! and blank lines and normalizes the spelling of numbers (e.g. `1e3` prints as `1000`). A [`Cst`]
+//! instead keeps every byte of the source: each line holds its tokens (with the whitespace before
+//! them), its comment, any trailing whitespace and its line ending, so that printing it gives back
+//! the source exactly. Each line also holds its parsed expression, so tools can inspect a script
+//! through the AST and edit it (e.g. with [`Cst::set_code`]) without destroying comments.
+//!
+//! ```
+//! use mips_parser::cst::Cst;
+//!
+//! let source = "move r0 1e3 # one thousand\n";
+//! let mut cst = Cst::parse(&source);
+//! assert_eq!(cst.to_string(), source);
+//!
+//! cst.set_code(0, "move r0 2e3");
+//! assert_eq!(cst.to_string(), "move r0 2e3 # one thousand\n");
+//! ```
+use std::ops::Range;
+use std::{fmt, fmt::Display};
+
+use crate::ast::nodes::{Expr, Line, Program};
+use crate::ast::Node;
+use crate::diagnostic::{comment_start, diagnose_line, tokenize, Diagnostic};
+
+/// Token of a line (an instruction, argument, label or comment) and the whitespace before it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CstToken {
+    /// Whitespace before the token.
+    pub leading: String,
+    /// Token as spelled in the source.
+    pub text: String,
+    /// Byte span of the token in the source.
+    pub span: Range<usize>,
+}
+
+impl Display for CstToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.leading, self.text)
+    }
+}
+
+/// Line of a concrete syntax tree.
+///
+/// A line prints as its tokens, then its comment, then its trailing whitespace and line ending.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CstLine {
+    /// Index of the line.
+    pub index: usize,
+    /// Byte span of the line in the source (without the line ending).
+    pub span: Range<usize>,
+    /// Tokens of the code (the instruction then its arguments, or the label).
+    pub tokens: Vec<CstToken>,
+    /// Comment (including the `#`), if any.
+    pub comment: Option<CstToken>,
+    /// Whitespace at the end of a line without a comment.
+    pub trailing: String,
+    /// Line ending (`\n`, `\r\n`, or empty for a last line without one).
+    pub newline: String,
+    /// Parsed expression (`None` for blank and invalid lines).
+    pub expr: Option<Expr>,
+    /// Diagnostic of an invalid line.
+    pub diagnostic: Option<Diagnostic>,
+}
+
+impl CstLine {
+    /// Parse a line (including its line ending, if any) starting at a byte offset in the source.
+    fn parse(index: usize, offset: usize, raw: &str) -> Self {
+        let (text, newline) = if let Some(text) = raw.strip_suffix("\r\n") {
+            (text, "\r\n")
+        } else if let Some(text) = raw.strip_suffix('\n') {
+            (text, "\n")
+        } else {
+            (raw, "")
+        };
+
+        let mut tokens = Vec::new();
+        let mut end = 0;
+        for (span, token) in tokenize(text) {
+            tokens.push(CstToken {
+                leading: text[end..span.start].to_string(),
+                text: token.to_string(),
+                span: offset + span.start..offset + span.end,
+            });
+            end = span.end;
+        }
+        let (comment, trailing) = match comment_start(text) {
+            Some(start) => {
+                let comment = CstToken {
+                    leading: text[end..start].to_string(),
+                    text: text[start..].to_string(),
+                    span: offset + start..offset + text.len(),
+                };
+                (Some(comment), String::new())
+            }
+            None => (None, text[end..].to_string()),
+        };
+
+        let diagnostic = diagnose_line(index, text);
+        let expr = match diagnostic {
+            None => Line::try_from_str(&text).ok().flatten(),
+            Some(_) => None,
+        };
+        Self {
+            index,
+            span: offset..offset + text.len(),
+            tokens,
+            comment,
+            trailing,
+            newline: newline.to_string(),
+            expr,
+            diagnostic,
+        }
+    }
+
+    /// Indentation of the line (the whitespace before the first token).
+    pub fn indent(&self) -> &str {
+        self.tokens.first().map_or("", |t| t.leading.as_str())
+    }
+
+    /// Code of the line as spelled in the source (without indentation or comment).
+    pub fn code(&self) -> String {
+        let mut tokens = self.tokens.iter();
+        let mut code = tokens.next().map(|t| t.text.clone()).unwrap_or_default();
+        for token in tokens {
+            code += &token.to_string();
+        }
+        code
+    }
+
+    /// Is the line blank (or a comment only).
+    pub fn is_blank(&self) -> bool {
+        self.tokens.is_empty()
+    }
+}
+
+impl Display for CstLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for token in self.tokens.iter() {
+            write!(f, "{}", token)?;
+        }
+        if let Some(comment) = &self.comment {
+            write!(f, "{}", comment)?;
+        }
+        write!(f, "{}{}", self.trailing, self.newline)
+    }
+}
+
+/// Lossless concrete syntax tree of a program.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Cst {
+    /// One node per source line.
+    pub lines: Vec<CstLine>,
+}
+
+impl Cst {
+    /// Parse a program (never fails; invalid lines hold their diagnostics).
+    pub fn parse<S: AsRef<str>>(source: &S) -> Self {
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for (i, raw) in source.as_ref().split_inclusive('\n').enumerate() {
+            lines.push(CstLine::parse(i, offset, raw));
+            offset += raw.len();
+        }
+        Self { lines }
+    }
+
+    /// Parse the printed tree again, e.g. after editing the nodes directly.
+    ///
+    /// Updates the line indices, spans, expressions and diagnostics.
+    pub fn reparse(&mut self) {
+        *self = Self::parse(&self.to_string());
+    }
+
+    /// Iterator over the lines.
+    pub fn iter(&self) -> impl Iterator<Item = &CstLine> {
+        self.lines.iter()
+    }
+
+    /// Were all lines valid.
+    pub fn is_ok(&self) -> bool {
+        self.lines.iter().all(|line| line.diagnostic.is_none())
+    }
+
+    /// Diagnostics of all invalid lines.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.lines
+            .iter()
+            .filter_map(|line| line.diagnostic.clone())
+            .collect()
+    }
+
+    /// Program of the valid expressions (invalid lines are left out).
+    pub fn program(&self) -> Program {
+        let expressions = self
+            .lines
+            .iter()
+            .filter_map(|line| line.expr.clone().map(|expr| (line.index, expr)))
+            .collect();
+        Program(expressions)
+    }
+
+    /// Line ending used by the program (that of the first line, `\n` by default).
+    pub(crate) fn newline(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.newline.as_str())
+            .find(|newline| !newline.is_empty())
+            .unwrap_or("\n")
+            .to_string()
+    }
+
+    /// Replace the code of a line, keeping its indentation and comment.
+    pub fn set_code(&mut self, i: usize, code: &str) {
+        let line = &mut self.lines[i];
+        let indent = line.indent().to_string();
+        line.tokens = vec![CstToken {
+            leading: indent,
+            text: code.to_string(),
+            span: 0..0,
+        }];
+        match &mut line.comment {
+            Some(comment) if comment.leading.is_empty() && !code.is_empty() => {
+                comment.leading = " ".to_string()
+            }
+            Some(_) => {}
+            None => line.trailing.clear(),
+        }
+        self.reparse();
+    }
+
+    /// Replace (or remove) the comment of a line, given its text without the `#`.
+    pub fn set_comment(&mut self, i: usize, comment: Option<&str>) {
+        let line = &mut self.lines[i];
+        line.comment = comment.map(|text| CstToken {
+            leading: match &line.comment {
+                Some(comment) => comment.leading.clone(),
+                None if line.tokens.is_empty() => String::new(),
+                None => " ".to_string(),
+            },
+            text: format!("# {}", text),
+            span: 0..0,
+        });
+        line.trailing.clear();
+        self.reparse();
+    }
+
+    /// Insert a line (given without its line ending) before line `i`.
+    pub fn insert_line(&mut self, i: usize, text: &str) {
+        let newline = self.newline();
+        let mut line = CstLine::parse(i, 0, text);
+        let n = self.lines.len();
+        match self.lines.last_mut() {
+            // Keep a missing line ending at the end of the program
+            Some(last) if i == n && last.newline.is_empty() => last.newline = newline,
+            _ => line.newline = newline,
+        }
+        self.lines.insert(i, line);
+        self.reparse();
+    }
+
+    /// Remove line `i`.
+    pub fn remove_line(&mut self, i: usize) {
+        let line = self.lines.remove(i);
+        if line.newline.is_empty() {
+            // Keep a missing line ending at the end of the program
+            if let Some(last) = self.lines.last_mut() {
+                last.newline.clear();
+            }
+        }
+        self.reparse();
+    }
+}
+
+impl Display for Cst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in self.lines.iter() {
+            write!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
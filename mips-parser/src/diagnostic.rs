@@ -0,0 +1,245 @@
+//! Source-located diagnostics for MIPS parse errors.
+//!
+//! Pest reports a failing program at the first position it could not match, which for a
+//! misplaced argument is often the start of the line (or the program). Instead, a failing line is
+//! checked against the [signature](Func::signature) of its instruction, so that the diagnostic
+//! points at the offending argument, e.g. "`add` expects mem, val, val; found device register d0".
+use std::ops::Range;
+use std::{fmt, fmt::Display};
+
+use itertools::join;
+use pest::Parser;
+
+pub use util::diagnostic::{Diagnostic, Severity};
+
+use crate::ast::nodes::{ArgKind, Func, Line};
+use crate::ast::{FirstInner, Node};
+use crate::{MipsParser, MipsParserError, Rule};
+
+/// Kind of an argument token, as found in the source.
+#[derive(Clone, PartialEq, Debug)]
+enum Found<'a> {
+    MemLit(&'a str),
+    DevLit(&'a str),
+    Num(&'a str),
+    Alias(&'a str),
+    Invalid(&'a str),
+}
+
+/// Does a rule match the whole of a string.
+fn matches_rule(rule: Rule, s: &str) -> bool {
+    MipsParser::parse(rule, s)
+        .ok()
+        .and_then(|mut pairs| pairs.next())
+        .map(|pair| pair.as_span().end() == s.len())
+        .unwrap_or(false)
+}
+
+impl<'a> Found<'a> {
+    fn classify(s: &'a str) -> Self {
+        if matches_rule(Rule::mem_lit, s) {
+            Found::MemLit(s)
+        } else if matches_rule(Rule::dev_lit, s) {
+            Found::DevLit(s)
+        } else if matches_rule(Rule::num, s) || matches_rule(Rule::lit, s) {
+            Found::Num(s)
+        } else if matches_rule(Rule::alias, s) {
+            Found::Alias(s)
+        } else {
+            Found::Invalid(s)
+        }
+    }
+
+    fn matches(&self, kind: ArgKind) -> bool {
+        use Found::*;
+        match kind {
+            ArgKind::Mem => matches!(self, MemLit(_) | Alias(_)),
+            ArgKind::Dev => matches!(self, DevLit(_) | Alias(_)),
+            ArgKind::Val => matches!(self, MemLit(_) | Num(_) | Alias(_)),
+            ArgKind::Tkn => matches!(self, MemLit(_) | DevLit(_) | Alias(_))
+                || matches!(self, Num(s) if matches_rule(Rule::tkn, s)),
+            ArgKind::Reg => matches!(self, MemLit(_) | DevLit(_) | Alias(_)),
+            ArgKind::Num => matches!(self, Num(_)),
+        }
+    }
+}
+
+impl<'a> Display for Found<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Found::MemLit(s) => write!(f, "memory register {}", s),
+            Found::DevLit(s) => write!(f, "device register {}", s),
+            Found::Num(s) => write!(f, "number {}", s),
+            Found::Alias(s) => write!(f, "alias {}", s),
+            Found::Invalid(s) => write!(f, "invalid token `{}`", s),
+        }
+    }
+}
+
+/// Byte offset of the start of the comment of a line, if any (`#` outside of quoted strings).
+pub(crate) fn comment_start(text: &str) -> Option<usize> {
+    let mut quoted = false;
+    text.char_indices()
+        .find(|(_, c)| {
+            quoted ^= *c == '"';
+            *c == '#' && !quoted
+        })
+        .map(|(i, _)| i)
+}
+
+/// Split the code of a line (i.e. without the comment) into tokens with their byte spans.
+///
+/// Quoted strings (e.g. `HASH("Structure Name")`) are kept within a single token.
+pub(crate) fn tokenize(text: &str) -> Vec<(Range<usize>, &str)> {
+    let code_end = comment_start(text).unwrap_or(text.len());
+    let code = &text[..code_end];
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    for (i, c) in code.char_indices().chain(std::iter::once((code.len(), ' '))) {
+        quoted ^= c == '"';
+        match (start, c == ' ' && !quoted) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                tokens.push((s..i, &code[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Character column of a byte offset within a line, as used by [`Diagnostic::span`].
+pub(crate) fn column(text: &str, offset: usize) -> usize {
+    text[..offset].chars().count()
+}
+
+/// Split the code of a line into tokens with their character column spans (see [`tokenize`]).
+pub(crate) fn tokenize_columns(text: &str) -> Vec<(Range<usize>, &str)> {
+    tokenize(text)
+        .into_iter()
+        .map(|(span, token)| (column(text, span.start)..column(text, span.end), token))
+        .collect()
+}
+
+/// Check a failing line against the signature of its instruction.
+fn check_signature(index: usize, text: &str) -> Option<Diagnostic> {
+    let tokens = tokenize_columns(text);
+    let ((name_span, name), args) = tokens.split_first()?;
+    if args.is_empty() && name.ends_with(':') {
+        // Labels have no signature to check against
+        return None;
+    }
+    let func = match Func::try_from_name(name) {
+        Some(func) => func,
+        None => {
+            return Some(
+                Diagnostic::error(format!("unknown instruction `{}`", name))
+                    .with_span(index, name_span.clone())
+                    .with_token(*name)
+                    .with_expected("expected an instruction"),
+            )
+        }
+    };
+    let signature = func.signature();
+    let expects = if signature.is_empty() {
+        format!("`{}` expects no arguments", func)
+    } else {
+        format!("`{}` expects {}", func, join(signature.iter(), ", "))
+    };
+
+    for (kind, (span, token)) in signature.iter().zip(args.iter()) {
+        let found = Found::classify(token);
+        if !found.matches(*kind) {
+            return Some(
+                Diagnostic::error(format!("{}; found {}", expects, found))
+                    .with_span(index, span.clone())
+                    .with_token(*token)
+                    .with_expected(format!("expected {}", kind)),
+            );
+        }
+    }
+
+    let found_n = format!(
+        "found {} argument{}",
+        args.len(),
+        if args.len() == 1 { "" } else { "s" }
+    );
+    if args.len() < signature.len() {
+        let end = args.last().unwrap_or(&tokens[0]).0.end + 1;
+        let kind = signature[args.len()];
+        return Some(
+            Diagnostic::error(format!("{}; {}", expects, found_n))
+                .with_span(index, end..end + 1)
+                .with_expected(format!("expected {}", kind)),
+        );
+    }
+    if args.len() > signature.len() {
+        let extra = &args[signature.len()..];
+        let span = extra[0].0.start..extra[extra.len() - 1].0.end;
+        return Some(
+            Diagnostic::error(format!("{}; {}", expects, found_n))
+                .with_span(index, span)
+                .with_token(extra[0].1)
+                .with_expected("unexpected argument"),
+        );
+    }
+    None
+}
+
+/// Diagnose a single source line (by line index), returning `None` if the line is valid.
+pub fn diagnose_line(index: usize, text: &str) -> Option<Diagnostic> {
+    let text = text.trim_end_matches(['\r', '\n']);
+    // The line rule always matches (a blank line at worst), so check how much was consumed
+    let pair = MipsParser::parse(Rule::line, text)
+        .ok()
+        .and_then(|pairs| pairs.first_inner().ok());
+    if let Some(pair) = pair.filter(|pair| pair.as_span().end() == text.len()) {
+        return Line::try_from_pair(pair).err().map(|e| {
+            Diagnostic::error(e.to_string()).with_span(index, 0..text.chars().count())
+        });
+    }
+    check_signature(index, text).or_else(|| {
+        // Fall back to where Pest fails on the expression
+        let diagnostic = match MipsParser::parse(Rule::expr, text) {
+            Err(e) => Diagnostic::from_pest(&e),
+            Ok(mut pairs) => {
+                let end = pairs
+                    .next_back()
+                    .map(|pair| pair.as_span().end())
+                    .unwrap_or(0);
+                Diagnostic::error("unexpected input")
+                    .with_span(0, column(text, end)..text.chars().count())
+            }
+        };
+        let span = diagnostic.span.clone();
+        Some(diagnostic.with_span(index, span))
+    })
+}
+
+/// Diagnose each source line, returning the diagnostics of all invalid lines.
+pub fn diagnose(source: &str) -> Vec<Diagnostic> {
+    source
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| diagnose_line(i, line))
+        .collect()
+}
+
+impl MipsParserError {
+    /// Source-located diagnostic for this error, given the source that failed to parse.
+    pub fn diagnostic(&self, source: &str) -> Diagnostic {
+        match self {
+            MipsParserError::IOError(e) => Diagnostic::error(e.to_string()),
+            MipsParserError::ParserError(e) => diagnose(source)
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| Diagnostic::from_pest(e)),
+            MipsParserError::AstError(e) => diagnose(source)
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| Diagnostic::error(e.to_string())),
+        }
+    }
+}
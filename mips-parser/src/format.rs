@@ -0,0 +1,445 @@
+//! Canonical formatter of MIPS programs.
+//!
+//! The [`Formatter`] works on the [concrete syntax tree][crate::cst] of a program, so comments and
+//! the original spelling of arguments are kept. It:
+//!
+//! - aligns the arguments of consecutive instructions into columns,
+//! - puts labels, aliases and defines at the start of their lines and indents instructions,
+//! - normalizes comment spacing (`#comment` becomes `# comment`), aligning trailing comments,
+//! - optionally expands (or strips) alias and define usage (see [`Names`]),
+//! - optionally hoists aliases and defines to the top of the program, and
+//! - falls back to single spaces on lines that alignment would push over the column limit.
+//!
+//! Lines that do not parse are kept as they are (without trailing whitespace).
+//!
+//! Since a line's index is its address, lines are only removed or moved when the program does not
+//! depend on them, i.e. when every jump targets a label (or `ra`).
+//!
+//! ```
+//! use mips_parser::format::Formatter;
+//!
+//! let source = "\
+//! alias x r0 #counter
+//! loop:
+//! add x x 1
+//! bne x 100 loop # again
+//! ";
+//! let formatted = "\
+//! alias x r0 # counter
+//! loop:
+//!     add x x   1
+//!     bne x 100 loop # again
+//! ";
+//! assert_eq!(Formatter::new().with_indent(4).format(&source), formatted);
+//! ```
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::nodes::{Arg, Dev, Expr, Func, Mem, Program, Val};
+use crate::cst::{Cst, CstLine};
+use crate::limits::MAX_COLUMNS;
+
+/// Handling of alias and define names.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Names {
+    /// Keep names as they are.
+    Keep,
+    /// Replace uses of names by what they stand for, keeping the declarations.
+    Expand,
+    /// Replace uses of names by what they stand for, removing the declarations.
+    ///
+    /// The lines of removed declarations are left blank (or with their comment) when the
+    /// program depends on line indices.
+    Strip,
+}
+
+/// Kind of a formatted line.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Kind {
+    Blank,
+    Comment,
+    Label,
+    Declaration,
+    Instruction,
+    Invalid,
+}
+
+/// Line being formatted.
+#[derive(Clone, Debug)]
+struct Row {
+    kind: Kind,
+    /// Code tokens (or the whole line, for invalid lines).
+    tokens: Vec<String>,
+    /// Normalized comment.
+    comment: Option<String>,
+}
+
+impl Row {
+    fn new(line: &CstLine) -> Self {
+        let comment = line.comment.as_ref().map(|c| normalize_comment(&c.text));
+        let (kind, tokens) = match &line.expr {
+            _ if line.diagnostic.is_some() => {
+                let text = line.to_string();
+                (Kind::Invalid, vec![text.trim_end().to_string()])
+            }
+            None if comment.is_some() => (Kind::Comment, vec![]),
+            None => (Kind::Blank, vec![]),
+            Some(Expr(func, _)) => {
+                let kind = match func {
+                    Func::Label => Kind::Label,
+                    Func::Alias | Func::Define => Kind::Declaration,
+                    _ => Kind::Instruction,
+                };
+                (kind, line.tokens.iter().map(|t| t.text.clone()).collect())
+            }
+        };
+        Self {
+            kind,
+            tokens,
+            comment: if kind == Kind::Invalid { None } else { comment },
+        }
+    }
+
+    /// Is the row aligned with its neighbours of the same kind.
+    fn is_aligned(&self) -> bool {
+        matches!(self.kind, Kind::Declaration | Kind::Instruction)
+    }
+
+    /// Clear the code of the row, keeping its comment.
+    fn clear(&mut self) {
+        self.tokens.clear();
+        self.kind = match self.comment {
+            Some(_) => Kind::Comment,
+            None => Kind::Blank,
+        };
+    }
+}
+
+/// Normalize the spacing of a comment: `#comment  ` becomes `# comment`.
+fn normalize_comment(text: &str) -> String {
+    let body = text.trim_start_matches('#');
+    let hashes = &text[..text.len() - body.len()];
+    match body.trim() {
+        "" => hashes.to_string(),
+        body => format!("{} {}", hashes, body),
+    }
+}
+
+/// Can lines of the program be removed or moved without changing what it does.
+///
+/// True when every jump targets a label or `ra`.
+fn is_relocatable(program: &Program) -> bool {
+    let labels: HashSet<&str> = program
+        .iter()
+        .filter_map(|(_, Expr(func, args))| match (func, args.as_slice()) {
+            (Func::Label, [Arg::ArgToken(name)]) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    program.iter().all(|(_, Expr(func, args))| {
+        match args.last() {
+            _ if !func.flow().jumps() => true,
+            _ if func.flow().is_relative() => false,
+            Some(Arg::ArgVal(Val::ValMem(Mem::MemAlias(name)))) => {
+                name == "ra" || labels.contains(name.as_str())
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Name used by an argument, if any.
+fn arg_name(arg: &Arg) -> Option<&str> {
+    match arg {
+        Arg::ArgMem(Mem::MemAlias(name))
+        | Arg::ArgDev(Dev::DevAlias(name))
+        | Arg::ArgVal(Val::ValMem(Mem::MemAlias(name))) => Some(name),
+        _ => None,
+    }
+}
+
+/// Canonical formatter.
+#[derive(Clone, Debug)]
+pub struct Formatter {
+    indent: usize,
+    align: bool,
+    align_comments: bool,
+    names: Names,
+    hoist: bool,
+    columns: Option<usize>,
+}
+
+impl Default for Formatter {
+    fn default() -> Self {
+        Self {
+            indent: 0,
+            align: true,
+            align_comments: true,
+            names: Names::Keep,
+            hoist: false,
+            columns: Some(MAX_COLUMNS),
+        }
+    }
+}
+
+impl Formatter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder helper to set the indentation of instructions (in spaces).
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Builder helper to set whether to align arguments into columns.
+    pub fn with_align(mut self, align: bool) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Builder helper to set whether to align trailing comments.
+    pub fn with_align_comments(mut self, align_comments: bool) -> Self {
+        self.align_comments = align_comments;
+        self
+    }
+
+    /// Builder helper to set the handling of alias and define names.
+    pub fn with_names(mut self, names: Names) -> Self {
+        self.names = names;
+        self
+    }
+
+    /// Builder helper to set whether to move aliases and defines to the top of the program.
+    ///
+    /// Only done when the program does not depend on line indices,
+    /// and does not declare any name twice.
+    pub fn with_hoist(mut self, hoist: bool) -> Self {
+        self.hoist = hoist;
+        self
+    }
+
+    /// Builder helper to set the column limit (`None` for no limit).
+    pub fn with_columns(mut self, columns: Option<usize>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Format a program.
+    pub fn format<S: AsRef<str>>(&self, source: &S) -> String {
+        let cst = Cst::parse(source);
+        let program = cst.program();
+        let relocatable = is_relocatable(&program);
+        let mut rows: Vec<Row> = cst.iter().map(Row::new).collect();
+
+        // Declarations by name
+        let mut declarations: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, Expr(func, args)) in program.iter() {
+            if let (Func::Alias | Func::Define | Func::Label, Some(Arg::ArgToken(name))) =
+                (func, args.first())
+            {
+                declarations.entry(name).or_default().push(*i);
+            }
+        }
+
+        if self.names != Names::Keep {
+            self.expand(&cst, &declarations, &mut rows, relocatable);
+        }
+
+        let unique = declarations.values().all(|lines| lines.len() == 1);
+        if self.hoist && relocatable && unique {
+            let (mut hoisted, rest): (Vec<Row>, Vec<Row>) = rows
+                .into_iter()
+                .partition(|row| row.kind == Kind::Declaration);
+            // After the comments heading the program
+            let head = rest
+                .iter()
+                .take_while(|row| row.kind == Kind::Comment)
+                .count();
+            let mut rest = rest.into_iter();
+            rows = rest.by_ref().take(head).collect();
+            rows.append(&mut hoisted);
+            rows.extend(rest);
+        }
+
+        let newline = cst.newline();
+        let mut formatted = self.render(&rows).join(&newline);
+        if cst
+            .lines
+            .last()
+            .is_some_and(|line| !line.newline.is_empty())
+        {
+            formatted += &newline;
+        }
+        formatted
+    }
+
+    /// Replace uses of the names declared once by what they stand for.
+    fn expand(
+        &self,
+        cst: &Cst,
+        declarations: &HashMap<&str, Vec<usize>>,
+        rows: &mut Vec<Row>,
+        relocatable: bool,
+    ) {
+        let mut values: HashMap<&str, String> = HashMap::new();
+        for (name, lines) in declarations.iter() {
+            if let [i] = lines.as_slice() {
+                if rows[*i].kind == Kind::Declaration {
+                    values.insert(name, rows[*i].tokens[2].clone());
+                }
+            }
+        }
+
+        for (line, row) in cst.iter().zip(rows.iter_mut()) {
+            match (&line.expr, row.kind) {
+                (Some(Expr(_, args)), Kind::Instruction) if row.tokens.len() == args.len() + 1 => {
+                    for (arg, token) in args.iter().zip(row.tokens.iter_mut().skip(1)) {
+                        if let Some(value) = arg_name(arg).and_then(|name| values.get(name)) {
+                            *token = value.clone();
+                        }
+                    }
+                }
+                (Some(_), Kind::Declaration)
+                    if self.names == Names::Strip
+                        && values.contains_key(row.tokens[1].as_str()) =>
+                {
+                    row.clear()
+                }
+                _ => {}
+            }
+        }
+
+        if self.names == Names::Strip && relocatable {
+            let mut i = 0;
+            rows.retain(|row| {
+                let keep = row.kind != Kind::Blank || cst.lines[i].expr.is_none();
+                i += 1;
+                keep
+            });
+        }
+    }
+
+    /// Render rows to lines.
+    fn render(&self, rows: &[Row]) -> Vec<String> {
+        let mut lines = Vec::with_capacity(rows.len());
+        let mut start = 0;
+        while start < rows.len() {
+            let kind = rows[start].kind;
+            let end = if rows[start].is_aligned() {
+                start
+                    + rows[start..]
+                        .iter()
+                        .take_while(|row| row.kind == kind)
+                        .count()
+            } else {
+                start + 1
+            };
+            let block = &rows[start..end];
+
+            // Column widths of the block
+            let mut widths: Vec<usize> = Vec::new();
+            if self.align {
+                for row in block.iter() {
+                    for (j, token) in row.tokens.iter().enumerate() {
+                        let n = token.chars().count();
+                        match widths.get_mut(j) {
+                            Some(width) => *width = (*width).max(n),
+                            None => widths.push(n),
+                        }
+                    }
+                }
+            }
+
+            let indent = match kind {
+                Kind::Instruction => " ".repeat(self.indent),
+                // Comment lines are indented as the code that follows
+                Kind::Comment => {
+                    let next = rows[start..].iter().find(|row| row.kind != Kind::Comment);
+                    match next.map(|row| row.kind) {
+                        Some(Kind::Instruction) => " ".repeat(self.indent),
+                        _ => String::new(),
+                    }
+                }
+                _ => String::new(),
+            };
+            let codes: Vec<String> = block
+                .iter()
+                .map(|row| {
+                    let aligned = indent.clone() + &pad(&row.tokens, &widths);
+                    if self.fits(&aligned) {
+                        aligned
+                    } else {
+                        indent.clone() + &row.tokens.join(" ")
+                    }
+                })
+                .collect();
+
+            // Trailing comments are aligned with those on adjacent lines
+            let mut comment_columns = vec![0; block.len()];
+            if self.align_comments {
+                let mut j = 0;
+                while j < block.len() {
+                    let run = block[j..]
+                        .iter()
+                        .take_while(|row| row.comment.is_some())
+                        .count()
+                        .max(1);
+                    let column = codes[j..j + run]
+                        .iter()
+                        .map(|c| c.chars().count())
+                        .max()
+                        .unwrap_or(0);
+                    for c in comment_columns[j..j + run].iter_mut() {
+                        *c = column;
+                    }
+                    j += run;
+                }
+            }
+            for ((row, code), comment_column) in block.iter().zip(codes).zip(comment_columns) {
+                let line = match (&row.comment, row.tokens.is_empty()) {
+                    (None, _) => code,
+                    (Some(comment), true) => code + comment,
+                    (Some(comment), false) => {
+                        let n = code.chars().count();
+                        let aligned = format!(
+                            "{}{} {}",
+                            code,
+                            " ".repeat(comment_column.saturating_sub(n)),
+                            comment
+                        );
+                        if self.fits(&aligned) {
+                            aligned
+                        } else {
+                            format!("{} {}", code, comment)
+                        }
+                    }
+                };
+                lines.push(line);
+            }
+            start = end;
+        }
+        lines
+    }
+
+    /// Does the line fit within the column limit.
+    fn fits(&self, line: &str) -> bool {
+        self.columns.is_none_or(|max| line.chars().count() <= max)
+    }
+}
+
+/// Join tokens, padding each (but the last) to the width of its column.
+fn pad(tokens: &[String], widths: &[usize]) -> String {
+    let mut code = String::new();
+    for (j, token) in tokens.iter().enumerate() {
+        if j > 0 {
+            code.push(' ');
+        }
+        code += token;
+        if j + 1 < tokens.len() {
+            let n = token.chars().count();
+            code += &" ".repeat(widths.get(j).map_or(0, |w| w.saturating_sub(n)));
+        }
+    }
+    code
+}
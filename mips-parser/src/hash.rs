@@ -0,0 +1,19 @@
+//! Stationeers string hashing (as by `HASH("...")`).
+
+/// CRC-32 (IEEE) checksum of bytes.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for b in bytes.iter() {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Hash of a string as by the game, i.e. its CRC-32 as a signed integer.
+pub fn hash(s: &str) -> i32 {
+    crc32(s.as_bytes()) as i32
+}
@@ -24,6 +24,13 @@ use pest_derive::Parser;
 pub struct MipsParser;
 
 pub mod ast;
+pub mod cfg;
+pub mod check;
+pub mod cst;
+pub mod diagnostic;
+pub mod format;
+pub mod hash;
+pub mod limits;
 
 /// MIPS parser error type.
 #[derive(Debug)]
@@ -35,8 +42,16 @@ pub enum MipsParserError {
 
 /// All-in-one module.
 pub mod prelude {
-    pub use crate::ast::nodes::{Arg, Dev, Expr, Func, Mem, Program, Val};
+    pub use crate::ast::nodes::{
+        Arg, Category, Dev, Expr, Flow, Func, FuncInfo, LossyLine, LossyProgram, Mem, Program, Val,
+    };
     pub use crate::ast::{Node, AstError, FirstInner};
+    pub use crate::cfg::{Cfg, EdgeKind};
+    pub use crate::check::Checker;
+    pub use crate::cst::{Cst, CstLine, CstToken};
+    pub use crate::diagnostic::{Diagnostic, Severity};
+    pub use crate::format::{Formatter, Names};
+    pub use crate::limits::Limits;
     pub use crate::{MipsParser, MipsParserError, Rule};
     pub use pest::{iterators::Pair, Parser};
 }
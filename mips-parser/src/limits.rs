@@ -0,0 +1,122 @@
+//! IC10 program limits lint.
+//!
+//! The game rejects programs over 128 lines, with lines over 90 characters,
+//! or over 4096 bytes in total.
+use crate::diagnostic::Diagnostic;
+
+/// Default maximum number of lines.
+pub const MAX_LINES: usize = 128;
+/// Default maximum number of characters per line.
+pub const MAX_COLUMNS: usize = 90;
+/// Default maximum number of bytes.
+pub const MAX_BYTES: usize = 4096;
+
+/// Program limits.
+#[derive(Clone, Debug)]
+pub struct Limits {
+    lines: Option<usize>,
+    columns: Option<usize>,
+    bytes: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            lines: Some(MAX_LINES),
+            columns: Some(MAX_COLUMNS),
+            bytes: Some(MAX_BYTES),
+        }
+    }
+}
+
+impl Limits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder helper to set the line limit (`None` for no limit).
+    pub fn with_lines(mut self, lines: Option<usize>) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    /// Builder helper to set the column limit (`None` for no limit).
+    pub fn with_columns(mut self, columns: Option<usize>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Builder helper to set the byte limit (`None` for no limit).
+    pub fn with_bytes(mut self, bytes: Option<usize>) -> Self {
+        self.bytes = bytes;
+        self
+    }
+
+    /// Check source against the limits, returning an error diagnostic for each violation.
+    pub fn check<S: AsRef<str>>(&self, source: &S) -> Vec<Diagnostic> {
+        let source = source.as_ref();
+        let lines: Vec<&str> = source.lines().collect();
+        let mut diagnostics = Vec::new();
+
+        if let Some(max) = self.columns {
+            for (i, line) in lines.iter().enumerate() {
+                let n = line.chars().count();
+                if n > max {
+                    diagnostics.push(
+                        Diagnostic::error(format!(
+                            "line is {} characters long, the limit is {}",
+                            n, max
+                        ))
+                        .with_span(i, max..n)
+                        .with_expected(format!("beyond column {}", max)),
+                    );
+                }
+            }
+        }
+
+        if let Some(max) = self.lines {
+            if lines.len() > max {
+                let n = lines[max].chars().count();
+                diagnostics.push(
+                    Diagnostic::error(format!(
+                        "program is {} lines long, the limit is {}",
+                        lines.len(),
+                        max
+                    ))
+                    .with_span(max, 0..n)
+                    .with_expected(format!("beyond line {}", max)),
+                );
+            }
+        }
+
+        if let Some(max) = self.bytes {
+            if source.len() > max {
+                let mut diagnostic = Diagnostic::error(format!(
+                    "program is {} bytes long, the limit is {}",
+                    source.len(),
+                    max
+                ));
+                // Point at the line containing the first byte over the limit
+                let mut start = 0;
+                for (i, line) in source.split('\n').enumerate() {
+                    if start + line.len() >= max {
+                        // Snap to the character crossing the limit, which may start before it
+                        let col = line
+                            .char_indices()
+                            .take_while(|(b, c)| start + b + c.len_utf8() <= max)
+                            .count();
+                        let end = line.trim_end_matches('\r').chars().count().max(col + 1);
+                        diagnostic = diagnostic
+                            .with_span(i, col..end)
+                            .with_expected(format!("beyond byte {}", max));
+                        break;
+                    }
+                    start += line.len() + 1;
+                }
+                diagnostics.push(diagnostic);
+            }
+        }
+
+        diagnostics
+    }
+}
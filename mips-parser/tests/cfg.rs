@@ -0,0 +1,220 @@
+use mips_parser::cfg::{Block, Edge, Loop};
+use mips_parser::prelude::*;
+
+fn cfg(source: &str) -> Cfg {
+    Cfg::new(&Program::try_from_str(&source).unwrap())
+}
+
+fn lines(cfg: &Cfg) -> Vec<std::ops::Range<usize>> {
+    cfg.blocks().iter().map(|b| b.lines.clone()).collect()
+}
+
+fn edges(cfg: &Cfg) -> Vec<(usize, usize, EdgeKind)> {
+    cfg.edges()
+        .iter()
+        .map(|Edge { from, to, kind }| (*from, *to, *kind))
+        .collect()
+}
+
+#[test]
+fn cfg_straight() {
+    let cfg = cfg("move r0 1\n\nadd r0 r0 1\n");
+    assert_eq!(
+        cfg.blocks(),
+        &[Block {
+            lines: 0..3,
+            computed: false,
+            exits: true,
+        }]
+    );
+    assert!(cfg.edges().is_empty());
+    assert!(cfg.loops().is_empty());
+    assert_eq!(cfg.block_of(1), Some(0));
+    assert_eq!(cfg.block_of(3), None);
+
+    assert!(Cfg::new(&Program::new()).blocks().is_empty());
+}
+
+#[test]
+fn cfg_absolute() {
+    let source = "\
+define TOP 1
+start:
+yield
+l r0 d0 Setting
+beqz r0 start
+s d1 On r0
+j TOP";
+    let cfg = cfg(source);
+    assert_eq!(lines(&cfg), vec![0..1, 1..5, 5..7]);
+    assert_eq!(
+        edges(&cfg),
+        vec![
+            (0, 1, EdgeKind::Next),
+            (1, 2, EdgeKind::Next),
+            (1, 1, EdgeKind::Branch),
+            (2, 1, EdgeKind::Jump),
+        ]
+    );
+    assert_eq!(cfg.successors(1), vec![2, 1]);
+    assert_eq!(cfg.predecessors(1), vec![0, 1, 2]);
+    assert_eq!(
+        cfg.loops(),
+        vec![Loop {
+            header: 1,
+            blocks: vec![1, 2],
+        }]
+    );
+    assert!(cfg.blocks().iter().all(|b| !b.exits && !b.computed));
+}
+
+#[test]
+fn cfg_relative() {
+    let source = "\
+move r0 10
+sub r0 r0 1
+brgtz r0 -1
+jr 2
+yield
+jr -6";
+    let cfg = cfg(source);
+    assert_eq!(lines(&cfg), vec![0..1, 1..3, 3..4, 4..5, 5..6]);
+    assert_eq!(
+        edges(&cfg),
+        vec![
+            (0, 1, EdgeKind::Next),
+            (1, 2, EdgeKind::Next),
+            (1, 1, EdgeKind::Branch),
+            (2, 4, EdgeKind::Jump),
+            (3, 4, EdgeKind::Next),
+        ]
+    );
+    // Jumping outside the program, and the unreachable `yield`
+    assert!(cfg.blocks()[4].exits);
+    assert_eq!(cfg.predecessors(3), vec![]);
+    assert_eq!(cfg.loops().len(), 1);
+}
+
+#[test]
+fn cfg_calls() {
+    let source = "\
+main:
+jal sub
+beqzal r0 sub
+j main
+sub:
+add r1 r1 1
+j ra";
+    let cfg = cfg(source);
+    assert_eq!(lines(&cfg), vec![0..2, 2..3, 3..4, 4..7]);
+    assert_eq!(
+        edges(&cfg),
+        vec![
+            (0, 3, EdgeKind::Call),
+            (1, 2, EdgeKind::Next),
+            (1, 3, EdgeKind::Call),
+            (2, 0, EdgeKind::Jump),
+            (3, 1, EdgeKind::Return),
+            (3, 2, EdgeKind::Return),
+        ]
+    );
+    // The subroutine is called from two places, but is not part of a loop
+    assert_eq!(
+        cfg.loops(),
+        vec![Loop {
+            header: 0,
+            blocks: vec![0, 1, 2],
+        }]
+    );
+}
+
+#[test]
+fn cfg_computed() {
+    let cfg = cfg("alias target r0\nmove target 3\nj target\nhcf\nj r17\n");
+    assert_eq!(lines(&cfg), vec![0..3, 3..4, 4..5]);
+    assert!(cfg.blocks()[0].computed);
+    assert!(cfg.blocks()[1].exits);
+    // `ra` without calls
+    assert!(cfg.blocks()[2].computed);
+    assert!(cfg.edges().is_empty());
+}
+
+#[test]
+fn cfg_loops() {
+    // Nested loops, and a cycle entered at two blocks
+    let source = "\
+outer:
+move r0 0
+inner:
+add r0 r0 1
+blt r0 10 inner
+bgtz r1 outer
+beqz r2 8
+j 8
+yield
+j 7";
+    let cfg = cfg(source);
+    assert_eq!(lines(&cfg), vec![0..2, 2..5, 5..6, 6..7, 7..8, 8..10]);
+    assert_eq!(
+        cfg.loops(),
+        vec![
+            Loop {
+                header: 0,
+                blocks: vec![0, 1, 2],
+            },
+            Loop {
+                header: 1,
+                blocks: vec![1],
+            },
+        ]
+    );
+
+    // Loops within a subroutine
+    let cfg = self::cfg("jal sub\nj 0\nsub:\nadd r1 r1 1\nblt r1 10 sub\nj ra\n");
+    assert_eq!(lines(&cfg), vec![0..1, 1..2, 2..5, 5..6]);
+    assert_eq!(
+        cfg.loops(),
+        vec![
+            Loop {
+                header: 0,
+                blocks: vec![0, 1],
+            },
+            Loop {
+                header: 2,
+                blocks: vec![2],
+            },
+        ]
+    );
+}
+
+#[test]
+fn cfg_dot() {
+    let cfg = cfg("start:\nl r0 d0 Setting\nbnez r0 start\ns db Setting HASH(\"x\")\n");
+    assert_eq!(
+        cfg.to_dot(),
+        "\
+digraph cfg {
+    node [shape=box, fontname=\"monospace\"];
+    b0 [label=\"0: start:\\l1: l r0 d0 Setting\\l2: bnez r0 start\\l\", peripheries=2];
+    b1 [label=\"3: s db Setting HASH(\\\"x\\\")\\l\"];
+    exit [shape=doublecircle, label=\"exit\"];
+    b0 -> b1;
+    b0 -> b0 [label=\"branch\"];
+    b1 -> exit;
+}
+"
+    );
+}
+
+#[test]
+fn cfg_rounding() {
+    // Literal targets are rounded half to even, as the simulator does with the game's semantics
+    let cfg = cfg("j 2.6\nyield\nmove r0 1\nyield\n");
+    assert_eq!(lines(&cfg), vec![0..1, 1..3, 3..4]);
+    assert_eq!(edges(&cfg), vec![(0, 2, EdgeKind::Jump), (1, 2, EdgeKind::Next)]);
+
+    // The offset of relative jumps is rounded together with the line
+    let cfg = self::cfg("yield\njr 0.5\nyield\nyield\n");
+    assert_eq!(lines(&cfg), vec![0..2, 2..4]);
+    assert_eq!(edges(&cfg), vec![(0, 1, EdgeKind::Jump)]);
+}
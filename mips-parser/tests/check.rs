@@ -0,0 +1,128 @@
+use mips_parser::prelude::*;
+
+fn messages(source: &str) -> Vec<(usize, String)> {
+    Checker::new()
+        .check_source(&source)
+        .into_iter()
+        .map(|d| (d.line.unwrap(), d.message))
+        .collect()
+}
+
+#[test]
+fn check_valid() {
+    let source = "\
+alias x r0
+alias sensor d0
+define MAX 10
+main:
+l x sensor Temperature
+add x x MAX
+s db Setting x
+push ra
+bgt x MAX main
+brlt x 0 -8
+j main";
+    assert_eq!(messages(source), vec![]);
+}
+
+#[test]
+fn check_undefined() {
+    let source = "\
+move x 1
+beqz r0 nowhere
+l r0 sensor Setting";
+    assert_eq!(
+        messages(source),
+        vec![
+            (0, "`x` is not defined".into()),
+            (1, "`nowhere` is not defined".into()),
+            (2, "`sensor` is not defined".into()),
+        ]
+    );
+}
+
+#[test]
+fn check_wrong_kind() {
+    let source = "\
+alias sensor d0
+alias x r0
+define MAX 5
+add r0 sensor 1
+l r1 x Setting
+move MAX 1";
+    assert_eq!(
+        messages(source),
+        vec![
+            (3, "device alias `sensor` used as val".into()),
+            (4, "memory alias `x` used as dev".into()),
+            (5, "define `MAX` used as mem".into()),
+        ]
+    );
+}
+
+#[test]
+fn check_register_range() {
+    let diagnostics = Checker::new().check_source(&"move r18 1\ns d6 On 1\nl r0 dr20 On");
+    let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "register r18 is out of range (r0-r17)",
+            "register d6 is out of range (d0-d5)",
+            "register dr20 is out of range (r0-r17)",
+        ]
+    );
+    assert_eq!(diagnostics[1].span, 2..4);
+
+    // Configurable sizes
+    let checker = Checker::new().with_mem_size(20).with_dev_size(8);
+    assert!(checker.check_source(&"move r18 1\ns d6 On 1").is_empty());
+}
+
+#[test]
+fn check_unreachable_and_labels() {
+    let source = "\
+main:
+j main
+move r0 1
+loop:
+jr -1
+yield
+main:";
+    let diagnostics = Checker::new().check_source(&source);
+    let found: Vec<(usize, bool, &str)> = diagnostics
+        .iter()
+        .map(|d| (d.line.unwrap(), d.is_error(), d.message.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (2, false, "unreachable code after unconditional jump on line 2"),
+            (5, false, "unreachable code after unconditional jump on line 5"),
+            (6, true, "label `main` is already defined on line 1"),
+        ]
+    );
+    assert_eq!(diagnostics[0].span, 0..9);
+    assert_eq!(diagnostics[2].span, 0..5);
+}
+
+#[test]
+fn check_relative_jumps() {
+    let source = "\
+yield
+jr 5
+brgt r0 0 -3
+jr -1";
+    let diagnostics = Checker::new().check(&Program::try_from_str(&source).unwrap());
+    let found: Vec<(Option<usize>, &str)> = diagnostics
+        .iter()
+        .map(|d| (d.line, d.message.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (Some(1), "relative jump by 5 leaves the program (to line 7)"),
+            (Some(2), "relative jump by -3 leaves the program (to line 0)"),
+        ]
+    );
+}
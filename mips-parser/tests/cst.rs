@@ -0,0 +1,148 @@
+use std::fs::read_to_string;
+
+use mips_parser::prelude::*;
+
+const SOURCE: &str = "\
+# Counter
+alias counter r0   # the count
+define STEP 1e0
+
+main:
+    add counter counter STEP
+    sb HASH(\"Structure # Name\") Setting counter
+  \tyield
+j main";
+
+#[test]
+fn cst_round_trip() {
+    for source in [
+        SOURCE,
+        "",
+        "\n",
+        "\n\n",
+        "move r0 1\r\nmove r1 $FF\r\n",
+        "move r0 %1010 # no newline",
+        "   ",
+        "# only a comment\n",
+    ]
+    .iter()
+    {
+        assert_eq!(Cst::parse(source).to_string(), *source);
+    }
+}
+
+#[test]
+fn cst_round_trip_scripts() {
+    let source = read_to_string("./example-scripts/solar.mips").unwrap();
+    let cst = Cst::parse(&source);
+    assert!(cst.is_ok());
+    assert_eq!(cst.to_string(), source);
+    assert_eq!(cst.program(), Program::try_from_str(&source).unwrap());
+}
+
+#[test]
+fn cst_lines() {
+    let cst = Cst::parse(&SOURCE);
+    assert_eq!(cst.lines.len(), 9);
+
+    let line = &cst.lines[1];
+    assert_eq!(line.code(), "alias counter r0");
+    assert_eq!(line.comment.as_ref().unwrap().leading, "   ");
+    assert_eq!(line.comment.as_ref().unwrap().text, "# the count");
+    assert!(line.expr.is_some());
+
+    // Original spelling
+    let line = &cst.lines[2];
+    assert_eq!(line.tokens[2].text, "1e0");
+    assert_eq!(line.expr.as_ref().unwrap().to_string(), "define STEP 1");
+
+    assert!(cst.lines[3].is_blank());
+    assert!(cst.lines[3].expr.is_none());
+    assert_eq!(cst.lines[5].indent(), "    ");
+
+    // Comment characters within quotes are not comments
+    let line = &cst.lines[6];
+    assert!(line.comment.is_none());
+    assert_eq!(line.tokens[1].text, "HASH(\"Structure # Name\")");
+
+    // Tabs are kept, but are not valid whitespace
+    let line = &cst.lines[7];
+    assert_eq!(line.indent(), "  ");
+    assert_eq!(line.tokens[0].text, "\tyield");
+    assert!(line.diagnostic.is_some());
+    assert!(!cst.is_ok());
+    assert_eq!(cst.diagnostics().len(), 1);
+
+    assert_eq!(cst.lines[8].newline, "");
+}
+
+#[test]
+fn cst_spans() {
+    let cst = Cst::parse(&SOURCE);
+    for line in cst.iter() {
+        assert_eq!(
+            &SOURCE[line.span.clone()],
+            line.to_string().trim_end_matches('\n')
+        );
+        for token in line.tokens.iter().chain(line.comment.iter()) {
+            assert_eq!(&SOURCE[token.span.clone()], token.text);
+        }
+    }
+}
+
+#[test]
+fn cst_program() {
+    let source = "move r0 1 # one\n\nfoo\nj 0\n";
+    let program = Cst::parse(&source).program();
+    let indices: Vec<usize> = program.iter().map(|(i, _)| *i).collect();
+    assert_eq!(indices, vec![0, 3]);
+    assert_eq!(program, Program::parse_lossy(&source).program());
+}
+
+#[test]
+fn cst_edit() {
+    let mut cst = Cst::parse(&SOURCE);
+
+    cst.set_code(5, "add counter counter 2");
+    assert_eq!(cst.lines[5].to_string(), "    add counter counter 2\n");
+    assert!(cst.lines[5].expr.is_some());
+
+    cst.set_code(1, "alias count r1");
+    assert_eq!(cst.lines[1].to_string(), "alias count r1   # the count\n");
+
+    cst.set_comment(2, Some("step size"));
+    assert_eq!(cst.lines[2].to_string(), "define STEP 1e0 # step size\n");
+    cst.set_comment(1, None);
+    assert_eq!(cst.lines[1].to_string(), "alias count r1\n");
+
+    cst.remove_line(7);
+    assert!(cst.is_ok());
+    cst.insert_line(7, "  sleep 1 # wait");
+    assert_eq!(cst.lines[7].index, 7);
+    assert_eq!(cst.lines[8].to_string(), "j main");
+
+    // Inserting at the end keeps the missing final line ending
+    cst.insert_line(9, "hcf");
+    assert!(cst.to_string().ends_with("j main\nhcf"));
+    cst.remove_line(9);
+    assert!(cst.to_string().ends_with("j main"));
+
+    let expected = "\
+# Counter
+alias count r1
+define STEP 1e0 # step size
+
+main:
+    add counter counter 2
+    sb HASH(\"Structure # Name\") Setting counter
+  sleep 1 # wait
+j main";
+    assert_eq!(cst.to_string(), expected);
+}
+
+#[test]
+fn cst_edit_crlf() {
+    let mut cst = Cst::parse(&"move r0 1\r\n");
+    cst.insert_line(1, "move r1 2");
+    assert_eq!(cst.to_string(), "move r0 1\r\nmove r1 2\r\n");
+}
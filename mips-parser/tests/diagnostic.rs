@@ -0,0 +1,94 @@
+use mips_parser::diagnostic::{diagnose, diagnose_line};
+use mips_parser::prelude::*;
+
+#[test]
+fn diagnostic_wrong_arg_kind() {
+    let d = diagnose_line(1, "add d0 1 2").unwrap();
+    assert_eq!(d.message, "`add` expects mem, val, val; found device register d0");
+    assert_eq!(d.line, Some(1));
+    assert_eq!(d.span, 4..6);
+    assert_eq!(d.token.as_deref(), Some("d0"));
+    assert_eq!(d.expected.as_deref(), Some("expected mem"));
+}
+
+#[test]
+fn diagnostic_arity() {
+    let d = diagnose_line(0, "add r0 1 # comment").unwrap();
+    assert_eq!(d.message, "`add` expects mem, val, val; found 2 arguments");
+    assert_eq!(d.span, 9..10);
+    assert_eq!(d.expected.as_deref(), Some("expected val"));
+
+    let d = diagnose_line(0, "yield 1 2").unwrap();
+    assert_eq!(d.message, "`yield` expects no arguments; found 2 arguments");
+    assert_eq!(d.span, 6..9);
+}
+
+#[test]
+fn diagnostic_unknown_instruction() {
+    let d = diagnose_line(0, "  foo r0").unwrap();
+    assert_eq!(d.message, "unknown instruction `foo`");
+    assert_eq!(d.span, 2..5);
+}
+
+#[test]
+fn diagnostic_valid_lines() {
+    let source = "\
+alias x r0
+main: # label
+# comment
+
+s db Setting x
+j main";
+    assert!(diagnose(source).is_empty());
+}
+
+#[test]
+fn diagnostic_from_program_error() {
+    let source = "move r0 1\nl r1 d0 Setting\nmove d1 r1\n";
+    let err = Program::try_from_str(&source).unwrap_err();
+    let d = err.diagnostic(source);
+    assert_eq!(d.line, Some(2));
+    assert_eq!(
+        d.render("test.mips", source),
+        "\
+error: `move` expects mem, val; found device register d1
+ --> test.mips:3:6
+  |
+3 | move d1 r1
+  |      ^^ expected mem"
+    );
+}
+
+#[test]
+fn diagnostic_const_literals() {
+    assert!(diagnose_line(0, "define X HASH(\"Name With Spaces\") # comment").is_none());
+    let d = diagnose_line(0, "move $FF HASH(\"A B\")").unwrap();
+    assert_eq!(d.message, "`move` expects mem, val; found number $FF");
+    let d = diagnose_line(0, "add r0 HASH(\"A B\") 1 2").unwrap();
+    assert_eq!(d.token.as_deref(), Some("2"));
+}
+
+#[test]
+fn diagnostic_non_ascii_columns() {
+    // Spans are in characters, like those of the limits, not in bytes
+    let source = "add r0 HASH(\"ÉÉ\") d0 # é";
+    let d = diagnose_line(0, source).unwrap();
+    assert_eq!(d.span, 18..20);
+    assert_eq!(
+        d.render("test.mips", source),
+        "\
+error: `add` expects mem, val, val; found device register d0
+ --> test.mips:1:19
+  |
+1 | add r0 HASH(\"ÉÉ\") d0 # é
+  |                   ^^ expected val"
+    );
+}
+
+#[test]
+fn diagnostic_brnaz_arity() {
+    assert!(diagnose_line(0, "brnaz r0 0.1 2").is_none());
+    let d = diagnose_line(0, "brnaz r0 0.1 2 3").unwrap();
+    assert_eq!(d.message, "`brnaz` expects val, val, val; found 4 arguments");
+    assert_eq!(d.span, 15..16);
+}
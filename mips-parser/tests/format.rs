@@ -0,0 +1,214 @@
+use std::fs::read_to_string;
+
+use mips_parser::prelude::*;
+
+const SOURCE: &str = "\
+#Solar tracking
+alias sensor d0   #the sensor
+alias x r0
+define SolarPanelHash -2045627372
+start:
+  yield
+horizontal:
+l x sensor Horizontal   #   load
+sub x 90 x
+sb SolarPanelHash Horizontal x
+j start
+";
+
+fn format(source: &str) -> String {
+    Formatter::new().format(&source)
+}
+
+#[test]
+fn format_align() {
+    let expected = "\
+# Solar tracking
+alias  sensor         d0 # the sensor
+alias  x              r0
+define SolarPanelHash -2045627372
+start:
+yield
+horizontal:
+l   x              sensor     Horizontal # load
+sub x              90         x
+sb  SolarPanelHash Horizontal x
+j   start
+";
+    assert_eq!(format(SOURCE), expected);
+}
+
+#[test]
+fn format_align_comments() {
+    let source = "\
+move r0 1 # a
+add r0 r0 10 #b
+yield
+j 0 # c
+";
+    let expected = "\
+move  r0 1     # a
+add   r0 r0 10 # b
+yield
+j     0 # c
+";
+    assert_eq!(format(source), expected);
+}
+
+#[test]
+fn format_unaligned() {
+    let formatter = Formatter::new()
+        .with_align(false)
+        .with_align_comments(false);
+    let expected = "\
+# Solar tracking
+alias sensor d0 # the sensor
+alias x r0
+define SolarPanelHash -2045627372
+start:
+yield
+horizontal:
+l x sensor Horizontal # load
+sub x 90 x
+sb SolarPanelHash Horizontal x
+j start
+";
+    assert_eq!(formatter.format(&SOURCE), expected);
+}
+
+#[test]
+fn format_indent() {
+    let formatted = Formatter::new().with_indent(2).format(&SOURCE);
+    let lines: Vec<&str> = formatted.lines().collect();
+    assert_eq!(lines[1], "alias  sensor         d0 # the sensor");
+    assert_eq!(lines[4], "start:");
+    assert_eq!(lines[5], "  yield");
+    assert_eq!(lines[8], "  sub x              90         x");
+    assert_eq!(lines[10], "  j   start");
+}
+
+#[test]
+fn format_idempotent() {
+    let formatters = [
+        Formatter::new(),
+        Formatter::new().with_indent(4),
+        Formatter::new().with_names(Names::Strip).with_hoist(true),
+    ];
+    let sources = [
+        SOURCE.to_string(),
+        read_to_string("./example-scripts/solar.mips").unwrap(),
+    ];
+    for formatter in formatters.iter() {
+        for source in sources.iter() {
+            let formatted = formatter.format(source);
+            assert_eq!(formatter.format(&formatted), formatted);
+        }
+    }
+}
+
+#[test]
+fn format_keeps_program() {
+    let source = read_to_string("./example-scripts/solar.mips").unwrap();
+    let formatted = Formatter::new().with_indent(4).format(&source);
+    assert_eq!(
+        Program::try_from_str(&formatted).unwrap(),
+        Program::try_from_str(&source).unwrap()
+    );
+}
+
+#[test]
+fn format_comments() {
+    // Comment lines are indented as the code that follows
+    let source = "#a\n##  b  \n#\nmove r0 1#c\n   # d\nstart:\n  #e\nmove r1 2\n";
+    let expected = "  # a\n  ## b\n  #\n  move r0 1 # c\n# d\nstart:\n  # e\n  move r1 2\n";
+    assert_eq!(Formatter::new().with_indent(2).format(&source), expected);
+}
+
+#[test]
+fn format_newlines() {
+    assert_eq!(format(""), "");
+    assert_eq!(format("yield"), "yield");
+    assert_eq!(format("yield  \r\n\r\nyield\r\n"), "yield\r\n\r\nyield\r\n");
+    assert_eq!(format("   \n\n"), "\n\n");
+}
+
+#[test]
+fn format_invalid() {
+    let source = "move r0 1\nfoo bar   \nadd r0 r0 1  # comment\n";
+    let expected = "move r0 1\nfoo bar\nadd r0 r0 1 # comment\n";
+    assert_eq!(format(source), expected);
+}
+
+#[test]
+fn format_columns() {
+    let long = "x".repeat(60);
+    let source = format!("sb {} Setting 1\nsb 1 On {}\n", long, long);
+    // Aligned, the second line would be too long
+    let formatted = format(&source);
+    assert_eq!(formatted, source);
+    assert!(Limits::new().check(&formatted).is_empty());
+
+    let formatted = Formatter::new().with_columns(None).format(&source);
+    assert!(!Limits::new().check(&formatted).is_empty());
+}
+
+#[test]
+fn format_expand() {
+    let formatter = Formatter::new().with_names(Names::Expand).with_align(false);
+    let expected = "\
+# Solar tracking
+alias sensor d0 # the sensor
+alias x r0
+define SolarPanelHash -2045627372
+start:
+yield
+horizontal:
+l r0 d0 Horizontal # load
+sub r0 90 r0
+sb -2045627372 Horizontal r0
+j start
+";
+    assert_eq!(formatter.format(&SOURCE), expected);
+
+    // Names declared more than once are kept
+    let source = "alias x r0\nmove x 1\nalias x r1\nmove x 2\n";
+    assert_eq!(formatter.format(&source), source);
+}
+
+#[test]
+fn format_strip() {
+    let formatter = Formatter::new().with_names(Names::Strip).with_align(false);
+    let expected = "\
+# Solar tracking
+# the sensor
+start:
+yield
+horizontal:
+l r0 d0 Horizontal # load
+sub r0 90 r0
+sb -2045627372 Horizontal r0
+j start
+";
+    assert_eq!(formatter.format(&SOURCE), expected);
+
+    // Line indices are kept when jumps depend on them
+    let source = "define N 1\nalias x r0\nadd x x N\nj 2\n";
+    assert_eq!(formatter.format(&source), "\n\nadd r0 r0 1\nj 2\n");
+}
+
+#[test]
+fn format_hoist() {
+    let formatter = Formatter::new().with_hoist(true).with_align(false);
+    let source = "# Header\nstart:\nalias x r0\nmove x 1\ndefine N 2\nj start\n";
+    let expected = "# Header\nalias x r0\ndefine N 2\nstart:\nmove x 1\nj start\n";
+    assert_eq!(formatter.format(&source), expected);
+
+    // Not when jumps depend on line indices
+    let source = "start:\nalias x r0\nmove x 1\nj 0\n";
+    assert_eq!(formatter.format(&source), source);
+
+    // Not when names are declared more than once
+    let source = "alias x r0\nmove x 1\nalias x r1\nmove x 2\nj ra\n";
+    let source = format!("start:\n{}", source);
+    assert_eq!(formatter.format(&source), source);
+}
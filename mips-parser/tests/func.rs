@@ -0,0 +1,125 @@
+use mips_parser::ast::nodes::{ArgKind, Line};
+use mips_parser::prelude::*;
+
+/// Source token of an argument kind.
+fn token(kind: &ArgKind) -> &'static str {
+    match kind {
+        ArgKind::Mem | ArgKind::Reg => "r0",
+        ArgKind::Dev => "d0",
+        ArgKind::Val | ArgKind::Num => "1",
+        ArgKind::Tkn => "On",
+    }
+}
+
+/// Line calling a function with arguments of the kinds of its signature.
+fn call(func: &Func, extra: usize) -> String {
+    let args = func
+        .signature()
+        .iter()
+        .map(token)
+        .chain((0..extra).map(|_| "1"));
+    std::iter::once(func.to_string())
+        .chain(args.map(String::from))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn func_signatures_match_grammar() {
+    for func in Func::ALL.iter().filter(|func| **func != Func::Label) {
+        let line = call(func, 0);
+        let expr = Line::try_from_str(&line)
+            .unwrap_or_else(|e| panic!("{}: {:?}", line, e))
+            .unwrap();
+        assert_eq!(&expr.0, func, "{}", line);
+        for (kind, arg) in func.signature().iter().zip(expr.1.iter()) {
+            assert!(kind.accepts(arg), "{}: {:?}", line, arg);
+        }
+        let line = call(func, 1);
+        assert!(Program::try_from_str(&line).is_err(), "{}", line);
+    }
+}
+
+#[test]
+fn func_flow() {
+    for func in Func::ALL.iter() {
+        let name = func.to_string();
+        let flow = func.flow();
+        assert_eq!(
+            flow.jumps(),
+            name.starts_with('b') || name.starts_with('j'),
+            "{}",
+            name
+        );
+        assert_eq!(
+            flow.is_relative(),
+            name.starts_with("br") || name == "jr",
+            "{}",
+            name
+        );
+        assert_eq!(
+            flow.stores_ra(),
+            flow.jumps() && name.ends_with("al"),
+            "{}",
+            name
+        );
+        assert!(!func.description().is_empty());
+    }
+    assert!(Func::J.flow().is_unconditional());
+    assert!(Func::Jr.flow().is_unconditional());
+    assert!(!Func::Jal.flow().is_unconditional());
+    assert!(!Func::Beq.flow().is_unconditional());
+}
+
+#[test]
+fn func_categories() {
+    let count = |category| {
+        Func::ALL
+            .iter()
+            .filter(|func| func.category() == category)
+            .count()
+    };
+    assert_eq!(count(Category::Io), 20);
+    assert_eq!(count(Category::Branch), 51);
+    assert_eq!(count(Category::Select), 19);
+    assert_eq!(count(Category::Math), 22);
+    assert_eq!(count(Category::Logic), 9);
+    assert_eq!(count(Category::Stack), 9);
+    assert_eq!(count(Category::Misc), 6);
+    assert_eq!(count(Category::Label), 1);
+
+    assert_eq!(Func::Bdns.category(), Category::Io);
+    assert_eq!(Func::Select.category(), Category::Select);
+    assert_eq!(
+        Category::Branch.to_string(),
+        "Flow Control, Branches and Jumps"
+    );
+}
+
+#[test]
+fn func_info() {
+    let info = Func::Bapz.info();
+    assert_eq!(info.signature, &[ArgKind::Val, ArgKind::Val, ArgKind::Val]);
+    assert_eq!(info.category, Category::Branch);
+    assert_eq!(info.flow, Flow::Branch);
+
+    assert_eq!(Func::Lr.signature().len(), 4);
+    assert_eq!(Func::Brnaz.signature().len(), 3);
+    assert!(Program::try_from_str(&"brnaz r0 0.1 2").is_ok());
+    assert!(Program::try_from_str(&"brnaz r0 0.1 2 3").is_err());
+}
+
+#[test]
+fn arg_kind_accepts() {
+    let mem = Arg::ArgMem(Mem::MemLit(0, 0));
+    let dev = Arg::ArgDev(Dev::DevLit(0, 0));
+    let lit = Arg::ArgVal(Val::ValLit(1.0, None));
+    let val = Arg::ArgVal(Val::ValMem(Mem::MemLit(0, 0)));
+    let tkn = Arg::ArgToken("On".into());
+    assert!(ArgKind::Mem.accepts(&mem) && !ArgKind::Mem.accepts(&val));
+    assert!(ArgKind::Dev.accepts(&dev) && !ArgKind::Dev.accepts(&mem));
+    assert!(ArgKind::Val.accepts(&lit) && ArgKind::Val.accepts(&val));
+    assert!(ArgKind::Reg.accepts(&mem) && ArgKind::Reg.accepts(&dev));
+    assert!(ArgKind::Num.accepts(&lit) && !ArgKind::Num.accepts(&val));
+    assert!(ArgKind::Tkn.accepts(&tkn) && !ArgKind::Tkn.accepts(&lit));
+}
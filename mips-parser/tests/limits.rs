@@ -0,0 +1,70 @@
+use mips_parser::prelude::*;
+
+#[test]
+fn limits_within() {
+    let source = "move r0 1\n".repeat(128);
+    assert!(Limits::new().check(&source).is_empty());
+}
+
+#[test]
+fn limits_lines() {
+    let source = "yield\n".repeat(130);
+    let diagnostics = Limits::new().check(&source);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "program is 130 lines long, the limit is 128");
+    assert_eq!(diagnostics[0].line, Some(128));
+
+    assert!(Limits::new().with_lines(Some(200)).check(&source).is_empty());
+}
+
+#[test]
+fn limits_columns() {
+    let long = format!("move r0 1 # {}", "x".repeat(90));
+    let source = format!("yield\n{}\nyield", long);
+    let diagnostics = Limits::new().check(&source);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].line, Some(1));
+    assert_eq!(diagnostics[0].span, 90..102);
+    assert_eq!(
+        diagnostics[0].render("test.mips", &source),
+        format!(
+            "\
+error: line is 102 characters long, the limit is 90
+ --> test.mips:2:91
+  |
+2 | {}
+  | {}^^^^^^^^^^^^ beyond column 90",
+            long,
+            " ".repeat(90)
+        )
+    );
+
+    assert!(Limits::new().with_columns(None).check(&source).is_empty());
+}
+
+#[test]
+fn limits_bytes() {
+    let source = "move r0 1\n".repeat(10);
+    let limits = Limits::new().with_bytes(Some(25));
+    let diagnostics = limits.check(&source);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "program is 100 bytes long, the limit is 25");
+    assert_eq!(diagnostics[0].line, Some(2));
+    assert_eq!(diagnostics[0].span, 5..9);
+}
+
+#[test]
+fn limits_bytes_non_ascii() {
+    // The limit falls within the two bytes of `é`
+    let source = format!("{}é", "#".repeat(4095));
+    let limits = Limits::new().with_columns(None).with_bytes(Some(4096));
+    let diagnostics = limits.check(&source);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "program is 4097 bytes long, the limit is 4096");
+    assert_eq!(diagnostics[0].span, 4095..4096);
+
+    let source = format!("move r0 1\n# {}", "é".repeat(10));
+    let diagnostics = Limits::new().with_bytes(Some(15)).check(&source);
+    assert_eq!(diagnostics[0].line, Some(1));
+    assert_eq!(diagnostics[0].span, 3..12);
+}
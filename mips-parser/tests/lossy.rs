@@ -0,0 +1,34 @@
+use mips_parser::prelude::*;
+
+#[test]
+fn parse_lossy_all_errors() {
+    let source = "\
+move r0 1
+add d0 1 2
+# comment
+foo r1
+sub r1 r0 1
+j";
+    let lossy = Program::parse_lossy(&source);
+    assert!(!lossy.is_ok());
+    assert_eq!(lossy.lines.len(), 6);
+    assert_eq!(lossy.lines[1], LossyLine::Error("add d0 1 2".into()));
+    assert_eq!(lossy.lines[2], LossyLine::Blank);
+
+    let lines: Vec<usize> = lossy.diagnostics.iter().filter_map(|d| d.line).collect();
+    assert_eq!(lines, vec![1, 3, 5]);
+
+    // Valid expressions keep their line indices
+    let program = lossy.program();
+    let indices: Vec<usize> = program.iter().map(|(i, _)| *i).collect();
+    assert_eq!(indices, vec![0, 4]);
+    assert_eq!(program.to_string(), "move r0 1\nsub r1 r0 1\n");
+}
+
+#[test]
+fn parse_lossy_valid() {
+    let source = "alias x r0\nmain:\nadd x x 1\nj main\n";
+    let lossy = Program::parse_lossy(&source);
+    assert!(lossy.is_ok());
+    assert_eq!(lossy.program(), Program::try_from_str(&source).unwrap());
+}
@@ -3,12 +3,35 @@
 use mips_parser::{ast::all_node_variants::*, prelude::*, Rule::*};
 use util::mips_ast_test;
 
-mips_ast_test!(val_lit_37,                 "37",         val, Val, ValLit(37.0));
-mips_ast_test!(val_lit_128_46,             "-128.46",    val, Val, ValLit(-128.46));
-mips_ast_test!(val_lit_3_14e5,             "3.14e5",     val, Val, ValLit(314000.0));
-mips_ast_test!(val_lit_neg_801_75_e_neg_7, "-801.75e-7", val, Val, ValLit(-0.000080175));
+mips_ast_test!(val_lit_37,                 "37",         val, Val, ValLit(37.0, None));
+mips_ast_test!(val_lit_128_46,             "-128.46",    val, Val, ValLit(-128.46, None));
+mips_ast_test!(val_lit_3_14e5,             "3.14e5",     val, Val, ValLit(314000.0, None));
+mips_ast_test!(val_lit_neg_801_75_e_neg_7, "-801.75e-7", val, Val, ValLit(-0.000080175, None));
 mips_ast_test!(val_mem_r0,                 "r0",         val, Val, ValMem(MemLit(0, 0)));
 mips_ast_test!(val_mem_rr1,                "rr1",        val, Val, ValMem(MemLit(1, 1)));
 mips_ast_test!(val_mem_rrr5,               "rrr5",       val, Val, ValMem(MemLit(5, 2)));
 mips_ast_test!(val_mem_x,                  "x",          val, Val, ValMem(MemAlias("x".into())));
 
+mips_ast_test!(val_hex_ff,                 "$FF",        val, Val, ValLit(255.0, Some("$FF".into())));
+mips_ast_test!(val_bin_1010,               "%1010",      val, Val, ValLit(10.0, Some("%1010".into())));
+mips_ast_test!(val_hash_logic_memory,      "HASH(\"StructureLogicMemory\")", val, Val,
+    ValLit(-851746783.0, Some("HASH(\"StructureLogicMemory\")".into())));
+
+#[test]
+fn val_const_round_trip() {
+    let source = "\
+define SOLAR HASH(\"StructureSolarPanel\")
+define MASK $FF
+define FLAGS %1010
+lb r0 HASH(\"StructureLogicMemory\") Setting 1
+and r1 r0 $0F
+";
+    let ast = Program::try_from_str(&source).unwrap();
+    assert_eq!(ast.to_string(), source);
+}
+
+#[test]
+fn val_hash_crc32() {
+    assert_eq!(mips_parser::hash::hash("StructureLogicMemory"), -851746783);
+    assert_eq!(mips_parser::hash::crc32(b"123456789"), 0xCBF43926);
+}
@@ -0,0 +1,381 @@
+//! Device behavior models.
+//!
+//! A [`Device`] on its own is a passive set of parameters. A [`DeviceBehavior`] updates a device
+//! once per game tick from its own parameters and a shared [`Environment`] (e.g. a solar panel
+//! updating its `Ratio` from its angles and the position of the sun), so that closed-loop control
+//! scripts can be tested against plausible physics.
+//!
+//! Behaviors are looked up in a [`Behaviors`] table by device label, then by device name, so a
+//! labelled device can be given its own model (e.g. a pump between particular atmospheres).
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::Device;
+
+/// Ideal gas constant (J/(mol K)).
+pub const GAS_CONSTANT: f64 = 8.314_462_618;
+/// Default number of ticks in a day.
+pub const DAY_TICKS: usize = 2400;
+
+/// Read a device parameter value regardless of its kind (zero if the device lacks it).
+fn get(dev: &Device, param: &str) -> f64 {
+    dev.params.get(param).map(|p| p.value()).unwrap_or(0.0)
+}
+
+/// Set a device parameter value regardless of its kind (ignored if the device lacks it).
+fn set(dev: &mut Device, param: &str, val: f64) {
+    dev.set(param, val).ok();
+}
+
+/// A volume of gas (e.g. a room or pipe network).
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Atmosphere {
+    /// Volume in litres.
+    pub volume: f64,
+    /// Amount of gas in moles.
+    pub moles: f64,
+    /// Temperature in kelvin.
+    pub temperature: f64,
+}
+
+impl Atmosphere {
+    pub fn new(volume: f64, moles: f64, temperature: f64) -> Self {
+        Self {
+            volume,
+            moles,
+            temperature,
+        }
+    }
+
+    /// Pressure in kilopascals.
+    pub fn pressure(&self) -> f64 {
+        if self.volume > 0.0 {
+            self.moles * GAS_CONSTANT * self.temperature / self.volume
+        } else {
+            0.0
+        }
+    }
+
+    /// Remove up to `moles` of gas, returning the amount removed.
+    pub fn remove(&mut self, moles: f64) -> f64 {
+        let moles = moles.min(self.moles).max(0.0);
+        self.moles -= moles;
+        moles
+    }
+
+    /// Add gas at a temperature, mixing the temperatures by amount.
+    pub fn add(&mut self, moles: f64, temperature: f64) {
+        let total = self.moles + moles;
+        if total > 0.0 {
+            self.temperature = (self.moles * self.temperature + moles * temperature) / total;
+        }
+        self.moles = total;
+    }
+}
+
+/// Environment shared by the devices being simulated.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Environment {
+    /// Number of ticks elapsed.
+    pub tick: usize,
+    /// Number of ticks in a day.
+    pub day_ticks: usize,
+    /// Atmospheres, by index.
+    pub atmospheres: Vec<Atmosphere>,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self {
+            tick: 0,
+            day_ticks: DAY_TICKS,
+            atmospheres: Vec::new(),
+        }
+    }
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder helper to set the number of ticks in a day.
+    pub fn with_day_ticks(mut self, day_ticks: usize) -> Self {
+        self.day_ticks = day_ticks.max(1);
+        self
+    }
+
+    /// Builder helper to add an atmosphere.
+    pub fn with_atmosphere(mut self, atmosphere: Atmosphere) -> Self {
+        self.atmospheres.push(atmosphere);
+        self
+    }
+
+    /// Angles of the sun in degrees `(horizontal, vertical)` at the current tick.
+    ///
+    /// The sun rises at vertical 0 at the start of the day, is overhead (vertical 90) a quarter
+    /// of the way through and sets (vertical 180) half way through, turning a full horizontal
+    /// circle each day. At night the vertical angle is beyond 180.
+    pub fn sun(&self) -> (f64, f64) {
+        let t = (self.tick % self.day_ticks) as f64 / self.day_ticks as f64;
+        (360.0 * t, 360.0 * t)
+    }
+
+    /// Is the sun above the horizon.
+    pub fn is_day(&self) -> bool {
+        self.sun().1 <= 180.0
+    }
+}
+
+/// Device behavior model, updating a device once per tick.
+pub trait DeviceBehavior: fmt::Debug + Send + Sync {
+    /// Update a device for an elapsed tick.
+    fn tick(&self, dev: &mut Device, env: &mut Environment);
+}
+
+/// Solar panel, generating `Charge` relative to how directly it faces the sun.
+#[derive(Copy, Clone, Debug)]
+pub struct SolarPanel {
+    /// Charge generated when facing the sun directly.
+    pub maximum: f64,
+}
+
+impl Default for SolarPanel {
+    fn default() -> Self {
+        Self { maximum: 500.0 }
+    }
+}
+
+impl DeviceBehavior for SolarPanel {
+    fn tick(&self, dev: &mut Device, env: &mut Environment) {
+        let (sun_h, sun_v) = env.sun();
+        let ratio = if env.is_day() {
+            let dh = (get(dev, "Horizontal") - sun_h).to_radians().cos();
+            let dv = (get(dev, "Vertical") - sun_v).to_radians().cos();
+            dh.max(0.0) * dv.max(0.0)
+        } else {
+            0.0
+        };
+        set(dev, "Maximum", self.maximum);
+        set(dev, "Ratio", ratio);
+        set(dev, "Charge", ratio * self.maximum);
+    }
+}
+
+/// Daylight sensor, reading out the angles of the sun.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct DaylightSensor;
+
+impl DeviceBehavior for DaylightSensor {
+    fn tick(&self, dev: &mut Device, env: &mut Environment) {
+        let (horizontal, vertical) = env.sun();
+        set(dev, "Horizontal", horizontal);
+        set(dev, "Vertical", vertical);
+        set(dev, "SolarAngle", (vertical - 90.0).abs());
+    }
+}
+
+/// Volume pump, moving `Setting` litres of gas per tick from one atmosphere to another while on.
+#[derive(Copy, Clone, Debug)]
+pub struct VolumePump {
+    /// Index of the input atmosphere.
+    pub input: usize,
+    /// Index of the output atmosphere.
+    pub output: usize,
+    /// Maximum litres moved per tick.
+    pub maximum: f64,
+}
+
+impl Default for VolumePump {
+    fn default() -> Self {
+        Self {
+            input: 0,
+            output: 1,
+            maximum: 10.0,
+        }
+    }
+}
+
+impl DeviceBehavior for VolumePump {
+    fn tick(&self, dev: &mut Device, env: &mut Environment) {
+        let litres = get(dev, "Setting").max(0.0).min(self.maximum);
+        set(dev, "Maximum", self.maximum);
+        set(dev, "Ratio", litres / self.maximum);
+        if get(dev, "On") == 0.0 || self.input == self.output {
+            return;
+        }
+        let input = match env.atmospheres.get_mut(self.input) {
+            Some(input) if input.volume > 0.0 => input,
+            _ => return,
+        };
+        let temperature = input.temperature;
+        let moles = input.remove(input.moles * (litres / input.volume).min(1.0));
+        match env.atmospheres.get_mut(self.output) {
+            Some(output) => output.add(moles, temperature),
+            // Nowhere to pump to, so put the gas back
+            None => env.atmospheres[self.input].add(moles, temperature),
+        }
+    }
+}
+
+/// Gas sensor, reading out the pressure and temperature of an atmosphere.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct GasSensor {
+    /// Index of the sensed atmosphere.
+    pub atmosphere: usize,
+}
+
+impl DeviceBehavior for GasSensor {
+    fn tick(&self, dev: &mut Device, env: &mut Environment) {
+        let (pressure, temperature, moles) = env
+            .atmospheres
+            .get(self.atmosphere)
+            .map(|a| (a.pressure(), a.temperature, a.moles))
+            .unwrap_or((0.0, 0.0, 0.0));
+        set(dev, "Pressure", pressure);
+        set(dev, "Temperature", temperature);
+        set(dev, "TotalMoles", moles);
+    }
+}
+
+/// Logic memory, which holds whatever was last written to it.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Memory;
+
+impl DeviceBehavior for Memory {
+    fn tick(&self, _dev: &mut Device, _env: &mut Environment) {}
+}
+
+/// Logic switch (or lever), whose `Setting` reads whether it is `Open`.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct LogicSwitch;
+
+impl DeviceBehavior for LogicSwitch {
+    fn tick(&self, dev: &mut Device, _env: &mut Environment) {
+        let open = get(dev, "Open");
+        set(dev, "Setting", (open != 0.0) as i32 as f64);
+    }
+}
+
+/// Logic button, whose `Setting` reads 1 for the tick after it was activated.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct LogicButton;
+
+impl DeviceBehavior for LogicButton {
+    fn tick(&self, dev: &mut Device, _env: &mut Environment) {
+        let activate = get(dev, "Activate");
+        set(dev, "Setting", (activate != 0.0) as i32 as f64);
+        set(dev, "Activate", 0.0);
+    }
+}
+
+/// LED display, drawing power while on.
+#[derive(Copy, Clone, Debug)]
+pub struct LedDisplay {
+    /// Power drawn while on (W).
+    pub power: f64,
+}
+
+impl Default for LedDisplay {
+    fn default() -> Self {
+        Self { power: 10.0 }
+    }
+}
+
+impl DeviceBehavior for LedDisplay {
+    fn tick(&self, dev: &mut Device, _env: &mut Environment) {
+        let power = if get(dev, "On") != 0.0 {
+            self.power
+        } else {
+            0.0
+        };
+        set(dev, "RequiredPower", self.power);
+        set(dev, "Power", power);
+    }
+}
+
+/// Table of device behaviors, by device label or name.
+#[derive(Clone, Default, Debug)]
+pub struct Behaviors {
+    map: HashMap<String, Arc<dyn DeviceBehavior>>,
+}
+
+impl Behaviors {
+    /// New empty behavior table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Behavior table of the built-in models for their devices.
+    pub fn builtin() -> Self {
+        let solar = [
+            "SolarPanel",
+            "SolarPanel45",
+            "SolarPanel45Reinforced",
+            "SolarPanelDual",
+            "SolarPanelDualReinforced",
+            "SolarPanelFlat",
+            "SolarPanelFlatReinforced",
+            "SolarPanelReinforced",
+        ];
+        let displays = ["ConsoleLED1x2", "ConsoleLED1x3", "ConsoleLED5"];
+        let mut behaviors = Self::new()
+            .with("DaylightSensor", DaylightSensor)
+            .with("VolumePump", VolumePump::default())
+            .with("GasSensor", GasSensor::default())
+            .with("LogicMemory", Memory)
+            .with("LogicSwitch", LogicSwitch)
+            .with("LogicSwitch2", LogicSwitch)
+            .with("LogicButton", LogicButton);
+        for name in solar.iter() {
+            behaviors = behaviors.with(*name, SolarPanel::default());
+        }
+        for name in displays.iter() {
+            behaviors = behaviors.with(*name, LedDisplay::default());
+        }
+        behaviors
+    }
+
+    /// Builder helper to set the behavior for a device label or name.
+    pub fn with<K, B>(mut self, key: K, behavior: B) -> Self
+    where
+        K: Into<String>,
+        B: DeviceBehavior + 'static,
+    {
+        self.insert(key, behavior);
+        self
+    }
+
+    /// Set the behavior for a device label or name.
+    pub fn insert<K, B>(&mut self, key: K, behavior: B)
+    where
+        K: Into<String>,
+        B: DeviceBehavior + 'static,
+    {
+        self.map.insert(key.into(), Arc::new(behavior));
+    }
+
+    /// Is the table empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Get the behavior of a device, by its label then by its name.
+    pub fn get(&self, dev: &Device) -> Option<&dyn DeviceBehavior> {
+        dev.label
+            .as_ref()
+            .and_then(|label| self.map.get(label))
+            .or_else(|| self.map.get(&dev.name))
+            .map(|b| b.as_ref())
+    }
+
+    /// Update a device for an elapsed tick (if it has a behavior).
+    pub fn tick(&self, dev: &mut Device, env: &mut Environment) {
+        if let Some(behavior) = self.get(dev) {
+            behavior.tick(dev, env);
+        }
+    }
+}
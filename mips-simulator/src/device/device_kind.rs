@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 
 use super::param::ParamKind;
+use super::reagent::Reagents;
+use super::slot::SlotKind;
 use super::Device;
 
 /// Device kind type.
@@ -11,6 +13,13 @@ pub struct DeviceKind {
     pub name: String,
     pub hash: i64,
     pub params: Vec<ParamKind>,
+    #[serde(default)]
+    pub slots: Vec<SlotKind>,
+    #[serde(default)]
+    pub reagents: Reagents,
+    /// Memory (stack) size.
+    #[serde(default)]
+    pub memory: usize,
 }
 
 /// Shortcut type for HashMap<String, DeviceKind>.
@@ -22,7 +31,17 @@ impl DeviceKind {
         let name = self.name.clone();
         let hash = self.hash;
         let params = self.params.iter().map(|pk| pk.make()).collect();
-        Device { name, hash, params }
+        let slots = self.slots.iter().map(|sk| sk.make()).collect();
+        Device {
+            name,
+            hash,
+            label: None,
+            reference_id: None,
+            params,
+            slots,
+            reagents: self.reagents.clone(),
+            memory: vec![0.0; self.memory],
+        }
     }
 }
 
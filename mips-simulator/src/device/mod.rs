@@ -4,11 +4,19 @@ use std::{fmt, fmt::Display};
 use maplit::hashmap;
 use serde::{Deserialize, Serialize};
 
+use crate::state::ReagentMode;
+
+pub mod behavior;
 mod device_kind;
 mod param;
+mod reagent;
+mod slot;
 
+pub use behavior::{Atmosphere, Behaviors, DeviceBehavior, Environment};
 pub use device_kind::{DeviceKind, DeviceKinds};
 pub use param::{Param, ParamKind, Params};
+pub use reagent::{Reagent, Reagents};
+pub use slot::{Slot, SlotKind, SLOT_PARAMS};
 
 #[derive(Clone, Debug)]
 pub enum DeviceError {
@@ -16,17 +24,99 @@ pub enum DeviceError {
     ParamUnknown(String),
     ParamReadOnly,
     ParamWriteOnly,
+    SlotUnknown(usize),
+    AddressUnknown(usize),
 }
 
 /// Device type.
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub struct Device {
     pub name: String,
     pub hash: i64,
+    /// Label given to the device (e.g. with a labeller), which determines its name hash.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Reference id of the device, by which the direct-id instructions (e.g. `ld`) address it.
+    #[serde(default)]
+    pub reference_id: Option<i64>,
     pub params: Params,
+    #[serde(default)]
+    pub slots: Vec<Slot>,
+    #[serde(default)]
+    pub reagents: Reagents,
+    /// Memory (stack) buffer, accessed with `get` and `put`.
+    #[serde(default)]
+    pub memory: Vec<f64>,
 }
 
 impl Device {
+    /// Builder helper to set the device label.
+    pub fn with_label<S: Into<String>>(mut self, label: S) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Builder helper to set the reference id.
+    pub fn with_reference_id(mut self, id: i64) -> Self {
+        self.reference_id = Some(id);
+        self
+    }
+
+    /// Builder helper to set the memory size (zeroing the memory).
+    pub fn with_memory(mut self, size: usize) -> Self {
+        self.memory = vec![0.0; size];
+        self
+    }
+
+    /// Is the device identical to another, comparing values bitwise.
+    ///
+    /// Unlike `==`, a value holding NaN is identical to the same NaN, so that it is not a change.
+    pub(crate) fn identical(&self, other: &Device) -> bool {
+        fn same(a: f64, b: f64) -> bool {
+            a.to_bits() == b.to_bits()
+        }
+        fn same_params(a: &Params, b: &Params) -> bool {
+            a.len() == b.len()
+                && a.iter().all(|(k, p)| {
+                    b.get(k).is_some_and(|q| {
+                        std::mem::discriminant(p) == std::mem::discriminant(q)
+                            && same(p.value(), q.value())
+                    })
+                })
+        }
+        self.name == other.name
+            && self.hash == other.hash
+            && self.label == other.label
+            && self.reference_id == other.reference_id
+            && same_params(&self.params, &other.params)
+            && self.slots.len() == other.slots.len()
+            && self
+                .slots
+                .iter()
+                .zip(other.slots.iter())
+                .all(|(a, b)| a.name == b.name && same_params(&a.params, &b.params))
+            && self.reagents.len() == other.reagents.len()
+            && self.reagents.iter().all(|(k, a)| {
+                other.reagents.get(k).is_some_and(|b| {
+                    same(a.contents, b.contents)
+                        && same(a.required, b.required)
+                        && same(a.recipe, b.recipe)
+                })
+            })
+            && self.memory.len() == other.memory.len()
+            && self
+                .memory
+                .iter()
+                .zip(other.memory.iter())
+                .all(|(a, b)| same(*a, *b))
+    }
+
+    /// Name hash of the device, i.e. the hash of its label (or its name if unlabelled).
+    pub fn name_hash(&self) -> i64 {
+        let name = self.label.as_ref().unwrap_or(&self.name);
+        mips_parser::hash::hash(name) as i64
+    }
+
     /// Try to get a specified parameter reference.
     pub fn try_get_param(&self, param: &String) -> Result<&Param, DeviceError> {
         self.params
@@ -64,6 +154,88 @@ impl Device {
             .flatten()
     }
 
+    /// Set a parameter regardless of its kind (e.g. to simulate the device itself).
+    ///
+    /// Fails if a parameter for the key does not exist.
+    pub fn set<K, V>(&mut self, param: K, val: V) -> Result<(), DeviceError>
+    where
+        K: Into<String>,
+        V: Into<f64>,
+    {
+        self.try_get_mut_param(&param.into())
+            .map(|p| p.set(val.into()))
+    }
+
+    /// Try to get a specified slot reference.
+    pub fn try_get_slot(&self, slot: usize) -> Result<&Slot, DeviceError> {
+        self.slots.get(slot).ok_or(DeviceError::SlotUnknown(slot))
+    }
+
+    /// Try to get a mutable specified slot reference.
+    pub fn try_get_mut_slot(&mut self, slot: usize) -> Result<&mut Slot, DeviceError> {
+        self.slots
+            .get_mut(slot)
+            .ok_or(DeviceError::SlotUnknown(slot))
+    }
+
+    /// Read from a slot parameter.
+    ///
+    /// Fails if the slot or the parameter for the key does not exist,
+    /// or if the parameter kind is write only.
+    pub fn read_slot<K>(&self, slot: usize, param: K) -> Result<f64, DeviceError>
+    where
+        K: Into<String>,
+    {
+        self.try_get_slot(slot)?.read(param)
+    }
+
+    /// Write to a slot parameter.
+    ///
+    /// Fails if the slot or the parameter for the key does not exist,
+    /// or if the parameter kind is read only.
+    pub fn write_slot<K, V>(&mut self, slot: usize, param: K, val: V) -> Result<(), DeviceError>
+    where
+        K: Into<String>,
+        V: Into<f64>,
+    {
+        self.try_get_mut_slot(slot)?.write(param, val)
+    }
+
+    /// Read the amount of a reagent (by hash) for a reagent mode.
+    ///
+    /// Reagents not in the reagent table read as zero.
+    pub fn read_reagent(&self, mode: ReagentMode, hash: i64) -> f64 {
+        self.reagents
+            .get(&hash)
+            .map(|r| r.read(mode))
+            .unwrap_or(0.0)
+    }
+
+    /// Read from a memory address.
+    ///
+    /// Fails if the address is beyond the memory size.
+    pub fn read_memory(&self, addr: usize) -> Result<f64, DeviceError> {
+        self.memory
+            .get(addr)
+            .cloned()
+            .ok_or(DeviceError::AddressUnknown(addr))
+    }
+
+    /// Write to a memory address.
+    ///
+    /// Fails if the address is beyond the memory size.
+    pub fn write_memory(&mut self, addr: usize, val: f64) -> Result<(), DeviceError> {
+        self.memory
+            .get_mut(addr)
+            .map(|m| *m = val)
+            .ok_or(DeviceError::AddressUnknown(addr))
+    }
+
+    /// Zero the memory.
+    pub fn clear_memory(&mut self) {
+        self.memory.iter_mut().for_each(|m| *m = 0.0);
+    }
+
     /// Construct a new Stationeers circuit housing device
     ///
     /// Used for the default state self device.
@@ -71,6 +243,8 @@ impl Device {
         Device {
             name: "CircuitHousing".to_string(),
             hash: -128473777,
+            label: None,
+            reference_id: None,
             params: hashmap! {
                 "On".into()            => Param::ReadWrite(0.0),
                 "RequiredPower".into() => Param::Read(0.0),
@@ -80,13 +254,19 @@ impl Device {
                 "Setting".into()       => Param::ReadWrite(0.0),
                 "Power".into()         => Param::Read(0.0)
             },
+            slots: vec![SlotKind::new("Programmable Chip").make()],
+            reagents: Reagents::new(),
+            memory: Vec::new(),
         }
     }
 }
 
 impl Display for Device {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_fmt(format_args!("{} {{\n", self.name))?;
+        match &self.label {
+            Some(label) => f.write_fmt(format_args!("{} \"{}\" {{\n", self.name, label))?,
+            None => f.write_fmt(format_args!("{} {{\n", self.name))?,
+        }
         for (key, param) in self.params.iter() {
             f.write_fmt(format_args!(
                 "    {}: {} ({})\n",
@@ -99,6 +279,22 @@ impl Display for Device {
                 }
             ))?;
         }
+        for (i, slot) in self.slots.iter().enumerate() {
+            f.write_fmt(format_args!("    slot {}: {} {{\n", i, slot.name))?;
+            for (key, param) in slot.params.iter() {
+                f.write_fmt(format_args!("        {}: {}\n", key, param))?;
+            }
+            f.write_str("    }\n")?;
+        }
+        for (hash, reagent) in self.reagents.iter() {
+            f.write_fmt(format_args!(
+                "    reagent {}: {} (required {}, recipe {})\n",
+                hash, reagent.contents, reagent.required, reagent.recipe
+            ))?;
+        }
+        if !self.memory.is_empty() {
+            f.write_fmt(format_args!("    memory: {} addresses\n", self.memory.len()))?;
+        }
         f.write_str("}")
     }
 }
@@ -36,7 +36,7 @@ impl Display for ParamKind {
 }
 
 /// Parameter type.
-#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub enum Param {
     Read(f64),
     Write(f64),
@@ -77,6 +77,14 @@ impl Param {
             Read(_) => Err(DeviceError::ParamReadOnly),
         }
     }
+
+    /// Set parameter value regardless of the parameter kind.
+    pub fn set(&mut self, val: f64) {
+        use Param::*;
+        match self {
+            Read(v) | Write(v) | ReadWrite(v) => *v = val,
+        }
+    }
 }
 
 impl Display for Param {
@@ -0,0 +1,40 @@
+//! Device reagent types.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::ReagentMode;
+
+/// Device reagent amounts.
+#[derive(Copy, Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct Reagent {
+    /// Amount of the reagent the device contains.
+    pub contents: f64,
+    /// Amount of the reagent the device requires (e.g. for its current recipe).
+    pub required: f64,
+    /// Amount of the reagent in the device's recipe.
+    pub recipe: f64,
+}
+
+impl Reagent {
+    /// Reagent contents constructor.
+    pub fn contents(contents: f64) -> Self {
+        Self {
+            contents,
+            ..Self::default()
+        }
+    }
+
+    /// Read the amount for a reagent mode.
+    pub fn read(&self, mode: ReagentMode) -> f64 {
+        match mode {
+            ReagentMode::Contents => self.contents,
+            ReagentMode::Required => self.required,
+            ReagentMode::Recipe => self.recipe,
+        }
+    }
+}
+
+/// Reagent table keyed by reagent hash.
+pub type Reagents = HashMap<i64, Reagent>;
@@ -0,0 +1,112 @@
+//! Device slot and slot kind types.
+use std::{fmt, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use super::param::{Param, ParamKind, Params};
+use super::DeviceError;
+
+/// Slot logic types common to all slots.
+pub const SLOT_PARAMS: [&str; 8] = [
+    "Occupied",
+    "OccupantHash",
+    "Quantity",
+    "Damage",
+    "Class",
+    "MaxQuantity",
+    "PrefabHash",
+    "SortingClass",
+];
+
+/// Slot kind type.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SlotKind {
+    pub name: String,
+    pub params: Vec<ParamKind>,
+}
+
+impl SlotKind {
+    /// New slot kind with the common (read only) slot logic types.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        let name = name.into();
+        let params = SLOT_PARAMS
+            .iter()
+            .map(|p| ParamKind::Read(p.to_string()))
+            .collect();
+        Self { name, params }
+    }
+
+    /// Make a new slot of this kind.
+    pub fn make(&self) -> Slot {
+        let name = self.name.clone();
+        let params = self.params.iter().map(|pk| pk.make()).collect();
+        Slot { name, params }
+    }
+}
+
+/// Device slot type.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Slot {
+    pub name: String,
+    pub params: Params,
+}
+
+impl Slot {
+    /// Try to get a specified slot parameter reference.
+    pub fn try_get_param(&self, param: &String) -> Result<&Param, DeviceError> {
+        self.params
+            .get(param)
+            .ok_or(DeviceError::ParamUnknown(param.clone()))
+    }
+
+    /// Try to get a mutable specified slot parameter reference.
+    pub fn try_get_mut_param(&mut self, param: &String) -> Result<&mut Param, DeviceError> {
+        self.params
+            .get_mut(param)
+            .ok_or(DeviceError::ParamUnknown(param.clone()))
+    }
+
+    /// Read from a slot parameter.
+    ///
+    /// Fails if a parameter for the key does not exist, or if the parameter kind is write only.
+    pub fn read<K>(&self, param: K) -> Result<f64, DeviceError>
+    where
+        K: Into<String>,
+    {
+        self.try_get_param(&param.into()).and_then(Param::read)
+    }
+
+    /// Write to a slot parameter.
+    ///
+    /// Fails if a parameter for the key does not exist, or if the parameter kind is read only.
+    pub fn write<K, V>(&mut self, param: K, val: V) -> Result<(), DeviceError>
+    where
+        K: Into<String>,
+        V: Into<f64>,
+    {
+        self.try_get_mut_param(&param.into())
+            .and_then(|p| p.write(val.into()))
+    }
+
+    /// Set a slot parameter regardless of its kind (e.g. to simulate inserting an item).
+    ///
+    /// Fails if a parameter for the key does not exist.
+    pub fn set<K, V>(&mut self, param: K, val: V) -> Result<(), DeviceError>
+    where
+        K: Into<String>,
+        V: Into<f64>,
+    {
+        self.try_get_mut_param(&param.into())
+            .map(|p| p.set(val.into()))
+    }
+}
+
+impl Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_fmt(format_args!("{} {{\n", self.name))?;
+        for (key, param) in self.params.iter() {
+            f.write_fmt(format_args!("    {}: {}\n", key, param))?;
+        }
+        f.write_str("}")
+    }
+}
@@ -18,8 +18,11 @@
 //! `mem_size-1`-th register and likewise the `mem_size-2`-th register for `ra`,
 //! regardless of how the `sp` and `ra` *aliases* are set.
 //!
+//! Numeric edge cases (index rounding, division by zero, domain errors, NaN and equality) follow
+//! the game by default, but can be configured via [`with_semantics`][`ICState::with_semantics`]
+//! (see [`Semantics`](state::Semantics)).
+//!
 //! ### TODO
-//! - Check how the game handles domain errors on math functions
 //! - Work on constant values (e.g. loading device kinds -> their hashes)
 #![feature(bool_to_option)]
 #![feature(result_cloned)]
@@ -28,15 +31,47 @@
 
 use std::{fmt, fmt::Display};
 
-use mips_parser::prelude::Expr;
+use mips_parser::prelude::{Expr, Node};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 
 /// Type for either an expression, or for representing a blank line.
+///
+/// Serialized with the expression as source text (e.g. `Expr(0, "add r0 r1 r2")`).
 #[derive(Clone, PartialEq, Debug)]
 pub enum Line {
     Expr(usize, Expr),
     Blank(usize),
 }
 
+/// Serialized form of [`Line`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Line")]
+enum LineRepr {
+    Expr(usize, String),
+    Blank(usize),
+}
+
+impl Serialize for Line {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Line::Expr(i, expr) => LineRepr::Expr(*i, expr.to_string()),
+            Line::Blank(i) => LineRepr::Blank(*i),
+        }
+        .serialize(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Line {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        match LineRepr::deserialize(d)? {
+            LineRepr::Expr(i, source) => Expr::try_from_str(&source)
+                .map(|expr| Line::Expr(i, expr))
+                .map_err(|e| D::Error::custom(format!("invalid line {} `{}`: {:?}", i, source, e))),
+            LineRepr::Blank(i) => Ok(Line::Blank(i)),
+        }
+    }
+}
+
 impl Display for Line {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -47,10 +82,12 @@ impl Display for Line {
 }
 
 pub mod device;
+pub mod scenario;
 pub mod simulator;
 pub mod state;
 pub mod test_utils;
 pub mod watcher;
+pub mod world;
 
 use device::Device;
 use state::{AliasKind, ICState};
@@ -59,6 +96,11 @@ pub const MEM_SIZE: usize = 18;
 pub const DEV_SIZE: usize = 6;
 pub const STACK_SIZE: usize = 512;
 
+/// Number of lines an IC10 executes per game tick.
+pub const INSTRUCTIONS_PER_TICK: usize = 128;
+/// Duration of a game tick in seconds.
+pub const TICK_SECONDS: f64 = 0.5;
+
 impl Default for ICState<MEM_SIZE, DEV_SIZE, STACK_SIZE> {
     /// New Stationeers default IC state (without the self device set).
     fn default() -> Self {
@@ -72,10 +114,21 @@ impl Default for ICState<MEM_SIZE, DEV_SIZE, STACK_SIZE> {
 
 /// All-in-one module.
 pub mod prelude {
-    pub use crate::device::{Device, DeviceKind, DeviceKinds};
-    pub use crate::simulator::{ICSimulator, ICSimulatorError, ICSimulatorDefault};
-    pub use crate::state::{AliasKind, DevId, ICState, ICStateError};
-    pub use crate::{Line, DEV_SIZE, MEM_SIZE, STACK_SIZE};
+    pub use crate::device::{
+        Atmosphere, Behaviors, Device, DeviceBehavior, DeviceKind, DeviceKinds, Environment,
+        Reagent, Reagents, Slot, SlotKind,
+    };
+    pub use crate::scenario::{Scenario, ScenarioError, ScenarioReport};
+    pub use crate::simulator::{
+        ICSimulator, ICSimulatorDefault, ICSimulatorError, TickEnd, TickReport,
+    };
+    pub use crate::state::{
+        AliasKind, DevId, DomainMode, ICState, ICStateError, IndexRounding, NanMode, ReagentMode,
+        SeededRng, Semantics,
+    };
+    pub use crate::watcher::{Breakpoint, Cmp, Condition, Watch, WatchReport, Watcher};
+    pub use crate::world::{RefId, World, WorldDefault, WorldError};
+    pub use crate::{Line, DEV_SIZE, INSTRUCTIONS_PER_TICK, MEM_SIZE, STACK_SIZE, TICK_SECONDS};
     pub use ron::de::from_reader;
     pub use std::fs::File;
 }
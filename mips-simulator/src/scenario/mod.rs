@@ -0,0 +1,398 @@
+//! Scenario regression tests.
+//!
+//! A [`Scenario`] (written in RON, like the device kinds file) declares the devices on the pins
+//! and network of an IC, a timeline of parameter changes at given ticks, and assertions on
+//! parameters and registers at given ticks, e.g.
+//!
+//! ```ron
+//! (
+//!     name: "Pump control",
+//!     ticks: Some(10),
+//!     pins: {
+//!         0: (kind: "VolumePump"),
+//!         1: (kind: "GasSensor", params: { "Pressure": 50.0 }),
+//!     },
+//!     timeline: [
+//!         (tick: 5, target: Pin(1, "Pressure"), value: 120.0),
+//!     ],
+//!     assertions: [
+//!         (tick: 4, target: Pin(0, "On"), cmp: Eq, value: 1.0),
+//!         (tick: 7, target: Pin(0, "On"), cmp: Eq, value: 0.0),
+//!     ],
+//! )
+//! ```
+//!
+//! The runner ([`Scenario::run`]) simulates the scenario tick by tick. At each tick `t` (i.e.
+//! after `t` ticks have elapsed) the assertions of tick `t` are checked, then the timeline
+//! changes of tick `t` are applied, before the tick itself is simulated.
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Error as IOError;
+use std::path::Path;
+use std::{fmt, fmt::Display};
+
+use ron::Error as RonError;
+use serde::{Deserialize, Serialize};
+
+use mips_parser::prelude::{MipsParserError, Node, Program};
+use util::impl_from_error;
+
+use crate::device::{Behaviors, Device, DeviceKinds};
+use crate::simulator::{ICSimulator, ICSimulatorError};
+use crate::state::{DevId, ICState, SeededRng};
+use crate::watcher::Cmp;
+use crate::{DEV_SIZE, MEM_SIZE, STACK_SIZE};
+
+/// Scenario error type.
+#[derive(Debug)]
+pub enum ScenarioError {
+    IOError(IOError),
+    RonError(RonError),
+    // Boxed, as parser errors are large
+    MipsParserError(Box<MipsParserError>),
+    ICSimulatorError(ICSimulatorError),
+
+    NoProgram,
+    UnknownKind(String),
+    UnknownParam(String),
+    UnknownPin(usize),
+}
+
+impl_from_error!(ScenarioError, IOError, RonError, ICSimulatorError);
+
+impl From<MipsParserError> for ScenarioError {
+    fn from(e: MipsParserError) -> Self {
+        ScenarioError::MipsParserError(Box::new(e))
+    }
+}
+
+/// Shortcut type for scenario results.
+pub type ScenarioResult<T> = Result<T, ScenarioError>;
+
+/// Device declared by a scenario.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct ScenarioDevice {
+    /// Device kind name (in the device kinds).
+    pub kind: String,
+    /// Device label.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Device reference id.
+    #[serde(default)]
+    pub reference_id: Option<i64>,
+    /// Initial parameter values.
+    #[serde(default)]
+    pub params: BTreeMap<String, f64>,
+}
+
+impl ScenarioDevice {
+    /// Try to make the device from its kind.
+    pub fn make(&self, kinds: &DeviceKinds) -> ScenarioResult<Device> {
+        let kind = kinds
+            .get(&self.kind)
+            .ok_or_else(|| ScenarioError::UnknownKind(self.kind.clone()))?;
+        let mut dev = kind.make();
+        dev.label = self.label.clone();
+        dev.reference_id = self.reference_id;
+        for (param, val) in self.params.iter() {
+            dev.set(param.as_str(), *val)
+                .map_err(|_| ScenarioError::UnknownParam(format!("{}.{}", self.kind, param)))?;
+        }
+        Ok(dev)
+    }
+}
+
+/// Register or device parameter targeted by a timeline change or an assertion.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum Target {
+    /// Memory register by index.
+    Reg(usize),
+    /// Parameter of the self device.
+    Db(String),
+    /// Parameter of the device on a pin.
+    Pin(usize, String),
+    /// Parameter of the network devices with a label (or name).
+    ///
+    /// Changes apply to all such devices, and assertions check the first.
+    Network(String, String),
+}
+
+impl Target {
+    /// Get the current value of the target (`None` if unset).
+    pub fn value<const MS: usize, const DS: usize, const SS: usize>(
+        &self,
+        state: &ICState<MS, DS, SS>,
+    ) -> Option<f64> {
+        let (dev, param) = match self {
+            Target::Reg(i) => return state.get_mem(*i).ok().cloned(),
+            Target::Db(p) => (state.get_dev(DevId::DevSelf).ok(), p),
+            Target::Pin(i, p) => (state.get_dev(DevId::DevBuf(*i)).ok(), p),
+            Target::Network(name, p) => (
+                state
+                    .network
+                    .values()
+                    .flatten()
+                    .find(|dev| Self::is_named(dev, name)),
+                p,
+            ),
+        };
+        dev.and_then(|dev| dev.params.get(param))
+            .map(|param| param.value())
+    }
+
+    /// Set the target to a value (regardless of the parameter kind).
+    ///
+    /// Returns whether the target was set.
+    pub fn set<const MS: usize, const DS: usize, const SS: usize>(
+        &self,
+        state: &mut ICState<MS, DS, SS>,
+        val: f64,
+    ) -> bool {
+        match self {
+            Target::Reg(i) => state.set_mem(*i, val).is_ok(),
+            Target::Db(p) => Self::set_dev(state.get_mut_dev(DevId::DevSelf).ok(), p, val),
+            Target::Pin(i, p) => Self::set_dev(state.get_mut_dev(DevId::DevBuf(*i)).ok(), p, val),
+            Target::Network(name, p) => {
+                let mut set = false;
+                for dev in state.network.values_mut().flatten() {
+                    if Self::is_named(dev, name) {
+                        set |= Self::set_dev(Some(dev), p, val);
+                    }
+                }
+                set
+            }
+        }
+    }
+
+    fn set_dev(dev: Option<&mut Device>, param: &str, val: f64) -> bool {
+        dev.is_some_and(|dev| dev.set(param, val).is_ok())
+    }
+
+    fn is_named(dev: &Device, name: &str) -> bool {
+        dev.label.as_deref().unwrap_or(&dev.name) == name
+    }
+}
+
+/// Change of a target value at a tick.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Change {
+    pub tick: usize,
+    pub target: Target,
+    pub value: f64,
+}
+
+/// Assertion on a target value at a tick.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Assertion {
+    pub tick: usize,
+    pub target: Target,
+    pub cmp: Cmp,
+    pub value: f64,
+}
+
+/// Scenario of devices, value changes and assertions over time.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Scenario {
+    #[serde(default)]
+    pub name: String,
+    /// Program source (if not given to the runner).
+    #[serde(default)]
+    pub program: Option<String>,
+    /// Number of ticks to simulate (by default the last tick of the timeline or assertions).
+    #[serde(default)]
+    pub ticks: Option<usize>,
+    /// Whether to simulate devices with the built-in behaviors.
+    #[serde(default)]
+    pub behaviors: bool,
+    /// Seed of the `rand` instruction (by default seeded from entropy).
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Devices by pin.
+    #[serde(default)]
+    pub pins: BTreeMap<usize, ScenarioDevice>,
+    /// Network devices.
+    #[serde(default)]
+    pub network: Vec<ScenarioDevice>,
+    #[serde(default)]
+    pub timeline: Vec<Change>,
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+}
+
+impl Scenario {
+    /// Try to load a scenario from a RON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> ScenarioResult<Self> {
+        let file = File::open(path)?;
+        Ok(ron::de::from_reader(file)?)
+    }
+
+    /// Try to parse a scenario from a RON string.
+    pub fn from_ron(s: &str) -> ScenarioResult<Self> {
+        Ok(ron::de::from_str(s)?)
+    }
+
+    /// Number of ticks to simulate.
+    pub fn ticks(&self) -> usize {
+        self.ticks.unwrap_or_else(|| {
+            let changes = self.timeline.iter().map(|c| c.tick);
+            let assertions = self.assertions.iter().map(|a| a.tick);
+            changes.chain(assertions).max().unwrap_or(0)
+        })
+    }
+
+    /// Try to set up a default simulator for the scenario, with the scenario program.
+    pub fn simulator(
+        &self,
+        kinds: &DeviceKinds,
+    ) -> ScenarioResult<ICSimulator<MEM_SIZE, DEV_SIZE, STACK_SIZE>> {
+        let source = self.program.as_ref().ok_or(ScenarioError::NoProgram)?;
+        let program = Program::try_from_str(source)?;
+        let mut sim = ICSimulator::new(ICState::default(), program);
+        self.setup(&mut sim, kinds)?;
+        Ok(sim)
+    }
+
+    /// Try to set the scenario devices (and behaviors) of a simulator.
+    pub fn setup<const MS: usize, const DS: usize, const SS: usize>(
+        &self,
+        sim: &mut ICSimulator<MS, DS, SS>,
+        kinds: &DeviceKinds,
+    ) -> ScenarioResult<()> {
+        for (pin, dev) in self.pins.iter() {
+            let dev = dev.make(kinds)?;
+            sim.state
+                .set_dev(DevId::DevBuf(*pin), Some(dev))
+                .map_err(|_| ScenarioError::UnknownPin(*pin))?;
+        }
+        for dev in self.network.iter() {
+            sim.state.dev_network_add(dev.make(kinds)?);
+        }
+        if self.behaviors {
+            sim.behaviors = Behaviors::builtin();
+        }
+        if let Some(seed) = self.seed {
+            sim.state.set_rng(SeededRng::new(seed));
+        }
+        Ok(())
+    }
+
+    /// Try to run the scenario with its own program.
+    pub fn run(&self, kinds: &DeviceKinds) -> ScenarioResult<ScenarioReport> {
+        let mut sim = self.simulator(kinds)?;
+        self.run_simulator(&mut sim)
+    }
+
+    /// Try to run the scenario against a simulator (already set up, see [`setup`](Self::setup)).
+    ///
+    /// Fails if the simulation fails, or if a timeline change targets an unset register or
+    /// parameter; failed assertions are in the report.
+    pub fn run_simulator<const MS: usize, const DS: usize, const SS: usize>(
+        &self,
+        sim: &mut ICSimulator<MS, DS, SS>,
+    ) -> ScenarioResult<ScenarioReport> {
+        let ticks = self.ticks();
+        let mut results = Vec::with_capacity(self.assertions.len());
+        for tick in 0..=ticks {
+            for assertion in self.assertions.iter().filter(|a| a.tick == tick) {
+                let actual = assertion.target.value(&sim.state);
+                let passed = actual.is_some_and(|a| assertion.cmp.test(a, assertion.value));
+                results.push(AssertionResult {
+                    assertion: assertion.clone(),
+                    actual,
+                    passed,
+                });
+            }
+            if tick == ticks {
+                break;
+            }
+            for change in self.timeline.iter().filter(|c| c.tick == tick) {
+                if !change.target.set(&mut sim.state, change.value) {
+                    return Err(ScenarioError::UnknownParam(change.target.to_string()));
+                }
+            }
+            sim.tick()?;
+        }
+        Ok(ScenarioReport {
+            name: self.name.clone(),
+            ticks,
+            results,
+        })
+    }
+}
+
+/// Result of a scenario assertion.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AssertionResult {
+    pub assertion: Assertion,
+    /// Value of the target when checked (`None` if unset).
+    pub actual: Option<f64>,
+    pub passed: bool,
+}
+
+/// Report of a scenario run.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScenarioReport {
+    pub name: String,
+    /// Number of ticks simulated.
+    pub ticks: usize,
+    pub results: Vec<AssertionResult>,
+}
+
+impl ScenarioReport {
+    /// Did all assertions pass.
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|r| r.passed)
+    }
+
+    /// Iterator over the failed assertions.
+    pub fn failures(&self) -> impl Iterator<Item = &AssertionResult> {
+        self.results.iter().filter(|r| !r.passed)
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Target::Reg(i) => write!(f, "r{}", i),
+            Target::Db(p) => write!(f, "db.{}", p),
+            Target::Pin(i, p) => write!(f, "d{}.{}", i, p),
+            Target::Network(name, p) => write!(f, "{}.{}", name, p),
+        }
+    }
+}
+
+impl Display for AssertionResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let a = &self.assertion;
+        write!(
+            f,
+            "{} tick {}: {} {} {}",
+            if self.passed { "PASS" } else { "FAIL" },
+            a.tick,
+            a.target,
+            a.cmp,
+            a.value
+        )?;
+        match self.actual {
+            Some(actual) => write!(f, " (actual {})", actual),
+            None => f.write_str(" (actual unset)"),
+        }
+    }
+}
+
+impl Display for ScenarioReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for result in self.results.iter() {
+            writeln!(f, "{}", result)?;
+        }
+        let failed = self.failures().count();
+        write!(
+            f,
+            "scenario `{}`: {} passed, {} failed ({} ticks)",
+            self.name,
+            self.results.len() - failed,
+            failed,
+            self.ticks
+        )
+    }
+}
@@ -0,0 +1,208 @@
+//! Per-step state deltas for reverse stepping.
+use serde::{Deserialize, Serialize};
+
+use crate::device::{Device, Environment};
+use crate::state::{AliasKind, DevId, DevRef, ICState, SeededRng, UndoLog};
+
+/// State values before a step, used to compute its delta.
+///
+/// Aliases and devices are not copied, but logged by the state on their first change.
+pub(crate) struct Snapshot<const MS: usize, const DS: usize, const SS: usize> {
+    mem: [f64; MS],
+    stk: [f64; SS],
+    rng: SeededRng,
+    next_line_index: usize,
+    ticks: usize,
+    sleep_ticks: usize,
+}
+
+impl<const MS: usize, const DS: usize, const SS: usize> Snapshot<MS, DS, SS> {
+    /// Take a snapshot of a state, and the simulator tick counters,
+    /// starting the state undo log.
+    pub(crate) fn take(state: &mut ICState<MS, DS, SS>, ticks: usize, sleep_ticks: usize) -> Self {
+        state.undo_log = Some(UndoLog::default());
+        Self {
+            mem: state.mem,
+            stk: state.stk,
+            rng: state.rng,
+            next_line_index: state.next_line_index,
+            ticks,
+            sleep_ticks,
+        }
+    }
+}
+
+/// Changes made by a single step, holding the values from before the step.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct StepDelta {
+    /// Index of the executed line.
+    pub line: usize,
+    /// Memory registers changed (index, old value).
+    pub mem: Vec<(usize, f64)>,
+    /// Stack slots changed (index, old value).
+    pub stk: Vec<(usize, f64)>,
+    /// Aliases changed (name, old alias kind if set).
+    pub map: Vec<(String, Option<AliasKind>)>,
+    /// Devices changed (device, old device if set).
+    pub dev: Vec<(DevId, Option<Device>)>,
+    /// Network devices changed (hash, index among devices of the hash, old device).
+    pub network: Vec<(i64, usize, Device)>,
+    /// Random number generator, if changed.
+    pub rng: Option<SeededRng>,
+    /// Number of game ticks elapsed (not counting the tick of the step).
+    #[serde(default)]
+    pub ticks: usize,
+    /// Number of ticks the program remained suspended for by `sleep`.
+    #[serde(default)]
+    pub sleep_ticks: usize,
+    /// Device environment, if changed by the device behaviors of a tick ending after the step.
+    #[serde(default)]
+    pub env: Option<Environment>,
+}
+
+/// Device register and network changes (as in [`StepDelta`]).
+type DeviceChanges = (Vec<(DevId, Option<Device>)>, Vec<(i64, usize, Device)>);
+
+/// Split the logged devices that changed into device register and network changes.
+fn changed_devices<const MS: usize, const DS: usize, const SS: usize>(
+    log: Vec<(DevRef, Option<Device>)>,
+    state: &ICState<MS, DS, SS>,
+) -> DeviceChanges {
+    let mut dev = Vec::new();
+    let mut network = Vec::new();
+    for (r, old) in log.into_iter() {
+        let new = match r {
+            DevRef::Id(di) => state.get_dev_opt(di).ok().and_then(Option::as_ref),
+            DevRef::Network(hash, i) => state.network.get(&hash).and_then(|v| v.get(i)),
+        };
+        // Compare bitwise so that NaN is only a change from non-NaN
+        let changed = match (&old, new) {
+            (Some(a), Some(b)) => !a.identical(b),
+            (a, b) => a.is_some() != b.is_some(),
+        };
+        if !changed {
+            continue;
+        }
+        match (r, old) {
+            (DevRef::Id(di), old) => dev.push((di, old)),
+            (DevRef::Network(hash, i), Some(old)) => network.push((hash, i, old)),
+            (DevRef::Network(..), None) => {}
+        }
+    }
+    (dev, network)
+}
+
+impl StepDelta {
+    /// Compute the delta between a snapshot and the state after a step,
+    /// ending the state undo log.
+    pub(crate) fn diff<const MS: usize, const DS: usize, const SS: usize>(
+        before: Snapshot<MS, DS, SS>,
+        state: &mut ICState<MS, DS, SS>,
+    ) -> Self {
+        fn changed_f64<'a>(
+            old: &'a [f64],
+            new: &'a [f64],
+        ) -> impl Iterator<Item = (usize, f64)> + 'a {
+            old.iter()
+                .zip(new.iter())
+                .enumerate()
+                // Compare bits so that NaN is only a change from non-NaN
+                .filter(|(_, (a, b))| a.to_bits() != b.to_bits())
+                .map(|(i, (a, _))| (i, *a))
+        }
+
+        let Snapshot {
+            mem: before_mem,
+            stk: before_stk,
+            rng: before_rng,
+            next_line_index: line,
+            ticks,
+            sleep_ticks,
+        } = before;
+
+        let mem = changed_f64(&before_mem, &state.mem).collect();
+        let stk = changed_f64(&before_stk, &state.stk).collect();
+
+        let log = state.undo_log.take().unwrap_or_default();
+        let map = log
+            .map
+            .into_iter()
+            .filter(|(k, v)| state.map.get(k) != v.as_ref())
+            .collect();
+
+        let (dev, network) = changed_devices(log.dev, state);
+        let rng = (before_rng != state.rng).then_some(before_rng);
+
+        Self {
+            line,
+            mem,
+            stk,
+            map,
+            dev,
+            network,
+            rng,
+            ticks,
+            sleep_ticks,
+            env: None,
+        }
+    }
+
+    /// Fold the changes made after the step by the device behaviors of a tick into the delta,
+    /// ending the state undo log.
+    ///
+    /// Values already in the delta are older, so are kept.
+    pub(crate) fn fold<const MS: usize, const DS: usize, const SS: usize>(
+        &mut self,
+        state: &mut ICState<MS, DS, SS>,
+        env: Environment,
+    ) {
+        let log = state.undo_log.take().unwrap_or_default();
+        let (dev, network) = changed_devices(log.dev, state);
+        for (di, old) in dev.into_iter() {
+            if !self.dev.iter().any(|(d, _)| *d == di) {
+                self.dev.push((di, old));
+            }
+        }
+        for (hash, i, old) in network.into_iter() {
+            if !self.network.iter().any(|(h, j, _)| (*h, *j) == (hash, i)) {
+                self.network.push((hash, i, old));
+            }
+        }
+        self.env.get_or_insert(env);
+    }
+
+    /// Restore the state to before the step
+    /// (the simulator restores its tick counters itself).
+    pub(crate) fn undo<const MS: usize, const DS: usize, const SS: usize>(
+        self,
+        state: &mut ICState<MS, DS, SS>,
+    ) {
+        for (i, v) in self.mem.into_iter() {
+            state.mem[i] = v;
+        }
+        for (i, v) in self.stk.into_iter() {
+            state.stk[i] = v;
+        }
+        for (k, v) in self.map.into_iter() {
+            match v {
+                Some(v) => state.map.insert(k, v),
+                None => state.map.remove(&k),
+            };
+        }
+        for (di, dev) in self.dev.into_iter() {
+            match di {
+                DevId::DevBuf(i) => state.dev[i] = dev,
+                DevId::DevSelf => state.dev_self = dev,
+            }
+        }
+        for (hash, i, dev) in self.network.into_iter() {
+            if let Some(old) = state.network.get_mut(&hash).and_then(|v| v.get_mut(i)) {
+                *old = dev;
+            }
+        }
+        if let Some(rng) = self.rng {
+            state.rng = rng;
+        }
+        state.next_line_index = self.line;
+    }
+}
@@ -1,18 +1,27 @@
 //! IC10 simulator.
 use mips_parser::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::state::{ExecResult, ICState, ICStateError};
-use crate::{MEM_SIZE, DEV_SIZE, STACK_SIZE, Line};
+mod history;
+use history::Snapshot;
+pub use history::StepDelta;
+
+use crate::device::{Behaviors, Environment};
+use crate::state::{ExecResult, ICState, ICStateError, SeededRng, UndoLog};
+use crate::watcher::Watcher;
+use crate::{Line, DEV_SIZE, INSTRUCTIONS_PER_TICK, MEM_SIZE, STACK_SIZE, TICK_SECONDS};
 
 #[derive(Debug)]
 pub enum ICSimulatorError {
     StateError(ICStateError),
     LineError(usize),
+    HistoryError(usize),
 }
 
 #[derive(Copy, Clone, Debug)]
 pub enum SimStatus {
     Running(usize),
+    Stopped(usize),
     Finished(usize),
 }
 
@@ -20,6 +29,7 @@ impl SimStatus {
     pub fn index(&self) -> usize {
         match self {
             &SimStatus::Running(i) => i,
+            &SimStatus::Stopped(i) => i,
             &SimStatus::Finished(i) => i,
         }
     }
@@ -27,10 +37,61 @@ impl SimStatus {
 
 pub type ICSimulatorResult = Result<SimStatus, ICSimulatorError>;
 
-#[derive(Clone, Debug)]
+/// Reason a simulated game tick ended.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TickEnd {
+    /// The instruction budget for the tick was used up.
+    Budget,
+    /// A `yield` was executed.
+    Yield,
+    /// A `sleep` was executed, suspending the program for the contained number of ticks.
+    Sleep(usize),
+    /// The tick was spent suspended by an earlier `sleep`.
+    Sleeping,
+    /// The watcher triggered (see [`Watcher::report`]).
+    Break,
+    /// The program ran out of lines.
+    Finished,
+}
+
+/// Report of a single simulated game tick.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TickReport {
+    /// Index of the tick (the number of ticks elapsed before it).
+    pub tick: usize,
+    /// Number of lines executed during the tick.
+    pub steps: usize,
+    /// Why the tick ended.
+    pub end: TickEnd,
+}
+
+/// Shortcut type for tick results.
+pub type TickResult = Result<TickReport, ICSimulatorError>;
+
+/// IC10 simulator.
+///
+/// Serializable (e.g. to RON or JSON) as a snapshot of a paused simulation, with the exception
+/// of the device behaviors (which are code, so must be set again after deserializing).
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct ICSimulator<const MS: usize, const DS: usize, const SS: usize> {
     pub state: ICState<MS, DS, SS>,
     pub lines: Vec<Line>,
+    pub watcher: Watcher,
+    /// Environment of the simulated devices.
+    pub env: Environment,
+    // Device behaviors, updated each tick
+    #[serde(skip)]
+    pub(crate) behaviors: Behaviors,
+    // Whether to record step deltas
+    pub(crate) record_history: bool,
+    // Deltas of the steps executed so far (if recording)
+    pub(crate) history: Vec<StepDelta>,
+    // Number of lines executed per game tick
+    pub(crate) instructions_per_tick: usize,
+    // Number of game ticks elapsed
+    pub(crate) ticks: usize,
+    // Number of ticks remaining to spend suspended by `sleep`
+    pub(crate) sleep_ticks: usize,
 }
 
 // Alias for a simulator of the default Stationeers IC state.
@@ -42,7 +103,56 @@ impl<const MS: usize, const DS: usize, const SS: usize>
     /// Construct new IC simulator.
     pub fn new(state: ICState<MS, DS, SS>, program: Program) -> Self {
         let lines = Self::program_to_lines(program);
-        Self { state, lines }
+        Self {
+            state,
+            lines,
+            watcher: Watcher::new(),
+            env: Environment::new(),
+            behaviors: Behaviors::new(),
+            record_history: false,
+            history: Vec::new(),
+            instructions_per_tick: INSTRUCTIONS_PER_TICK,
+            ticks: 0,
+            sleep_ticks: 0,
+        }
+    }
+
+    /// Builder helper to set the state watcher.
+    pub fn with_watcher(mut self, watcher: Watcher) -> Self {
+        self.watcher = watcher;
+        self
+    }
+
+    /// Builder helper to set the device behaviors, updated at the end of each tick.
+    ///
+    /// Note that in a [`World`](crate::world::World) the world behaviors should be set instead.
+    pub fn with_behaviors(mut self, behaviors: Behaviors) -> Self {
+        self.behaviors = behaviors;
+        self
+    }
+
+    /// Builder helper to set the device environment.
+    pub fn with_environment(mut self, env: Environment) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Builder helper to seed the random number generator of the state.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.state.set_rng(SeededRng::new(seed));
+        self
+    }
+
+    /// Builder helper to enable recording step deltas, for stepping backwards.
+    pub fn with_history(mut self) -> Self {
+        self.record_history = true;
+        self
+    }
+
+    /// Builder helper to set the number of lines executed per game tick.
+    pub fn with_instructions_per_tick(mut self, n: usize) -> Self {
+        self.instructions_per_tick = n;
+        self
     }
 
     /// Load a new state.
@@ -90,33 +200,72 @@ impl<const MS: usize, const DS: usize, const SS: usize>
     /// Get the status of this simulator.
     pub fn status(&self) -> SimStatus {
         let i = self.state.next_line_index;
-        if !self.is_finished() {
+        if self.watcher.triggered() {
+            SimStatus::Stopped(i)
+        } else if self.is_finished() {
+            SimStatus::Finished(i)
+        } else {
             SimStatus::Running(i)
+        }
+    }
+
+    /// Get the number of lines executed per game tick.
+    pub fn instructions_per_tick(&self) -> usize {
+        self.instructions_per_tick
+    }
+
+    /// Get the number of game ticks elapsed.
+    pub fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    /// Get the number of ticks the program remains suspended for by `sleep`.
+    pub fn sleep_ticks(&self) -> usize {
+        self.sleep_ticks
+    }
+
+    /// Number of whole ticks spanned by a sleep of `secs` seconds.
+    fn secs_to_ticks(secs: f64) -> usize {
+        if secs > 0.0 {
+            (secs / TICK_SECONDS).ceil() as usize
         } else {
-            SimStatus::Finished(i)
+            0
         }
     }
 
-    /// Step once through the program.
-    pub fn step(&mut self) -> ICSimulatorResult {
+    /// Execute the next line, advancing the next line index unless the line jumped.
+    fn exec_next(&mut self) -> Result<ExecResult, ICSimulatorError> {
         let i = self.state.next_line_index;
         if self.is_finished() {
             return Err(ICSimulatorError::LineError(i));
         }
+        let before = self
+            .record_history
+            .then(|| Snapshot::take(&mut self.state, self.ticks, self.sleep_ticks));
         let line = &self.lines[i];
-        let exec_res = self
-            .state
-            .exec_line(line)
-            .map_err(ICSimulatorError::StateError)?;
-        match exec_res {
-            ExecResult::Normal(jumped) => {
-                if !jumped {
-                    self.state.next_line_index += 1;
-                }
+        self.watcher.before_line(&self.state);
+        let exec_res = self.state.exec_line(line);
+        self.state.sync_shared();
+        if let Ok(res) = &exec_res {
+            match res {
+                ExecResult::Normal(true) => {}
+                _ => self.state.next_line_index += 1,
             }
-            ExecResult::Sleep(_) => {}
-            ExecResult::Yield => {}
+            self.watcher.after_line(&self.state, i);
+        }
+        // Record even failed steps, which may have partially changed the state
+        if let Some(before) = before {
+            self.history.push(StepDelta::diff(before, &mut self.state));
         }
+        exec_res.map_err(ICSimulatorError::StateError)
+    }
+
+    /// Step once through the program.
+    ///
+    /// Ignores the tick budget and any pending sleep; see [`tick`](Self::tick) for that.
+    /// Returns [`SimStatus::Stopped`] if the watcher triggered on the line.
+    pub fn step(&mut self) -> ICSimulatorResult {
+        self.exec_next()?;
         Ok(self.status())
     }
 
@@ -135,4 +284,156 @@ impl<const MS: usize, const DS: usize, const SS: usize>
         }
         Ok(self.status())
     }
+
+    /// Get the number of recorded steps.
+    pub fn steps(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Iterator over the recorded step deltas, oldest first.
+    pub fn iter_history(&self) -> impl Iterator<Item = &StepDelta> {
+        self.history.iter()
+    }
+
+    /// Step back `n` times, undoing the recorded steps.
+    ///
+    /// Fails, without stepping back, if fewer than `n` steps were recorded.
+    pub fn step_back(&mut self, n: usize) -> ICSimulatorResult {
+        if n > self.history.len() {
+            return Err(ICSimulatorError::HistoryError(n));
+        }
+        for _ in 0..n {
+            let mut delta = self.history.pop().unwrap();
+            self.ticks = delta.ticks;
+            self.sleep_ticks = delta.sleep_ticks;
+            if let Some(env) = delta.env.take() {
+                self.env = env;
+            }
+            delta.undo(&mut self.state);
+        }
+        self.watcher.reset();
+        Ok(self.status())
+    }
+
+    /// Go to the state after `step` recorded steps, stepping backwards or forwards as needed.
+    pub fn goto(&mut self, step: usize) -> ICSimulatorResult {
+        let steps = self.history.len();
+        if step <= steps {
+            self.step_back(steps - step)
+        } else {
+            self.step_n(step - steps)
+        }
+    }
+
+    /// Run the simulator until the watcher triggers or it is finished.
+    pub fn run_until_break(&mut self) -> ICSimulatorResult {
+        if self.watcher.before_start(&self.state) {
+            return Ok(self.status());
+        }
+        while !self.is_finished() {
+            if let SimStatus::Stopped(i) = self.step()? {
+                return Ok(SimStatus::Stopped(i));
+            }
+        }
+        Ok(self.status())
+    }
+
+    /// Simulate one game tick.
+    ///
+    /// Lines are executed until either the instruction budget is used up, a `yield` or `sleep` is
+    /// executed, the watcher triggers, or the program finishes. If the program is suspended by an
+    /// earlier `sleep` no lines are executed.
+    pub fn tick(&mut self) -> TickResult {
+        // The tick only counts as elapsed once its lines executed,
+        // so stepping back into a tick restores the number of ticks before it
+        let tick = self.ticks;
+        let report = self.tick_lines(tick);
+        self.ticks += 1;
+        self.tick_devices();
+        report
+    }
+
+    /// Update the devices by their behaviors for an elapsed tick.
+    ///
+    /// If recording history, the changes are folded into the last step delta,
+    /// so that stepping back over it undoes them.
+    fn tick_devices(&mut self) {
+        if self.behaviors.is_empty() {
+            return;
+        }
+        let env = match self.history.last() {
+            Some(_) if self.record_history => {
+                self.state.undo_log = Some(UndoLog::default());
+                Some(self.env.clone())
+            }
+            _ => None,
+        };
+        for r in self.state.dev_refs() {
+            let behavior = match self.state.get_dev_ref(r) {
+                Ok(dev) => self.behaviors.get(dev),
+                Err(_) => None,
+            };
+            if let (Some(behavior), Ok(dev)) = (behavior, self.state.get_mut_dev_ref(r)) {
+                behavior.tick(dev, &mut self.env);
+            }
+        }
+        self.env.tick += 1;
+        if let (Some(env), Some(delta)) = (env, self.history.last_mut()) {
+            delta.fold(&mut self.state, env);
+        }
+    }
+
+    /// Execute the lines of a tick.
+    fn tick_lines(&mut self, tick: usize) -> TickResult {
+        if self.sleep_ticks > 0 {
+            self.sleep_ticks -= 1;
+            let end = TickEnd::Sleeping;
+            return Ok(TickReport { tick, steps: 0, end });
+        }
+
+        let mut steps = 0;
+        if self.watcher.before_start(&self.state) {
+            let end = TickEnd::Break;
+            return Ok(TickReport { tick, steps, end });
+        }
+        let end = loop {
+            if self.is_finished() {
+                break TickEnd::Finished;
+            }
+            if steps >= self.instructions_per_tick {
+                break TickEnd::Budget;
+            }
+            let exec_res = self.exec_next()?;
+            steps += 1;
+            if let ExecResult::Sleep(secs) = exec_res {
+                // The current tick counts towards the sleep
+                self.sleep_ticks = Self::secs_to_ticks(secs).saturating_sub(1);
+            }
+            // A `yield` or `sleep` line can trigger the watcher too (a `sleep` still suspends)
+            if self.watcher.triggered() {
+                break TickEnd::Break;
+            }
+            match exec_res {
+                ExecResult::Normal(_) => {}
+                ExecResult::Yield => break TickEnd::Yield,
+                ExecResult::Sleep(_) => break TickEnd::Sleep(self.sleep_ticks),
+            }
+        };
+        Ok(TickReport { tick, steps, end })
+    }
+
+    /// Simulate up to `n` game ticks, stopping early if the program finishes.
+    ///
+    /// Returns a report for each elapsed tick.
+    pub fn run_ticks(&mut self, n: usize) -> Result<Vec<TickReport>, ICSimulatorError> {
+        let mut reports = Vec::new();
+        for _ in 0..n {
+            let report = self.tick()?;
+            reports.push(report);
+            if report.end == TickEnd::Finished {
+                break;
+            }
+        }
+        Ok(reports)
+    }
 }
@@ -61,7 +61,8 @@ impl_tuple_try_from_reducer!(
     [(A, B), (a, b), (0, 1), 2],
     [(A, B, C), (a, b, c), (0, 1, 2), 3],
     [(A, B, C, D), (a, b, c, d), (0, 1, 2, 3), 4],
-    [(A, B, C, D, E), (a, b, c, d, e), (0, 1, 2, 3, 4), 5]
+    [(A, B, C, D, E), (a, b, c, d, e), (0, 1, 2, 3, 4), 5],
+    [(A, B, C, D, E, F), (a, b, c, d, e, f), (0, 1, 2, 3, 4, 5), 6]
 );
 
 // ================================================================================================
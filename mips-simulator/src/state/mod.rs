@@ -1,9 +1,10 @@
 //! Integrated Circuit (IC10) simulator state.
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::{fmt, fmt::Debug, fmt::Display};
 
 use mips_parser::prelude::*;
+use serde::{Deserialize, Serialize};
 use util::{impl_from_error, is_as_inner};
 
 use std::num::{IntErrorKind, TryFromIntError};
@@ -11,14 +12,14 @@ use std::num::{IntErrorKind, TryFromIntError};
 use crate::device::{Device, DeviceError};
 use crate::Line;
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub enum DevId {
     DevBuf(usize),
     DevSelf,
 }
 
 /// Alias kind type.
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub enum AliasKind {
     MemId(usize),
     DevId(DevId),
@@ -70,9 +71,17 @@ pub enum BatchMode {
     Max,
 }
 
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ReagentMode {
+    Contents,
+    Required,
+    Recipe,
+}
+
 #[derive(Debug)]
 pub enum UnknownConstantError {
     BatchMode(f64),
+    ReagentMode(f64),
 }
 
 impl TryFrom<f64> for BatchMode {
@@ -92,6 +101,21 @@ impl TryFrom<f64> for BatchMode {
     }
 }
 
+impl TryFrom<f64> for ReagentMode {
+    type Error = UnknownConstantError;
+
+    fn try_from(v: f64) -> Result<ReagentMode, UnknownConstantError> {
+        use ReagentMode::*;
+        let mode = match v as i32 {
+            0 => Contents,
+            1 => Required,
+            2 => Recipe,
+            _ => return Err(UnknownConstantError::ReagentMode(v)),
+        };
+        Ok(mode)
+    }
+}
+
 /// Out of bound kinds.
 #[derive(Debug)]
 pub enum OutOfBounds {
@@ -102,6 +126,32 @@ pub enum OutOfBounds {
     Stack(usize),
 }
 
+/// Device addressed by reference id.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum DevRef {
+    Id(DevId),
+    // Network device (by hash and index of the devices of that hash)
+    Network(i64, usize),
+}
+
+/// Device reachable by more than one path (e.g. a device register and the network).
+#[derive(Clone, Debug)]
+pub(crate) struct SharedDevice {
+    // Device as last synchronized
+    dev: Device,
+    // Paths to the copies of the device
+    refs: Vec<DevRef>,
+}
+
+/// Values from before their first change by a line, kept while recording history.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct UndoLog {
+    // Aliases changed (name, old alias kind if set)
+    pub(crate) map: Vec<(String, Option<AliasKind>)>,
+    // Devices accessed mutably (path, old device if set)
+    pub(crate) dev: Vec<(DevRef, Option<Device>)>,
+}
+
 /// State simulator error type.
 #[derive(Debug)]
 pub enum ICStateError {
@@ -113,6 +163,10 @@ pub enum ICStateError {
 
     AliasUnset(String),
     AliasWrongKind(String),
+    DeviceIdUnknown(i64),
+    DomainError(String),
+    InvalidIndex(f64),
+    NanValue(usize),
     OutOfBounds(OutOfBounds),
     StackFull,
     StackEmpty,
@@ -131,6 +185,7 @@ impl_from_error!(
 );
 
 /// Ok result type for ICState::exec_line.
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum ExecResult {
     Normal(bool),
     Sleep(f64),
@@ -144,11 +199,16 @@ pub type MemRegs<const MS: usize> = [f64; MS];
 pub type Devices<const DS: usize> = [Option<Device>; DS];
 
 /// Integrated Circuit (IC10) simulator state.
-#[derive(Clone, Debug)]
+///
+/// Serializable (e.g. to RON or JSON) as a snapshot of a paused simulation.
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct ICState<const MS: usize, const DS: usize, const SS: usize> {
     // Memory register, device IO and stack buffers
+    #[serde(with = "serde_array")]
     pub(crate) mem: MemRegs<MS>,
+    #[serde(with = "serde_array")]
     pub(crate) dev: Devices<DS>,
+    #[serde(with = "serde_array")]
     pub(crate) stk: MemRegs<SS>,
     // Device of the state itself, if set
     pub(crate) dev_self: Option<Device>,
@@ -156,12 +216,32 @@ pub struct ICState<const MS: usize, const DS: usize, const SS: usize> {
     pub(crate) map: HashMap<String, AliasKind>,
     // Network devices (hash -> devices of hash)
     pub(crate) network: HashMap<i64, Vec<Device>>,
+    // Numeric semantics
+    #[serde(default)]
+    pub(crate) semantics: Semantics,
+    // Random number generator of `rand`
+    #[serde(default)]
+    pub(crate) rng: SeededRng,
     // Index of next line in program (used for jumps, but more so by `ICSimulator`)
     pub(crate) next_line_index: usize,
+    // Devices reachable by more than one path, kept in sync line by line
+    #[serde(skip)]
+    pub(crate) shared: Vec<SharedDevice>,
+    // Undo log of the line being executed, if recording history
+    #[serde(skip)]
+    pub(crate) undo_log: Option<UndoLog>,
 }
 
 // Argument reducer helper
 mod arg_reducer;
+// Array (de)serialization helper
+mod serde_array;
+
+mod semantics;
+pub use semantics::{DomainMode, IndexRounding, NanMode, Semantics};
+
+mod rng;
+pub use rng::SeededRng;
 
 /// Stationeers/C# constants
 pub const EPS: f64 = 1.121039e-44; // floating-point epsilon
@@ -187,7 +267,11 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
             dev_self: NONE,
             map: HashMap::new(),
             network: HashMap::new(),
+            semantics: Semantics::default(),
+            rng: SeededRng::default(),
             next_line_index: 0,
+            shared: Vec::new(),
+            undo_log: None,
         }
     }
 
@@ -214,6 +298,23 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
         self
     }
 
+    /// Builder helper to set the numeric semantics.
+    pub fn with_semantics(mut self, semantics: Semantics) -> Self {
+        self.semantics = semantics;
+        self
+    }
+
+    /// Builder helper to set the random number generator.
+    pub fn with_rng(mut self, rng: SeededRng) -> Self {
+        self.rng = rng;
+        self
+    }
+
+    /// Builder helper to seed the random number generator.
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.with_rng(SeededRng::new(seed))
+    }
+
     // ============================================================================================
     // Utility methods
     // ============================================================================================
@@ -222,22 +323,41 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
     pub fn index_reduce(&self, mut i: usize, num_indirections: usize) -> ICStateResult<usize> {
         for _ in 0..num_indirections {
             let j = self.get_mem(i)?;
-            i = usize::try_from((*j) as isize)?;
+            i = self.semantics.index(*j)?;
         }
         Ok(i)
     }
 
-    fn try_index(v: f64) -> Result<usize, IntErrorKind> {
-        // NOTE: This may be subject to change, but Stationeering ignores f64 values not near enough to
-        // an integer (abs(v - round(v) <= EPS)), but Stationeers in game rounds all the time.
-        // let a = <usize>::try_from();
-        if -0.05 < v && v < (SS as f64 + 0.5) {
-            Ok(v as usize)
+    /// Try to convert a value to a stack index (or one past the end).
+    fn try_index(&self, v: f64) -> ICStateResult<usize> {
+        let i = self.semantics.index(v)?;
+        if i <= SS {
+            Ok(i)
         } else {
-            Err(IntErrorKind::NegOverflow)
+            Err(ICStateError::OutOfBounds(OutOfBounds::Stack(i)))
         }
     }
 
+    /// Get the numeric semantics.
+    pub fn semantics(&self) -> &Semantics {
+        &self.semantics
+    }
+
+    /// Set the numeric semantics.
+    pub fn set_semantics(&mut self, semantics: Semantics) {
+        self.semantics = semantics;
+    }
+
+    /// Get the random number generator.
+    pub fn rng(&self) -> &SeededRng {
+        &self.rng
+    }
+
+    /// Set the random number generator.
+    pub fn set_rng(&mut self, rng: SeededRng) {
+        self.rng = rng;
+    }
+
     // ============================================================================================
     // Jump methods
     // ============================================================================================
@@ -271,7 +391,7 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
             if relative {
                 i += self.next_line_index as f64;
             };
-            let i = usize::try_from(i as isize)?;
+            let i = self.semantics.index(i)?;
             if save {
                 self.jump_save(i);
             } else {
@@ -287,6 +407,11 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
 
     /// Set an alias.
     pub fn set_alias(&mut self, a: String, r: AliasKind) {
+        if let Some(log) = &mut self.undo_log {
+            if !log.map.iter().any(|(k, _)| *k == a) {
+                log.map.push((a.clone(), self.map.get(&a).cloned()));
+            }
+        }
         self.map.insert(a, r);
     }
 
@@ -311,7 +436,10 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
     }
 
     /// Try to set a memory register value.
+    ///
+    /// Writing NaN is subject to the [`NanMode`] of the semantics.
     pub fn set_mem(&mut self, i: usize, v: f64) -> ICStateResult<()> {
+        let v = self.semantics.store(i, v)?;
         self.mem
             .get_mut(i)
             .ok_or(ICStateError::OutOfBounds(OutOfBounds::Mem(i)))
@@ -335,6 +463,20 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
         self.dev.iter()
     }
 
+    /// Paths to all set devices (self, device registers and network).
+    pub(crate) fn dev_refs(&self) -> Vec<DevRef> {
+        let set = |dev: &Option<Device>| dev.is_some();
+        let dev_self = set(&self.dev_self).then_some(DevRef::Id(DevId::DevSelf));
+        let dev = (0..DS)
+            .filter(|i| set(&self.dev[*i]))
+            .map(|i| DevRef::Id(DevId::DevBuf(i)));
+        let network = self
+            .network
+            .iter()
+            .flat_map(|(hash, devices)| (0..devices.len()).map(move |i| DevRef::Network(*hash, i)));
+        dev_self.into_iter().chain(dev).chain(network).collect()
+    }
+
     /// Get a device option reference.
     fn get_dev_unchecked(&self, di: DevId) -> Option<&Option<Device>> {
         match di {
@@ -367,6 +509,7 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
     ///
     /// Fails if the index is out of bounds.
     pub fn get_mut_dev_opt(&mut self, di: DevId) -> ICStateResult<&mut Option<Device>> {
+        self.touch_dev(DevRef::Id(di));
         match di {
             DevId::DevBuf(i) => Ok(self
                 .get_mut_dev_unchecked(di)
@@ -433,47 +576,247 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
         devices.push(dev);
     }
 
+    /// Iterator over the network devices of a given hash, optionally filtered by name hash.
+    fn dev_network_iter(&self, hash: i64, name: Option<i64>) -> impl Iterator<Item = &Device> {
+        self.network
+            .get(&hash)
+            .into_iter()
+            .flatten()
+            .filter(move |dev| name.is_none_or(|name| dev.name_hash() == name))
+    }
+
     /// Try to read a parameter value from all devices of a given hash on the network.
     ///
     /// If none of the particular device are on the network, returns zero.
     ///
     /// * `hash` - Hash of devices to read from
+    /// * `name` - Name hash of devices to read from (or any name if `None`)
+    /// * `slot` - Slot of devices to read from (or the device parameters if `None`)
     /// * `var` - Parameter to read
     /// * `mode` - Mode (0: average, 1: sum, 2: min, 3: max)
-    pub fn dev_network_read(&self, hash: i64, var: &str, mode: f64) -> ICStateResult<f64> {
+    pub fn dev_network_read(
+        &self,
+        hash: i64,
+        name: Option<i64>,
+        slot: Option<usize>,
+        var: &str,
+        mode: f64,
+    ) -> ICStateResult<f64> {
         let mode = BatchMode::try_from(mode)?;
-        if let Some(devices) = self.network.get(&hash) {
-            let vals: Vec<f64> = devices
-                .iter()
-                .map(|dev| dev.read(var))
-                .collect::<Result<Vec<f64>, DeviceError>>()?;
-            let iter = vals.into_iter();
-            let val = match mode {
-                BatchMode::Avg => iter.sum::<f64>() / devices.len() as f64,
-                BatchMode::Sum => iter.sum::<f64>(),
-                BatchMode::Min => iter.fold(f64::INFINITY, f64::min),
-                BatchMode::Max => iter.fold(f64::NEG_INFINITY, f64::max),
-            };
-            Ok(val)
-        } else {
-            Ok(0.0)
+        let vals: Vec<f64> = self
+            .dev_network_iter(hash, name)
+            .map(|dev| match slot {
+                Some(slot) => dev.read_slot(slot, var),
+                None => dev.read(var),
+            })
+            .collect::<Result<Vec<f64>, DeviceError>>()?;
+        if vals.is_empty() {
+            return Ok(0.0);
         }
+        let n = vals.len() as f64;
+        let iter = vals.into_iter();
+        let val = match mode {
+            BatchMode::Avg => iter.sum::<f64>() / n,
+            BatchMode::Sum => iter.sum::<f64>(),
+            BatchMode::Min => iter.fold(f64::INFINITY, f64::min),
+            BatchMode::Max => iter.fold(f64::NEG_INFINITY, f64::max),
+        };
+        Ok(val)
     }
 
     /// Try to write a parameter value to all devices of a given hash on the network.
     ///
     /// * `hash` - Hash of devices to write to
+    /// * `name` - Name hash of devices to write to (or any name if `None`)
+    /// * `slot` - Slot of devices to write to (or the device parameters if `None`)
     /// * `var` - Parameter to write
     /// * `val` - Value to write
-    pub fn dev_network_write(&mut self, hash: i64, var: &String, val: f64) -> ICStateResult<()> {
-        if let Some(devices) = self.network.get_mut(&hash) {
-            for dev in devices.iter_mut() {
-                dev.write(var, val)?;
+    pub fn dev_network_write(
+        &mut self,
+        hash: i64,
+        name: Option<i64>,
+        slot: Option<usize>,
+        var: &str,
+        val: f64,
+    ) -> ICStateResult<()> {
+        let n = self.network.get(&hash).map_or(0, Vec::len);
+        for i in 0..n {
+            let r = DevRef::Network(hash, i);
+            if name.is_some_and(|name| self.network[&hash][i].name_hash() != name) {
+                continue;
+            }
+            let dev = self.get_mut_dev_ref(r)?;
+            match slot {
+                Some(slot) => dev.write_slot(slot, var, val)?,
+                None => dev.write(var, val)?,
             }
         }
         Ok(())
     }
 
+    /// Try to find a device by reference id (the self device, device registers or network).
+    fn dev_ref(&self, id: i64) -> ICStateResult<DevRef> {
+        let is_id = |dev: &Option<Device>| {
+            dev.as_ref()
+                .is_some_and(|dev| dev.reference_id == Some(id))
+        };
+        if is_id(&self.dev_self) {
+            return Ok(DevRef::Id(DevId::DevSelf));
+        }
+        if let Some(i) = self.dev.iter().position(is_id) {
+            return Ok(DevRef::Id(DevId::DevBuf(i)));
+        }
+        for (hash, devices) in self.network.iter() {
+            if let Some(i) = devices.iter().position(|dev| dev.reference_id == Some(id)) {
+                return Ok(DevRef::Network(*hash, i));
+            }
+        }
+        Err(ICStateError::DeviceIdUnknown(id))
+    }
+
+    pub(crate) fn get_dev_ref(&self, r: DevRef) -> ICStateResult<&Device> {
+        match r {
+            DevRef::Id(di) => self.get_dev(di),
+            DevRef::Network(hash, i) => Ok(&self.network[&hash][i]),
+        }
+    }
+
+    pub(crate) fn get_mut_dev_ref(&mut self, r: DevRef) -> ICStateResult<&mut Device> {
+        match r {
+            DevRef::Id(di) => self.get_mut_dev(di),
+            DevRef::Network(hash, i) => {
+                self.touch_dev(r);
+                Ok(&mut self.network.get_mut(&hash).unwrap()[i])
+            }
+        }
+    }
+
+    /// Log a device before its first mutable access by a line, if recording history.
+    fn touch_dev(&mut self, r: DevRef) {
+        let Some(log) = &self.undo_log else {
+            return;
+        };
+        if log.dev.iter().any(|(t, _)| *t == r) {
+            return;
+        }
+        let old = match r {
+            DevRef::Id(di) => match self.get_dev_unchecked(di) {
+                Some(dev) => dev.clone(),
+                None => return,
+            },
+            DevRef::Network(hash, i) => match self.network.get(&hash).and_then(|v| v.get(i)) {
+                Some(dev) => Some(dev.clone()),
+                None => return,
+            },
+        };
+        if let Some(log) = &mut self.undo_log {
+            log.dev.push((r, old));
+        }
+    }
+
+    /// Find the devices reachable by more than one path (by reference id),
+    /// so that a write through one path is seen through the others (see [`sync_shared`]).
+    ///
+    /// [`sync_shared`]: Self::sync_shared
+    pub(crate) fn share_devices(&mut self) {
+        let mut refs: BTreeMap<i64, Vec<DevRef>> = BTreeMap::new();
+        let mut add = |dev: Option<&Device>, r: DevRef| {
+            if let Some(id) = dev.and_then(|dev| dev.reference_id) {
+                refs.entry(id).or_default().push(r);
+            }
+        };
+        add(self.dev_self.as_ref(), DevRef::Id(DevId::DevSelf));
+        for (i, dev) in self.dev.iter().enumerate() {
+            add(dev.as_ref(), DevRef::Id(DevId::DevBuf(i)));
+        }
+        for (hash, devices) in self.network.iter() {
+            for (i, dev) in devices.iter().enumerate() {
+                add(Some(dev), DevRef::Network(*hash, i));
+            }
+        }
+        self.shared = refs
+            .into_values()
+            .filter(|refs| refs.len() > 1)
+            .filter_map(|refs| {
+                let dev = self.get_dev_ref(refs[0]).ok()?.clone();
+                Some(SharedDevice { dev, refs })
+            })
+            .collect();
+    }
+
+    /// Copy a shared device changed through one path to all its other paths.
+    pub(crate) fn sync_shared(&mut self) {
+        let mut shared = std::mem::take(&mut self.shared);
+        for SharedDevice { dev, refs } in shared.iter_mut() {
+            let changed = refs
+                .iter()
+                .filter_map(|r| self.get_dev_ref(*r).ok())
+                .find(|copy| !copy.identical(dev))
+                .cloned();
+            if let Some(changed) = changed {
+                for r in refs.iter() {
+                    if let Ok(copy) = self.get_mut_dev_ref(*r) {
+                        *copy = changed.clone();
+                    }
+                }
+                *dev = changed;
+            }
+        }
+        self.shared = shared;
+    }
+
+    /// Try to get a device reference by reference id.
+    pub fn get_dev_by_id(&self, id: i64) -> ICStateResult<&Device> {
+        self.get_dev_ref(self.dev_ref(id)?)
+    }
+
+    /// Try to get a mutable device reference by reference id.
+    pub fn get_mut_dev_by_id(&mut self, id: i64) -> ICStateResult<&mut Device> {
+        let r = self.dev_ref(id)?;
+        self.get_mut_dev_ref(r)
+    }
+
+    /// Try to read from the memory of a device.
+    ///
+    /// The memory of the self device is the state stack.
+    fn dev_memory_read(&self, r: DevRef, addr: f64) -> ICStateResult<f64> {
+        let addr = self.semantics.index(addr)?;
+        match r {
+            DevRef::Id(DevId::DevSelf) => self
+                .stk
+                .get(addr)
+                .cloned()
+                .ok_or(ICStateError::OutOfBounds(OutOfBounds::Stack(addr))),
+            _ => Ok(self.get_dev_ref(r)?.read_memory(addr)?),
+        }
+    }
+
+    /// Try to write to the memory of a device.
+    ///
+    /// The memory of the self device is the state stack.
+    fn dev_memory_write(&mut self, r: DevRef, addr: f64, val: f64) -> ICStateResult<()> {
+        let addr = self.semantics.index(addr)?;
+        match r {
+            DevRef::Id(DevId::DevSelf) => self
+                .stk
+                .get_mut(addr)
+                .map(|s| *s = val)
+                .ok_or(ICStateError::OutOfBounds(OutOfBounds::Stack(addr))),
+            _ => Ok(self.get_mut_dev_ref(r)?.write_memory(addr, val)?),
+        }
+    }
+
+    /// Try to zero the memory of a device.
+    ///
+    /// The memory of the self device is the state stack.
+    fn dev_memory_clear(&mut self, r: DevRef) -> ICStateResult<()> {
+        match r {
+            DevRef::Id(DevId::DevSelf) => self.stk.iter_mut().for_each(|s| *s = 0.0),
+            _ => self.get_mut_dev_ref(r)?.clear_memory(),
+        }
+        Ok(())
+    }
+
     // ============================================================================================
     // Value methods
     // ============================================================================================
@@ -481,7 +824,7 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
     /// Try to reduce a value node to an `f64`.
     pub fn val_reduce(&self, val: &Val) -> ICStateResult<f64> {
         match val {
-            Val::ValLit(v) => Ok(*v),
+            Val::ValLit(v, _) => Ok(*v),
             Val::ValMem(m) => match m {
                 Mem::MemAlias(a) => match self.get_alias(a)? {
                     AliasKind::MemId(i) => self.get_mem(*i).cloned(),
@@ -506,8 +849,8 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
     }
 
     pub fn get_stack_head(&mut self) -> ICStateResult<(&mut f64, &mut f64)> {
+        let i = self.try_index(self.mem[Self::SP])?;
         let sp = &mut self.mem[Self::SP];
-        let i = Self::try_index(*sp)?;
         if i < SS {
             Ok((sp, &mut self.stk[i]))
         } else {
@@ -516,22 +859,21 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
     }
 
     pub fn push(&mut self, v: f64) -> ICStateResult<()> {
+        let i = self.try_index(self.mem[Self::SP])?;
         let sp = &mut self.mem[Self::SP];
-        let i = Self::try_index(*sp)?;
         *sp += 1.0;
         self.stk[i] = v;
         Ok(())
     }
 
     pub fn peek(&mut self) -> ICStateResult<f64> {
-        let sp = &mut self.mem[Self::SP];
-        let i = Self::try_index(*sp - 1.0)?;
+        let i = self.try_index(self.mem[Self::SP] - 1.0)?;
         Ok(self.stk[i])
     }
 
     pub fn pop(&mut self) -> ICStateResult<f64> {
+        let i = self.try_index(self.mem[Self::SP] - 1.0)?;
         let sp = &mut self.mem[Self::SP];
-        let i = Self::try_index(*sp - 1.0)?;
         *sp -= 1.0;
         Ok(self.stk[i])
     }
@@ -554,11 +896,29 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
             }
         }
 
+        // Logic operations act on the value truncated to a signed 64-bit integer
+        #[inline]
+        fn val_to_int(a: f64) -> i64 {
+            a as i64
+        }
+
+        #[inline]
+        fn int_to_val(a: i64) -> f64 {
+            a as f64
+        }
+
+        // Shift amounts wrap to 0-63, as for 64-bit shifts in the game
+        #[inline]
+        fn shift_amount(b: f64) -> u32 {
+            (val_to_int(b) & 63) as u32
+        }
+
         #[inline]
         fn f_ap(a: f64, b: f64, c: f64) -> bool {
             (a - b).abs() <= (c * a.abs().max(b.abs()).max(EPS))
         }
 
+        let semantics = self.semantics;
         let mut jumped = false;
         if let Line::Expr(i, expr) = line {
             let (func, args) = expr.into();
@@ -600,14 +960,44 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Lb => {
                     let (M(r), V(h), T(p), V(m)) = reducer.try_into()?;
-                    let val = self.dev_network_read(h as i64, &p, m)?;
+                    let val = self.dev_network_read(h as i64, None, None, &p, m)?;
+                    self.set_mem(r, val)?;
+                }
+                Lbn => {
+                    let (M(r), V(h), V(n), T(p), V(m)) = reducer.try_into()?;
+                    let val = self.dev_network_read(h as i64, Some(n as i64), None, &p, m)?;
+                    self.set_mem(r, val)?;
+                }
+                Lbns => {
+                    let (M(r), V(h), V(n), V(s), T(p), V(m)) = reducer.try_into()?;
+                    let s = self.semantics.index(s)?;
+                    let val = self.dev_network_read(h as i64, Some(n as i64), Some(s), &p, m)?;
+                    self.set_mem(r, val)?;
+                }
+                Lbs => {
+                    let (M(r), V(h), V(s), T(p), V(m)) = reducer.try_into()?;
+                    let s = self.semantics.index(s)?;
+                    let val = self.dev_network_read(h as i64, None, Some(s), &p, m)?;
                     self.set_mem(r, val)?;
                 }
                 Lr => {
-                    // TODO: Reagent everything
+                    let (M(r), D(d), V(m), V(h)) = reducer.try_into()?;
+                    let mode = ReagentMode::try_from(m)?;
+                    let dev = self.get_dev(d)?;
+                    let reagent_value = dev.read_reagent(mode, h as i64);
+                    self.set_mem(r, reagent_value)?;
+                }
+                Ld => {
+                    let (M(r), V(id), T(t)) = reducer.try_into()?;
+                    let param_value = self.get_dev_by_id(id as i64)?.read(t)?;
+                    self.set_mem(r, param_value)?;
                 }
                 Ls => {
-                    // TODO: Slot everything
+                    let (M(r), D(d), V(s), T(t)) = reducer.try_into()?;
+                    let s = self.semantics.index(s)?;
+                    let dev = self.get_dev(d)?;
+                    let slot_value = dev.read_slot(s, t)?;
+                    self.set_mem(r, slot_value)?;
                 }
                 S => {
                     let (D(d), T(p), V(v)) = reducer.try_into()?;
@@ -616,7 +1006,26 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Sb => {
                     let (V(h), T(p), V(v)) = reducer.try_into()?;
-                    self.dev_network_write(h as i64, &p, v)?;
+                    self.dev_network_write(h as i64, None, None, &p, v)?;
+                }
+                Sbn => {
+                    let (V(h), V(n), T(p), V(v)) = reducer.try_into()?;
+                    self.dev_network_write(h as i64, Some(n as i64), None, &p, v)?;
+                }
+                Sbs => {
+                    let (V(h), V(s), T(p), V(v)) = reducer.try_into()?;
+                    let s = self.semantics.index(s)?;
+                    self.dev_network_write(h as i64, None, Some(s), &p, v)?;
+                }
+                Sd => {
+                    let (V(id), T(p), V(v)) = reducer.try_into()?;
+                    self.get_mut_dev_by_id(id as i64)?.write(p, v)?;
+                }
+                Ss => {
+                    let (D(d), V(s), T(t), V(v)) = reducer.try_into()?;
+                    let s = self.semantics.index(s)?;
+                    let dev = self.get_mut_dev(d)?;
+                    dev.write_slot(s, t, v)?;
                 }
                 // ================================================================================
                 // Flow Control, Branches and Jumps
@@ -639,19 +1048,19 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Beq => {
                     let (V(a), V(b), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, false, false, a == b)?;
+                    jumped = self.jump_helper(l, false, false, semantics.eq(a, b))?;
                 }
                 Beqal => {
                     let (V(a), V(b), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, false, true, a == b)?;
+                    jumped = self.jump_helper(l, false, true, semantics.eq(a, b))?;
                 }
                 Beqz => {
                     let (V(a), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, false, false, a == 0.0)?;
+                    jumped = self.jump_helper(l, false, false, semantics.eq(a, 0.0))?;
                 }
                 Beqzal => {
                     let (V(a), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, false, true, a == 0.0)?;
+                    jumped = self.jump_helper(l, false, true, semantics.eq(a, 0.0))?;
                 }
                 Bge => {
                     let (V(a), V(b), V(l)) = reducer.try_into()?;
@@ -719,35 +1128,35 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Bna => {
                     let (V(a), V(b), V(c), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, false, false, f_ap(a, b, c))?;
+                    jumped = self.jump_helper(l, false, false, !f_ap(a, b, c))?;
                 }
                 Bnaal => {
                     let (V(a), V(b), V(c), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, false, true, f_ap(a, b, c))?;
+                    jumped = self.jump_helper(l, false, true, !f_ap(a, b, c))?;
                 }
                 Bnaz => {
                     let (V(a), V(b), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, false, false, f_ap(a, 0.0, b))?;
+                    jumped = self.jump_helper(l, false, false, !f_ap(a, 0.0, b))?;
                 }
                 Bnazal => {
                     let (V(a), V(b), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, false, true, f_ap(a, 0.0, b))?;
+                    jumped = self.jump_helper(l, false, true, !f_ap(a, 0.0, b))?;
                 }
                 Bne => {
                     let (V(a), V(b), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, false, false, a != b)?;
+                    jumped = self.jump_helper(l, false, false, !semantics.eq(a, b))?;
                 }
                 Bneal => {
                     let (V(a), V(b), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, false, true, a != b)?;
+                    jumped = self.jump_helper(l, false, true, !semantics.eq(a, b))?;
                 }
                 Bnez => {
                     let (V(a), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, false, false, a != 0.0)?;
+                    jumped = self.jump_helper(l, false, false, !semantics.eq(a, 0.0))?;
                 }
                 Bnezal => {
                     let (V(a), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, false, true, a != 0.0)?;
+                    jumped = self.jump_helper(l, false, true, !semantics.eq(a, 0.0))?;
                 }
                 Brap => {
                     let (V(a), V(b), V(c), V(l)) = reducer.try_into()?;
@@ -759,11 +1168,11 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Breq => {
                     let (V(a), V(b), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, true, false, a == b)?;
+                    jumped = self.jump_helper(l, true, false, semantics.eq(a, b))?;
                 }
                 Breqz => {
                     let (V(a), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, true, false, a == 0.0)?;
+                    jumped = self.jump_helper(l, true, false, semantics.eq(a, 0.0))?;
                 }
                 Brge => {
                     let (V(a), V(b), V(l)) = reducer.try_into()?;
@@ -807,11 +1216,11 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Brne => {
                     let (V(a), V(b), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, true, false, a != b)?;
+                    jumped = self.jump_helper(l, true, false, !semantics.eq(a, b))?;
                 }
                 Brnez => {
                     let (V(a), V(l)) = reducer.try_into()?;
-                    jumped = self.jump_helper(l, true, false, a != 0.0)?;
+                    jumped = self.jump_helper(l, true, false, !semantics.eq(a, 0.0))?;
                 }
                 J => {
                     let (V(l),) = reducer.try_into()?;
@@ -846,16 +1255,15 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Select => {
                     let (M(r), V(a), V(b), V(c)) = reducer.try_into()?;
-                    // TODO: Test whether the game uses a approximately zero, or absolutely
-                    self.set_mem(r, if a == 0.0 { b } else { c })?;
+                    self.set_mem(r, if !semantics.eq(a, 0.0) { b } else { c })?;
                 }
                 Seq => {
                     let (M(r), V(a), V(b)) = reducer.try_into()?;
-                    self.set_mem(r, bool_to_val(a == b))?;
+                    self.set_mem(r, bool_to_val(semantics.eq(a, b)))?;
                 }
                 Seqz => {
                     let (M(r), V(a)) = reducer.try_into()?;
-                    self.set_mem(r, bool_to_val(a == 0.0))?;
+                    self.set_mem(r, bool_to_val(semantics.eq(a, 0.0)))?;
                 }
                 Sge => {
                     let (M(r), V(a), V(b)) = reducer.try_into()?;
@@ -899,11 +1307,11 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Sne => {
                     let (M(r), V(a), V(b)) = reducer.try_into()?;
-                    self.set_mem(r, bool_to_val(a != b))?;
+                    self.set_mem(r, bool_to_val(!semantics.eq(a, b)))?;
                 }
                 Snez => {
                     let (M(r), V(a)) = reducer.try_into()?;
-                    self.set_mem(r, bool_to_val(a != 0.0))?;
+                    self.set_mem(r, bool_to_val(!semantics.eq(a, 0.0)))?;
                 }
                 // ================================================================================
                 // Mathematical Operations
@@ -914,7 +1322,7 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Acos => {
                     let (M(r), V(a)) = reducer.try_into()?;
-                    self.set_mem(r, a.acos())?;
+                    self.set_mem(r, semantics.acos(a)?)?;
                 }
                 Add => {
                     let (M(r), V(a), V(b)) = reducer.try_into()?;
@@ -922,7 +1330,7 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Asin => {
                     let (M(r), V(a)) = reducer.try_into()?;
-                    self.set_mem(r, a.asin())?;
+                    self.set_mem(r, semantics.asin(a)?)?;
                 }
                 Atan => {
                     let (M(r), V(a)) = reducer.try_into()?;
@@ -938,7 +1346,7 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Div => {
                     let (M(r), V(a), V(b)) = reducer.try_into()?;
-                    self.set_mem(r, a / b)?;
+                    self.set_mem(r, semantics.div(a, b)?)?;
                 }
                 Exp => {
                     let (M(r), V(a)) = reducer.try_into()?;
@@ -950,7 +1358,7 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Log => {
                     let (M(r), V(a)) = reducer.try_into()?;
-                    self.set_mem(r, a.ln())?;
+                    self.set_mem(r, semantics.ln(a)?)?;
                 }
                 Max => {
                     let (M(r), V(a), V(b)) = reducer.try_into()?;
@@ -962,7 +1370,7 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Mod => {
                     let (M(r), V(a), V(b)) = reducer.try_into()?;
-                    self.set_mem(r, a.rem_euclid(b))?;
+                    self.set_mem(r, semantics.rem(a, b)?)?;
                 }
                 Mul => {
                     let (M(r), V(a), V(b)) = reducer.try_into()?;
@@ -970,7 +1378,8 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Rand => {
                     let (M(r),) = reducer.try_into()?;
-                    self.set_mem(r, rand::random())?;
+                    let v = self.rng.next_f64();
+                    self.set_mem(r, v)?;
                 }
                 Round => {
                     let (M(r), V(a)) = reducer.try_into()?;
@@ -982,7 +1391,7 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 }
                 Sqrt => {
                     let (M(r), V(a)) = reducer.try_into()?;
-                    self.set_mem(r, a.sqrt())?;
+                    self.set_mem(r, semantics.sqrt(a)?)?;
                 }
                 Sub => {
                     let (M(r), V(a), V(b)) = reducer.try_into()?;
@@ -1001,19 +1410,36 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                 // ================================================================================
                 And => {
                     let (M(r), V(a), V(b)) = reducer.try_into()?;
-                    self.set_mem(r, bool_to_val((a > 0.0) || (b > 0.0)))?;
+                    self.set_mem(r, int_to_val(val_to_int(a) & val_to_int(b)))?;
                 }
                 Nor => {
                     let (M(r), V(a), V(b)) = reducer.try_into()?;
-                    self.set_mem(r, bool_to_val(!((a > 0.0) || (b > 0.0))))?;
+                    self.set_mem(r, int_to_val(!(val_to_int(a) | val_to_int(b))))?;
+                }
+                Not => {
+                    let (M(r), V(a)) = reducer.try_into()?;
+                    self.set_mem(r, int_to_val(!val_to_int(a)))?;
                 }
                 Or => {
                     let (M(r), V(a), V(b)) = reducer.try_into()?;
-                    self.set_mem(r, bool_to_val((a > 0.0) || (b > 0.0)))?;
+                    self.set_mem(r, int_to_val(val_to_int(a) | val_to_int(b)))?;
+                }
+                Sla | Sll => {
+                    let (M(r), V(a), V(b)) = reducer.try_into()?;
+                    self.set_mem(r, int_to_val(val_to_int(a) << shift_amount(b)))?;
+                }
+                Sra => {
+                    let (M(r), V(a), V(b)) = reducer.try_into()?;
+                    self.set_mem(r, int_to_val(val_to_int(a) >> shift_amount(b)))?;
+                }
+                Srl => {
+                    let (M(r), V(a), V(b)) = reducer.try_into()?;
+                    let a = val_to_int(a) as u64 >> shift_amount(b);
+                    self.set_mem(r, int_to_val(a as i64))?;
                 }
                 Xor => {
                     let (M(r), V(a), V(b)) = reducer.try_into()?;
-                    self.set_mem(r, bool_to_val(a != b))?;
+                    self.set_mem(r, int_to_val(val_to_int(a) ^ val_to_int(b)))?;
                 }
                 // ================================================================================
                 // Stack
@@ -1032,6 +1458,37 @@ impl<const MS: usize, const DS: usize, const SS: usize> ICState<MS, DS, SS> {
                     let (V(v),) = reducer.try_into()?;
                     self.push(v)?;
                 }
+                Clr => {
+                    let (D(d),) = reducer.try_into()?;
+                    self.get_dev(d)?;
+                    self.dev_memory_clear(DevRef::Id(d))?;
+                }
+                Clrd => {
+                    let (V(id),) = reducer.try_into()?;
+                    let r = self.dev_ref(id as i64)?;
+                    self.dev_memory_clear(r)?;
+                }
+                Get => {
+                    let (M(r), D(d), V(a)) = reducer.try_into()?;
+                    self.get_dev(d)?;
+                    let v = self.dev_memory_read(DevRef::Id(d), a)?;
+                    self.set_mem(r, v)?;
+                }
+                Getd => {
+                    let (M(r), V(id), V(a)) = reducer.try_into()?;
+                    let v = self.dev_memory_read(self.dev_ref(id as i64)?, a)?;
+                    self.set_mem(r, v)?;
+                }
+                Put => {
+                    let (D(d), V(a), V(v)) = reducer.try_into()?;
+                    self.get_dev(d)?;
+                    self.dev_memory_write(DevRef::Id(d), a, v)?;
+                }
+                Putd => {
+                    let (V(id), V(a), V(v)) = reducer.try_into()?;
+                    let r = self.dev_ref(id as i64)?;
+                    self.dev_memory_write(r, a, v)?;
+                }
                 // ================================================================================
                 // Misc
                 // ================================================================================
@@ -0,0 +1,74 @@
+//! Seedable random number generator for the `rand` instruction.
+//!
+//! The generator (SplitMix64) is small enough to be part of a state snapshot, so that a program
+//! using `rand` can be replayed exactly from its seed, or resumed from a snapshot.
+use rand::{Error as RandError, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// Seedable random number generator.
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct SeededRng {
+    seed: u64,
+    state: u64,
+}
+
+impl Default for SeededRng {
+    /// New generator seeded from entropy.
+    fn default() -> Self {
+        Self::from_entropy()
+    }
+}
+
+impl SeededRng {
+    /// New generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// New generator seeded from entropy.
+    pub fn from_entropy() -> Self {
+        Self::new(rand::random())
+    }
+
+    /// Get the seed this generator was created from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restart the sequence from the seed.
+    pub fn reset(&mut self) {
+        self.state = self.seed;
+    }
+
+    /// Next value in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        // Top 53 bits, i.e. the precision of an f64
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), RandError> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
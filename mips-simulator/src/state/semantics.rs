@@ -0,0 +1,218 @@
+//! Numeric semantics of the simulated IC10.
+//!
+//! The game computes with C# doubles, so that division by zero and out of domain math functions
+//! give infinities or NaN (rather than failing), NaN is stored in registers like any other value,
+//! and values used as indices (registers, stack, slots and lines) are rounded (half to even). This
+//! is what [`Semantics::game`] (the default) does. Other modes are useful to catch such values
+//! early, e.g. [`Semantics::strict`] fails on any of them.
+use serde::{Deserialize, Serialize};
+use util::round_index;
+
+use super::{ICStateError, ICStateResult, EPS};
+
+/// Conversion of values used as indices.
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum IndexRounding {
+    /// Truncate towards zero.
+    Truncate,
+    /// Round half to even (as the game does).
+    Round,
+    /// Only accept values within `EPS` of an integer (as Stationeering does).
+    Exact,
+}
+
+/// Result of a math operation outside of its domain (e.g. division by zero or `sqrt` of a
+/// negative).
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum DomainMode {
+    /// IEEE 754 result, i.e. infinity or NaN (as the game does).
+    Ieee,
+    /// Zero.
+    Zero,
+    /// Fail with [`ICStateError::DomainError`].
+    Error,
+}
+
+/// Writing NaN into a register.
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum NanMode {
+    /// Store NaN (as the game does).
+    Propagate,
+    /// Store zero instead.
+    Zero,
+    /// Fail with [`ICStateError::NanValue`].
+    Error,
+}
+
+/// Numeric semantics configuration.
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Semantics {
+    /// Conversion of values used as indices.
+    pub index_rounding: IndexRounding,
+    /// Result of `div` and `mod` by zero.
+    pub div_by_zero: DomainMode,
+    /// Result of `sqrt`, `log`, `asin` and `acos` outside of their domains.
+    pub domain: DomainMode,
+    /// Writing NaN into a register.
+    pub nan: NanMode,
+    /// Compare for (in)equality within `EPS` rather than exactly (e.g. `seq`, `beqz`, `select`).
+    pub eps_compare: bool,
+}
+
+impl Default for Semantics {
+    fn default() -> Self {
+        Self::game()
+    }
+}
+
+impl Semantics {
+    /// Semantics matching the game.
+    pub fn game() -> Self {
+        Self {
+            index_rounding: IndexRounding::Round,
+            div_by_zero: DomainMode::Ieee,
+            domain: DomainMode::Ieee,
+            nan: NanMode::Propagate,
+            eps_compare: false,
+        }
+    }
+
+    /// Semantics failing on inexact indices, domain errors and NaN.
+    pub fn strict() -> Self {
+        Self {
+            index_rounding: IndexRounding::Exact,
+            div_by_zero: DomainMode::Error,
+            domain: DomainMode::Error,
+            nan: NanMode::Error,
+            eps_compare: false,
+        }
+    }
+
+    /// Builder helper to set the index rounding.
+    pub fn with_index_rounding(mut self, index_rounding: IndexRounding) -> Self {
+        self.index_rounding = index_rounding;
+        self
+    }
+
+    /// Builder helper to set the result of division by zero.
+    pub fn with_div_by_zero(mut self, div_by_zero: DomainMode) -> Self {
+        self.div_by_zero = div_by_zero;
+        self
+    }
+
+    /// Builder helper to set the result of math functions outside of their domains.
+    pub fn with_domain(mut self, domain: DomainMode) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    /// Builder helper to set writing NaN into registers.
+    pub fn with_nan(mut self, nan: NanMode) -> Self {
+        self.nan = nan;
+        self
+    }
+
+    /// Builder helper to set comparing within `EPS`.
+    pub fn with_eps_compare(mut self, eps_compare: bool) -> Self {
+        self.eps_compare = eps_compare;
+        self
+    }
+
+    /// Convert a value to an index.
+    pub fn index(&self, v: f64) -> ICStateResult<usize> {
+        let i = match self.index_rounding {
+            IndexRounding::Truncate => v.trunc(),
+            IndexRounding::Round => round_index(v),
+            IndexRounding::Exact if (v - v.round()).abs() <= EPS => v.round(),
+            IndexRounding::Exact => return Err(ICStateError::InvalidIndex(v)),
+        };
+        // Also catches NaN
+        if i >= 0.0 && i <= usize::MAX as f64 {
+            Ok(i as usize)
+        } else {
+            Err(ICStateError::InvalidIndex(v))
+        }
+    }
+
+    /// Are two values equal.
+    pub fn eq(&self, a: f64, b: f64) -> bool {
+        if self.eps_compare {
+            (a - b).abs() <= EPS
+        } else {
+            a == b
+        }
+    }
+
+    /// Result of an operation outside of its domain.
+    fn outside(mode: DomainMode, v: f64, f: &str, a: f64) -> ICStateResult<f64> {
+        match mode {
+            DomainMode::Ieee => Ok(v),
+            DomainMode::Zero => Ok(0.0),
+            DomainMode::Error => Err(ICStateError::DomainError(format!("{} {}", f, a))),
+        }
+    }
+
+    /// Divide `a` by `b`.
+    pub fn div(&self, a: f64, b: f64) -> ICStateResult<f64> {
+        if b == 0.0 {
+            Self::outside(self.div_by_zero, a / b, "div", a)
+        } else {
+            Ok(a / b)
+        }
+    }
+
+    /// Euclidean remainder of `a` by `b`.
+    pub fn rem(&self, a: f64, b: f64) -> ICStateResult<f64> {
+        if b == 0.0 {
+            Self::outside(self.div_by_zero, a.rem_euclid(b), "mod", a)
+        } else {
+            Ok(a.rem_euclid(b))
+        }
+    }
+
+    /// Square root.
+    pub fn sqrt(&self, a: f64) -> ICStateResult<f64> {
+        if a < 0.0 {
+            Self::outside(self.domain, a.sqrt(), "sqrt", a)
+        } else {
+            Ok(a.sqrt())
+        }
+    }
+
+    /// Natural logarithm.
+    pub fn ln(&self, a: f64) -> ICStateResult<f64> {
+        if a <= 0.0 {
+            Self::outside(self.domain, a.ln(), "log", a)
+        } else {
+            Ok(a.ln())
+        }
+    }
+
+    /// Arcsine.
+    pub fn asin(&self, a: f64) -> ICStateResult<f64> {
+        if a.abs() > 1.0 {
+            Self::outside(self.domain, a.asin(), "asin", a)
+        } else {
+            Ok(a.asin())
+        }
+    }
+
+    /// Arccosine.
+    pub fn acos(&self, a: f64) -> ICStateResult<f64> {
+        if a.abs() > 1.0 {
+            Self::outside(self.domain, a.acos(), "acos", a)
+        } else {
+            Ok(a.acos())
+        }
+    }
+
+    /// Value to write into register `i`.
+    pub fn store(&self, i: usize, v: f64) -> ICStateResult<f64> {
+        match self.nan {
+            _ if !v.is_nan() => Ok(v),
+            NanMode::Propagate => Ok(v),
+            NanMode::Zero => Ok(0.0),
+            NanMode::Error => Err(ICStateError::NanValue(i)),
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! Serde helpers for const generic arrays (serialized as sequences).
+//!
+//! Serde only implements its traits for arrays of up to 32 elements, which the stack is not.
+use std::convert::TryInto;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub(crate) fn serialize<S, T, const N: usize>(array: &[T; N], s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    s.collect_seq(array.iter())
+}
+
+pub(crate) fn deserialize<'de, D, T, const N: usize>(d: D) -> Result<[T; N], D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let v: Vec<T> = Vec::deserialize(d)?;
+    let len = v.len();
+    v.try_into()
+        .map_err(|_| D::Error::invalid_length(len, &format!("{} elements", N).as_str()))
+}
@@ -0,0 +1,281 @@
+//! Assertion DSL for testing programs.
+//!
+//! "Given" a program and some registers and devices, run it (for a number of steps, until it
+//! yields, or until it finishes) "then" expect some registers and device parameters to have
+//! certain values, e.g.
+//!
+//! ```
+//! use mips_simulator::test_utils::Given;
+//!
+//! Given::program("add r0 r1 r2")
+//!     .reg(1, 2.0)
+//!     .reg(2, 3.0)
+//!     .until_finished()
+//!     .expect_reg(0, 5.0)
+//!     .check();
+//! ```
+//!
+//! On failure [`Then::check`] panics with each failed expectation and a diff of the whole state
+//! (expected against actual).
+use std::{fmt, fmt::Display};
+
+use mips_parser::prelude::{Node, Program};
+
+use crate::device::Device;
+use crate::scenario::Target;
+use crate::simulator::{ICSimulatorDefault, TickEnd};
+use crate::state::{DevId, ICState};
+use crate::watcher::Cmp;
+
+/// Maximum number of ticks run until a yield (or until finished) before giving up.
+pub const MAX_TICKS: usize = 10_000;
+
+/// Initial state of a test.
+#[derive(Clone, Debug)]
+pub struct Given {
+    sim: ICSimulatorDefault,
+}
+
+impl Given {
+    /// Given a program (panics if the program is invalid).
+    pub fn program(source: &str) -> Self {
+        let program =
+            Program::try_from_str(&source).unwrap_or_else(|e| panic!("invalid program: {:?}", e));
+        Self::simulator(ICSimulatorDefault::new(ICState::default(), program))
+    }
+
+    /// Given a simulator.
+    pub fn simulator(sim: ICSimulatorDefault) -> Self {
+        Self { sim }
+    }
+
+    /// Given a memory register value.
+    pub fn reg(mut self, i: usize, v: f64) -> Self {
+        self.sim
+            .state
+            .set_mem(i, v)
+            .unwrap_or_else(|e| panic!("cannot set r{}: {:?}", i, e));
+        self
+    }
+
+    /// Given a device on a pin.
+    pub fn dev(mut self, pin: usize, dev: Device) -> Self {
+        self.sim
+            .state
+            .set_dev(DevId::DevBuf(pin), Some(dev))
+            .unwrap_or_else(|e| panic!("cannot set d{}: {:?}", pin, e));
+        self
+    }
+
+    /// Given a self device.
+    pub fn db(mut self, dev: Device) -> Self {
+        self.sim.state.dev_self = Some(dev);
+        self
+    }
+
+    /// Given a device on the network.
+    pub fn network(mut self, dev: Device) -> Self {
+        self.sim.state.dev_network_add(dev);
+        self
+    }
+
+    /// Given a register or device parameter value (regardless of the parameter kind).
+    pub fn value(mut self, target: Target, v: f64) -> Self {
+        if !target.set(&mut self.sim.state, v) {
+            panic!("cannot set {}", target);
+        }
+        self
+    }
+
+    /// Given a parameter value of the device on a pin.
+    pub fn param<S: Into<String>>(self, pin: usize, param: S, v: f64) -> Self {
+        self.value(Target::Pin(pin, param.into()), v)
+    }
+
+    /// Run a number of steps (lines).
+    pub fn steps(mut self, n: usize) -> Then {
+        for _ in 0..n {
+            if let Err(e) = self.sim.step() {
+                self.fail(e);
+            }
+        }
+        Then::new(self.sim)
+    }
+
+    /// Run a number of ticks.
+    pub fn ticks(mut self, n: usize) -> Then {
+        if let Err(e) = self.sim.run_ticks(n) {
+            self.fail(e);
+        }
+        Then::new(self.sim)
+    }
+
+    /// Run ticks until a yield (or sleep) is executed or the program finishes.
+    pub fn until_yield(self) -> Then {
+        self.run_until(|end| end != TickEnd::Budget && end != TickEnd::Sleeping)
+    }
+
+    /// Run until the program finishes.
+    pub fn until_finished(self) -> Then {
+        self.run_until(|end| end == TickEnd::Finished)
+    }
+
+    fn run_until<F: Fn(TickEnd) -> bool>(mut self, until: F) -> Then {
+        for _ in 0..MAX_TICKS {
+            match self.sim.tick() {
+                Ok(report) if until(report.end) => return Then::new(self.sim),
+                Ok(report) if report.end == TickEnd::Finished => {
+                    panic!(
+                        "program finished before the expected end\n{}",
+                        self.sim.state
+                    )
+                }
+                Ok(_) => {}
+                Err(e) => self.fail(e),
+            }
+        }
+        panic!(
+            "program did not end within {} ticks\n{}",
+            MAX_TICKS, self.sim.state
+        )
+    }
+
+    fn fail<E: fmt::Debug>(&self, e: E) -> ! {
+        panic!(
+            "error on line {}: {:?}\n{}",
+            self.sim.next_line_index(),
+            e,
+            self.sim.state
+        )
+    }
+}
+
+/// Expectation of a register or device parameter value.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Expectation {
+    pub target: Target,
+    pub cmp: Cmp,
+    pub value: f64,
+}
+
+impl Display for Expectation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.target, self.cmp, self.value)
+    }
+}
+
+/// State of a test after running, and the expectations on it.
+#[must_use = "expectations are only checked by `check`"]
+#[derive(Clone, Debug)]
+pub struct Then {
+    sim: ICSimulatorDefault,
+    expectations: Vec<Expectation>,
+    tolerance: f64,
+}
+
+impl Then {
+    fn new(sim: ICSimulatorDefault) -> Self {
+        Self {
+            sim,
+            expectations: Vec::new(),
+            tolerance: 0.0,
+        }
+    }
+
+    /// The simulator after running.
+    pub fn sim(&self) -> &ICSimulatorDefault {
+        &self.sim
+    }
+
+    /// Set the absolute tolerance of equality expectations (exact by default).
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Expect a comparison of a register or device parameter to hold.
+    pub fn expect(mut self, target: Target, cmp: Cmp, value: f64) -> Self {
+        self.expectations.push(Expectation { target, cmp, value });
+        self
+    }
+
+    /// Expect a memory register value.
+    pub fn expect_reg(self, i: usize, v: f64) -> Self {
+        self.expect(Target::Reg(i), Cmp::Eq, v)
+    }
+
+    /// Expect a parameter value of the device on a pin.
+    pub fn expect_param<S: Into<String>>(self, pin: usize, param: S, v: f64) -> Self {
+        self.expect(Target::Pin(pin, param.into()), Cmp::Eq, v)
+    }
+
+    /// Expect a parameter value of the self device.
+    pub fn expect_db<S: Into<String>>(self, param: S, v: f64) -> Self {
+        self.expect(Target::Db(param.into()), Cmp::Eq, v)
+    }
+
+    /// Does an expectation hold.
+    fn holds(&self, expectation: &Expectation, actual: f64) -> bool {
+        match expectation.cmp {
+            Cmp::Eq => (actual - expectation.value).abs() <= self.tolerance,
+            Cmp::Ne => (actual - expectation.value).abs() > self.tolerance,
+            cmp => cmp.test(actual, expectation.value),
+        }
+    }
+
+    /// Check the expectations, returning a report of the failures (with a state diff) if any.
+    pub fn try_check(&self) -> Result<(), String> {
+        let state = &self.sim.state;
+        let mut expected = state.clone();
+        let mut report = String::new();
+        for expectation in self.expectations.iter() {
+            let actual = expectation.target.value(state);
+            if actual.is_some_and(|actual| self.holds(expectation, actual)) {
+                continue;
+            }
+            let actual = actual.map_or("unset".to_string(), |a| a.to_string());
+            report += &format!("expected {}, found {}\n", expectation, actual);
+            if expectation.cmp == Cmp::Eq {
+                expectation.target.set(&mut expected, expectation.value);
+            }
+        }
+        if report.is_empty() {
+            return Ok(());
+        }
+        report += "state (- expected, + actual):\n";
+        report += &diff(&expected.to_string(), &state.to_string());
+        Err(report)
+    }
+
+    /// Check the expectations, panicking with a report of the failures (with a state diff).
+    ///
+    /// Returns the simulator for further inspection.
+    pub fn check(self) -> ICSimulatorDefault {
+        if let Err(report) = self.try_check() {
+            panic!("{}", report);
+        }
+        self.sim
+    }
+}
+
+/// Line diff of two renderings of a state, marking changed lines.
+fn diff(expected: &str, actual: &str) -> String {
+    let mut s = String::new();
+    let mut expected = expected.lines();
+    let mut actual = actual.lines();
+    loop {
+        match (expected.next(), actual.next()) {
+            (Some(e), Some(a)) if e == a => s += &format!("  {}\n", a),
+            (e, a) if e.is_none() && a.is_none() => break,
+            (e, a) => {
+                if let Some(e) = e {
+                    s += &format!("- {}\n", e);
+                }
+                if let Some(a) = a {
+                    s += &format!("+ {}\n", a);
+                }
+            }
+        }
+    }
+    s
+}
@@ -1,6 +1,10 @@
 use crate::prelude::*;
 use mips_parser::prelude::{Node, Program};
 
+mod given;
+
+pub use given::{Expectation, Given, Then, MAX_TICKS};
+
 pub fn dev_kinds() -> DeviceKinds {
     const PATH: &'static str = "./tests/device-kinds.ron";
     let file = File::open(PATH).unwrap();
@@ -1,6 +1,380 @@
 //! IC10 state watcher
 //!
 //! Useful for debugging programs by determining when, why and how a state variable changed.
+//!
+//! A [`Watcher`] holds watchpoints ([`Watch`]) on state variables and line breakpoints
+//! ([`Breakpoint`]), optionally conditional on a state variable. The watcher of an
+//! [`ICSimulator`](crate::simulator::ICSimulator) is checked on every executed line, and when a
+//! watched variable changes or a breakpoint is reached the simulator stops with a
+//! [`WatchReport`] describing which line changed what, from which old value to which new value.
+use std::{fmt, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::{AliasKind, DevId, ICState};
+
+/// Watchable state variable.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum Watch {
+    /// Memory register by index.
+    Mem(usize),
+    /// Stack slot by index.
+    Stack(usize),
+    /// Alias (alias, label or define) by name.
+    Alias(String),
+    /// Device parameter by device and parameter name.
+    DevParam(DevId, String),
+}
+
+/// Value of a watched state variable.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum WatchValue {
+    Num(f64),
+    Alias(AliasKind),
+    Unset,
+}
+
+// NaN equals NaN, so a register holding NaN is not a change on every line
+impl PartialEq for WatchValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (WatchValue::Num(a), WatchValue::Num(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (WatchValue::Alias(a), WatchValue::Alias(b)) => a == b,
+            (WatchValue::Unset, WatchValue::Unset) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Watch {
+    /// Get the current value of this variable from a state.
+    pub fn value<const MS: usize, const DS: usize, const SS: usize>(
+        &self,
+        state: &ICState<MS, DS, SS>,
+    ) -> WatchValue {
+        let value = match self {
+            Watch::Mem(i) => state.mem.get(*i).cloned().map(WatchValue::Num),
+            Watch::Stack(i) => state.stk.get(*i).cloned().map(WatchValue::Num),
+            Watch::Alias(a) => state.map.get(a).cloned().map(WatchValue::Alias),
+            Watch::DevParam(di, p) => state
+                .get_dev(*di)
+                .ok()
+                .and_then(|dev| dev.params.get(p))
+                .map(|param| WatchValue::Num(param.value())),
+        };
+        value.unwrap_or(WatchValue::Unset)
+    }
+}
+
+/// Comparison operator for breakpoint conditions.
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cmp {
+    /// Does the comparison `a <cmp> b` hold.
+    pub fn test(&self, a: f64, b: f64) -> bool {
+        match self {
+            Cmp::Eq => a == b,
+            Cmp::Ne => a != b,
+            Cmp::Lt => a < b,
+            Cmp::Le => a <= b,
+            Cmp::Gt => a > b,
+            Cmp::Ge => a >= b,
+        }
+    }
+}
+
+/// Breakpoint condition (e.g. `r0 > 5`).
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Condition {
+    pub watch: Watch,
+    pub cmp: Cmp,
+    pub val: f64,
+}
+
+impl Condition {
+    pub fn new(watch: Watch, cmp: Cmp, val: f64) -> Self {
+        Self { watch, cmp, val }
+    }
+
+    /// Does the condition hold for a state.
+    ///
+    /// Conditions on unset or non-numeric variables never hold.
+    pub fn test<const MS: usize, const DS: usize, const SS: usize>(
+        &self,
+        state: &ICState<MS, DS, SS>,
+    ) -> bool {
+        let (a, b) = match self.watch.value(state) {
+            WatchValue::Num(a) => (a, self.val),
+            _ => return false,
+        };
+        self.cmp.test(a, b)
+    }
+}
+
+/// Line breakpoint, optionally conditional.
+///
+/// The simulator stops when the line is next to be executed (and the condition holds),
+/// including before the first line of a run.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Breakpoint {
+    pub line: usize,
+    pub condition: Option<Condition>,
+}
+
+impl Breakpoint {
+    /// New unconditional breakpoint.
+    pub fn line(line: usize) -> Self {
+        Self { line, condition: None }
+    }
+
+    /// New conditional breakpoint.
+    pub fn conditional(line: usize, condition: Condition) -> Self {
+        let condition = Some(condition);
+        Self { line, condition }
+    }
+}
+
+/// Change of a watched variable.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Change {
+    pub watch: Watch,
+    pub old: WatchValue,
+    pub new: WatchValue,
+}
+
+/// Report of why the simulator stopped.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct WatchReport {
+    /// Index of the executed line
+    /// (or of the first line, if the watcher triggered before any line executed).
+    pub line: usize,
+    /// Changes to watched variables made by the line.
+    pub changes: Vec<Change>,
+    /// Breakpoint reached by the next line, if any.
+    pub breakpoint: Option<Breakpoint>,
+}
 
+/// State watcher.
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
 pub struct Watcher {
+    watches: Vec<Watch>,
+    breakpoints: Vec<Breakpoint>,
+    // Values of the watches before the current line
+    before: Vec<WatchValue>,
+    // Report of the last line, if the watcher triggered
+    report: Option<WatchReport>,
+    // Has any line executed (or the first line's breakpoints been checked)
+    #[serde(default)]
+    started: bool,
+}
+
+impl Watcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder helper to add a watchpoint.
+    pub fn with_watch(mut self, watch: Watch) -> Self {
+        self.watch(watch);
+        self
+    }
+
+    /// Builder helper to add a breakpoint.
+    pub fn with_breakpoint(mut self, bp: Breakpoint) -> Self {
+        self.add_breakpoint(bp);
+        self
+    }
+
+    /// Add a watchpoint.
+    pub fn watch(&mut self, watch: Watch) {
+        if !self.watches.contains(&watch) {
+            self.watches.push(watch);
+        }
+    }
+
+    /// Remove a watchpoint.
+    pub fn unwatch(&mut self, watch: &Watch) {
+        self.watches.retain(|w| w != watch);
+    }
+
+    /// Add a breakpoint.
+    pub fn add_breakpoint(&mut self, bp: Breakpoint) {
+        self.breakpoints.push(bp);
+    }
+
+    /// Remove all breakpoints on a line.
+    pub fn remove_breakpoints(&mut self, line: usize) {
+        self.breakpoints.retain(|bp| bp.line != line);
+    }
+
+    /// Remove all watchpoints and breakpoints.
+    pub fn clear(&mut self) {
+        self.watches.clear();
+        self.breakpoints.clear();
+        self.report = None;
+    }
+
+    /// Iterator over the watchpoints.
+    pub fn iter_watches(&self) -> impl Iterator<Item = &Watch> {
+        self.watches.iter()
+    }
+
+    /// Iterator over the breakpoints.
+    pub fn iter_breakpoints(&self) -> impl Iterator<Item = &Breakpoint> {
+        self.breakpoints.iter()
+    }
+
+    /// Report of the last executed line, if the watcher triggered on it.
+    pub fn report(&self) -> Option<&WatchReport> {
+        self.report.as_ref()
+    }
+
+    /// Did the watcher trigger on the last executed line.
+    pub fn triggered(&self) -> bool {
+        self.report.is_some()
+    }
+
+    /// Forget the report of the last executed line.
+    pub(crate) fn reset(&mut self) {
+        self.report = None;
+    }
+
+    /// Breakpoint reached by the next line, if any.
+    fn breakpoint<const MS: usize, const DS: usize, const SS: usize>(
+        &self,
+        state: &ICState<MS, DS, SS>,
+    ) -> Option<Breakpoint> {
+        let next = state.next_line_index;
+        self.breakpoints
+            .iter()
+            .find(|bp| bp.line == next && bp.condition.as_ref().is_none_or(|c| c.test(state)))
+            .cloned()
+    }
+
+    /// Check the breakpoints of the first line before any line executes,
+    /// returning whether the watcher triggered.
+    ///
+    /// Only the start of a run is checked, so that a run can continue past such a breakpoint.
+    pub(crate) fn before_start<const MS: usize, const DS: usize, const SS: usize>(
+        &mut self,
+        state: &ICState<MS, DS, SS>,
+    ) -> bool {
+        if std::mem::replace(&mut self.started, true) {
+            return false;
+        }
+        self.report = self.breakpoint(state).map(|bp| WatchReport {
+            line: state.next_line_index,
+            changes: Vec::new(),
+            breakpoint: Some(bp),
+        });
+        self.triggered()
+    }
+
+    /// Sample the watched values before a line executes.
+    pub(crate) fn before_line<const MS: usize, const DS: usize, const SS: usize>(
+        &mut self,
+        state: &ICState<MS, DS, SS>,
+    ) {
+        self.reset();
+        self.started = true;
+        self.before = self.watches.iter().map(|w| w.value(state)).collect();
+    }
+
+    /// Compare the watched values after line `i` executed and check the breakpoints of the next
+    /// line, returning whether the watcher triggered.
+    pub(crate) fn after_line<const MS: usize, const DS: usize, const SS: usize>(
+        &mut self,
+        state: &ICState<MS, DS, SS>,
+        i: usize,
+    ) -> bool {
+        let changes: Vec<Change> = self
+            .watches
+            .iter()
+            .zip(self.before.drain(..))
+            .filter_map(|(watch, old)| {
+                let new = watch.value(state);
+                (new != old).then(|| Change {
+                    watch: watch.clone(),
+                    old,
+                    new,
+                })
+            })
+            .collect();
+        let breakpoint = self.breakpoint(state);
+        if !changes.is_empty() || breakpoint.is_some() {
+            self.report = Some(WatchReport {
+                line: i,
+                changes,
+                breakpoint,
+            });
+        }
+        self.triggered()
+    }
+}
+
+impl Display for Watch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Watch::Mem(i) => write!(f, "r{}", i),
+            Watch::Stack(i) => write!(f, "stack[{}]", i),
+            Watch::Alias(a) => write!(f, "{}", a),
+            Watch::DevParam(DevId::DevBuf(i), p) => write!(f, "d{}.{}", i, p),
+            Watch::DevParam(DevId::DevSelf, p) => write!(f, "db.{}", p),
+        }
+    }
+}
+
+impl Display for WatchValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WatchValue::Num(v) => write!(f, "{}", v),
+            WatchValue::Alias(a) => write!(f, "{:?}", a),
+            WatchValue::Unset => write!(f, "(unset)"),
+        }
+    }
+}
+
+impl Display for Cmp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Cmp::Eq => "==",
+            Cmp::Ne => "!=",
+            Cmp::Lt => "<",
+            Cmp::Le => "<=",
+            Cmp::Gt => ">",
+            Cmp::Ge => ">=",
+        };
+        f.write_str(s)
+    }
+}
+
+impl Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "breakpoint at line {}", self.line)?;
+        if let Some(c) = &self.condition {
+            write!(f, " if {} {} {}", c.watch, c.cmp, c.val)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for WatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}", self.line)?;
+        for c in self.changes.iter() {
+            write!(f, "\n    changed {} from {} to {}", c.watch, c.old, c.new)?;
+        }
+        if let Some(bp) = &self.breakpoint {
+            write!(f, "\n    reached {}", bp)?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,340 @@
+//! Multi-IC world simulation.
+//!
+//! A [`World`] owns devices by reference id and lets several [`ICSimulator`]s share them, either
+//! by setting them on their device pins or by connecting them to the shared data network.
+//! Each IC housing is itself a device of the world (connected to the network), so one IC can
+//! read and write another's `Setting` just as in game.
+//!
+//! Every world tick, each IC is ticked once in the order it was added. Before an IC is ticked the
+//! current world devices are copied into its state, and afterwards any device the IC changed is
+//! copied back into the world, so later ICs of the same tick see earlier ICs' writes.
+//! A device an IC reaches by more than one path (e.g. `d0` and the network) is kept as one device
+//! within the tick: a write through one path is seen through the others from the next line on.
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::device::{Behaviors, Device, Environment};
+use crate::simulator::{ICSimulator, ICSimulatorError, TickReport};
+use crate::{DEV_SIZE, MEM_SIZE, STACK_SIZE};
+use util::impl_from_error;
+
+/// Reference id of a device owned by a [`World`].
+pub type RefId = i64;
+
+/// World error type.
+#[derive(Debug)]
+pub enum WorldError {
+    ICSimulatorError(ICSimulatorError),
+
+    UnknownDevice(RefId),
+    UnknownIC(usize),
+    UnknownPin(usize),
+}
+
+impl_from_error!(WorldError, ICSimulatorError);
+
+/// Shortcut type for world error results.
+pub type WorldResult<T> = Result<T, WorldError>;
+
+/// An IC simulator placed in a world.
+#[derive(Clone, Debug)]
+pub struct WorldIC<const MS: usize, const DS: usize, const SS: usize> {
+    pub sim: ICSimulator<MS, DS, SS>,
+    // Reference id of the IC housing device
+    pub(crate) housing: RefId,
+    // Reference ids of the devices set on the device pins
+    pub(crate) pins: [Option<RefId>; DS],
+}
+
+impl<const MS: usize, const DS: usize, const SS: usize> WorldIC<MS, DS, SS> {
+    /// Reference id of the IC housing device.
+    pub fn housing(&self) -> RefId {
+        self.housing
+    }
+
+    /// Reference ids of the devices set on the device pins.
+    pub fn pins(&self) -> &[Option<RefId>; DS] {
+        &self.pins
+    }
+}
+
+/// Multi-IC world.
+#[derive(Clone, Debug)]
+pub struct World<const MS: usize, const DS: usize, const SS: usize> {
+    // Devices (reference id -> device)
+    pub(crate) devices: BTreeMap<RefId, Device>,
+    // Reference ids of devices on the shared data network
+    pub(crate) network: BTreeSet<RefId>,
+    // IC simulators, in tick order
+    pub(crate) ics: Vec<WorldIC<MS, DS, SS>>,
+    // Reference id of the next added device
+    pub(crate) next_id: RefId,
+    // Number of world ticks elapsed
+    pub(crate) ticks: usize,
+    /// Environment of the world devices.
+    pub env: Environment,
+    // Device behaviors, updated at the end of each world tick
+    pub(crate) behaviors: Behaviors,
+}
+
+// Alias for a world of default Stationeers IC states.
+pub type WorldDefault = World<MEM_SIZE, DEV_SIZE, STACK_SIZE>;
+
+impl<const MS: usize, const DS: usize, const SS: usize> World<MS, DS, SS> {
+    /// New empty world.
+    pub fn new() -> Self {
+        Self {
+            devices: BTreeMap::new(),
+            network: BTreeSet::new(),
+            ics: Vec::new(),
+            next_id: 1,
+            ticks: 0,
+            env: Environment::new(),
+            behaviors: Behaviors::new(),
+        }
+    }
+
+    /// Builder helper to set the device behaviors, updated at the end of each world tick.
+    pub fn with_behaviors(mut self, behaviors: Behaviors) -> Self {
+        self.behaviors = behaviors;
+        self
+    }
+
+    /// Builder helper to set the device environment.
+    pub fn with_environment(mut self, env: Environment) -> Self {
+        self.env = env;
+        self
+    }
+
+    // ============================================================================================
+    // Device methods
+    // ============================================================================================
+
+    /// Add a device to the world (without connecting it to the network).
+    ///
+    /// The device reference id is set to its world reference id.
+    pub fn add_device(&mut self, dev: Device) -> RefId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.devices.insert(id, dev.with_reference_id(id));
+        id
+    }
+
+    /// Add a device to the world and connect it to the network.
+    pub fn add_network_device(&mut self, dev: Device) -> RefId {
+        let id = self.add_device(dev);
+        self.network.insert(id);
+        id
+    }
+
+    /// Try to connect a device to the network.
+    pub fn connect(&mut self, id: RefId) -> WorldResult<()> {
+        self.get_device(id)?;
+        self.network.insert(id);
+        Ok(())
+    }
+
+    /// Disconnect a device from the network.
+    pub fn disconnect(&mut self, id: RefId) {
+        self.network.remove(&id);
+    }
+
+    /// Iterator over the world devices.
+    pub fn iter_devices(&self) -> impl Iterator<Item = (&RefId, &Device)> {
+        self.devices.iter()
+    }
+
+    /// Iterator over the reference ids of the network devices.
+    pub fn iter_network(&self) -> impl Iterator<Item = &RefId> {
+        self.network.iter()
+    }
+
+    /// Try to get a device reference.
+    pub fn get_device(&self, id: RefId) -> WorldResult<&Device> {
+        self.devices.get(&id).ok_or(WorldError::UnknownDevice(id))
+    }
+
+    /// Try to get a mutable device reference.
+    pub fn get_mut_device(&mut self, id: RefId) -> WorldResult<&mut Device> {
+        self.devices.get_mut(&id).ok_or(WorldError::UnknownDevice(id))
+    }
+
+    // ============================================================================================
+    // IC methods
+    // ============================================================================================
+
+    /// Add an IC simulator to the world, returning its index.
+    ///
+    /// The simulator self device (or a new circuit housing if unset) becomes a network device of
+    /// the world, and any devices already set on its pins or network are moved into the world.
+    pub fn add_ic(&mut self, mut sim: ICSimulator<MS, DS, SS>) -> usize {
+        let state = &mut sim.state;
+
+        let housing = state.dev_self.take().unwrap_or_else(Device::circuit_housing);
+        let housing = self.add_network_device(housing);
+
+        const NONE: Option<RefId> = None;
+        let mut pins = [NONE; DS];
+        for (pin, dev) in pins.iter_mut().zip(state.dev.iter_mut()) {
+            *pin = dev.take().map(|dev| self.add_device(dev));
+        }
+
+        // Sorted by hash so reference ids are deterministic
+        let mut network: Vec<(i64, Vec<Device>)> = state.network.drain().collect();
+        network.sort_by_key(|(hash, _)| *hash);
+        for (_, devices) in network.into_iter() {
+            for dev in devices.into_iter() {
+                self.add_network_device(dev);
+            }
+        }
+
+        self.ics.push(WorldIC { sim, housing, pins });
+        self.ics.len() - 1
+    }
+
+    /// Try to set (or unset) the device on a pin of an IC.
+    pub fn set_pin(&mut self, ic: usize, pin: usize, id: Option<RefId>) -> WorldResult<()> {
+        if let Some(id) = id {
+            self.get_device(id)?;
+        }
+        let ic = self.get_mut_ic(ic)?;
+        let slot = ic.pins.get_mut(pin).ok_or(WorldError::UnknownPin(pin))?;
+        *slot = id;
+        Ok(())
+    }
+
+    /// Iterator over the world ICs, in tick order.
+    pub fn iter_ics(&self) -> impl Iterator<Item = &WorldIC<MS, DS, SS>> {
+        self.ics.iter()
+    }
+
+    /// Try to get an IC reference.
+    pub fn get_ic(&self, ic: usize) -> WorldResult<&WorldIC<MS, DS, SS>> {
+        self.ics.get(ic).ok_or(WorldError::UnknownIC(ic))
+    }
+
+    /// Try to get a mutable IC reference.
+    pub fn get_mut_ic(&mut self, ic: usize) -> WorldResult<&mut WorldIC<MS, DS, SS>> {
+        self.ics.get_mut(ic).ok_or(WorldError::UnknownIC(ic))
+    }
+
+    /// Try to get an IC simulator reference.
+    ///
+    /// Note that devices in the simulator state are only current during the IC's own tick;
+    /// read world devices via [`get_device`](Self::get_device) instead.
+    pub fn get_sim(&self, ic: usize) -> WorldResult<&ICSimulator<MS, DS, SS>> {
+        self.get_ic(ic).map(|ic| &ic.sim)
+    }
+
+    // ============================================================================================
+    // Simulation methods
+    // ============================================================================================
+
+    /// Get the number of world ticks elapsed.
+    pub fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    /// Copy the current world devices into the state of an IC.
+    fn sync_in(&mut self, ic: usize) {
+        let devices = &self.devices;
+        let network = &self.network;
+        let WorldIC { sim, housing, pins } = &mut self.ics[ic];
+        let state = &mut sim.state;
+
+        state.dev_self = devices.get(housing).cloned();
+        // The housing memory is the IC stack
+        if let Some(dev) = &state.dev_self {
+            for (s, m) in state.stk.iter_mut().zip(dev.memory.iter()) {
+                *s = *m;
+            }
+        }
+        for (dev, pin) in state.dev.iter_mut().zip(pins.iter()) {
+            *dev = pin.and_then(|id| devices.get(&id).cloned());
+        }
+        state.network.clear();
+        for id in network.iter() {
+            if let Some(dev) = devices.get(id) {
+                state.dev_network_add(dev.clone());
+            }
+        }
+        // The same device may be on a pin, the housing and the network
+        state.share_devices();
+    }
+
+    /// Copy any devices changed by an IC back into the world.
+    fn sync_out(&mut self, ic: usize) {
+        let devices = &mut self.devices;
+        let network = &self.network;
+        let WorldIC { sim, housing, pins } = &mut self.ics[ic];
+        let state = &mut sim.state;
+
+        if let Some(dev) = &mut state.dev_self {
+            dev.memory = state.stk.to_vec();
+        }
+        // Copies of a shared device are equal once synced, so one copy per reference id suffices
+        state.sync_shared();
+
+        let mut changed = BTreeMap::new();
+        let mut changed_if = |id: RefId, dev: &Device| {
+            if devices.get(&id).is_some_and(|d| !d.identical(dev)) {
+                changed.entry(id).or_insert_with(|| dev.clone());
+            }
+        };
+
+        for (dev, pin) in state.dev.iter().zip(pins.iter()) {
+            if let (Some(dev), Some(id)) = (dev, pin) {
+                changed_if(*id, dev);
+            }
+        }
+        if let Some(dev) = &state.dev_self {
+            changed_if(*housing, dev);
+        }
+        // Network devices were added in reference id order, so are found in that order per hash
+        let mut offsets = HashMap::new();
+        for id in network.iter() {
+            if let Some(hash) = devices.get(id).map(|dev| dev.hash) {
+                let offset = offsets.entry(hash).or_insert(0);
+                if let Some(dev) = state.network.get(&hash).and_then(|v| v.get(*offset)) {
+                    changed_if(*id, dev);
+                }
+                *offset += 1;
+            }
+        }
+
+        devices.append(&mut changed);
+    }
+
+    /// Simulate one world tick, ticking each IC once in order.
+    ///
+    /// Returns the tick report of each IC.
+    pub fn tick(&mut self) -> WorldResult<Vec<TickReport>> {
+        let mut reports = Vec::with_capacity(self.ics.len());
+        for ic in 0..self.ics.len() {
+            self.sync_in(ic);
+            let res = self.ics[ic].sim.tick();
+            self.sync_out(ic);
+            reports.push(res?);
+        }
+        if !self.behaviors.is_empty() {
+            for dev in self.devices.values_mut() {
+                self.behaviors.tick(dev, &mut self.env);
+            }
+            self.env.tick += 1;
+        }
+        self.ticks += 1;
+        Ok(reports)
+    }
+
+    /// Simulate `n` world ticks.
+    ///
+    /// Returns the tick reports of each IC for each elapsed tick.
+    pub fn run_ticks(&mut self, n: usize) -> WorldResult<Vec<Vec<TickReport>>> {
+        (0..n).map(|_| self.tick()).collect()
+    }
+}
+
+impl Default for World<MEM_SIZE, DEV_SIZE, STACK_SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
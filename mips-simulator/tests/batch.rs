@@ -0,0 +1,97 @@
+use mips_simulator::prelude::*;
+use mips_simulator::test_utils::{dev_kinds, setup};
+
+const LOGIC_MEMORY: i64 = -851746783;
+
+fn memory(label: Option<&str>) -> Device {
+    let mut dev = dev_kinds()["LogicMemory"].make();
+    dev.slots = vec![SlotKind::new("Import").make()];
+    match label {
+        Some(label) => dev.with_label(label),
+        None => dev,
+    }
+}
+
+fn run(sim: &mut ICSimulatorDefault) {
+    sim.run_until_finished().unwrap();
+}
+
+#[test]
+fn device_name_hash() {
+    let dev = memory(None);
+    assert_eq!(
+        dev.name_hash(),
+        mips_parser::hash::hash("LogicMemory") as i64
+    );
+    let dev = dev.with_label("Tank");
+    assert_eq!(dev.name_hash(), mips_parser::hash::hash("Tank") as i64);
+}
+
+#[test]
+fn batch_named() {
+    let mut sim = setup(
+        "\
+define MEMORY HASH(\"StructureLogicMemory\")
+sbn MEMORY HASH(\"Tank\") Setting 5
+sb MEMORY Setting 1
+sbn MEMORY HASH(\"Tank\") Setting 7
+lbn r0 MEMORY HASH(\"Tank\") Setting 1
+lb r1 MEMORY Setting 1
+lbn r2 MEMORY HASH(\"Missing\") Setting 0",
+    );
+    sim.state.dev_network_add(memory(Some("Tank")));
+    sim.state.dev_network_add(memory(Some("Tank")));
+    sim.state.dev_network_add(memory(Some("Pump")));
+    run(&mut sim);
+
+    assert_eq!(sim.state.get_mem(0).unwrap(), &14.0);
+    assert_eq!(sim.state.get_mem(1).unwrap(), &15.0);
+    // No devices with the name hash read as zero
+    assert_eq!(sim.state.get_mem(2).unwrap(), &0.0);
+    let read = |name| {
+        sim.state
+            .dev_network_read(LOGIC_MEMORY, name, None, "Setting", 2.0)
+    };
+    assert_eq!(
+        read(Some(mips_parser::hash::hash("Pump") as i64)).unwrap(),
+        1.0
+    );
+}
+
+#[test]
+fn batch_slots() {
+    let mut sim = setup(
+        "\
+lbs r0 -851746783 0 Occupied 1
+lbns r1 -851746783 HASH(\"Tank\") 0 Occupied 1
+lbs r2 -851746783 0 Occupied 0",
+    );
+    for label in ["Tank", "Pump"].iter() {
+        let mut dev = memory(Some(label));
+        dev.try_get_mut_slot(0)
+            .unwrap()
+            .set("Occupied", 1.0)
+            .unwrap();
+        sim.state.dev_network_add(dev);
+    }
+    run(&mut sim);
+
+    assert_eq!(sim.state.get_mem(0).unwrap(), &2.0);
+    assert_eq!(sim.state.get_mem(1).unwrap(), &1.0);
+    assert_eq!(sim.state.get_mem(2).unwrap(), &1.0);
+}
+
+#[test]
+fn batch_slot_errors() {
+    // Slot parameters are read only, and slots must exist
+    for source in [
+        "sbs -851746783 0 Occupied 1",
+        "lbs r0 -851746783 1 Occupied 0",
+    ]
+    .iter()
+    {
+        let mut sim = setup(source);
+        sim.state.dev_network_add(memory(None));
+        assert!(sim.step().is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use mips_simulator::device::behavior::{GasSensor, VolumePump};
+use mips_simulator::prelude::*;
+use mips_simulator::test_utils::{dev_kinds, setup};
+
+fn make(name: &str) -> Device {
+    dev_kinds()[name].make()
+}
+
+#[test]
+fn environment_atmosphere() {
+    let mut a = Atmosphere::new(100.0, 4.0, 300.0);
+    assert!((a.pressure() - 99.77).abs() < 0.01);
+    assert_eq!(a.remove(5.0), 4.0);
+    a.add(2.0, 200.0);
+    a.add(2.0, 400.0);
+    assert_eq!(a.moles, 4.0);
+    assert_eq!(a.temperature, 300.0);
+}
+
+#[test]
+fn environment_sun() {
+    let mut env = Environment::new().with_day_ticks(8);
+    assert_eq!(env.sun(), (0.0, 0.0));
+    env.tick = 2;
+    assert_eq!(env.sun(), (90.0, 90.0));
+    assert!(env.is_day());
+    env.tick = 5;
+    assert!(!env.is_day());
+}
+
+#[test]
+fn behavior_lookup() {
+    let behaviors = Behaviors::builtin().with("Tank", GasSensor { atmosphere: 1 });
+    assert!(behaviors.get(&make("GasSensor")).is_some());
+    assert!(behaviors.get(&make("StellarAnchor")).is_none());
+    let labelled = make("StellarAnchor").with_label("Tank");
+    assert!(behaviors.get(&labelled).is_some());
+}
+
+#[test]
+fn solar_tracking() {
+    // Point the panel at the sun each tick
+    let mut sim = setup(
+        "\
+main:
+l r0 d0 Horizontal
+l r1 d0 Vertical
+s d1 Horizontal r0
+s d1 Vertical r1
+l r2 d1 Ratio
+yield
+j main",
+    )
+    .with_behaviors(Behaviors::builtin())
+    .with_environment(Environment::new().with_day_ticks(100));
+    sim.state
+        .set_dev(DevId::DevBuf(0), Some(make("DaylightSensor")))
+        .unwrap();
+    sim.state
+        .set_dev(DevId::DevBuf(1), Some(make("SolarPanel")))
+        .unwrap();
+
+    // The panel lags the sun by one tick
+    sim.run_ticks(20).unwrap();
+    let ratio = *sim.state.get_mem(2).unwrap();
+    assert!(ratio > 0.99, "{}", ratio);
+    let panel = sim.state.get_dev(DevId::DevBuf(1)).unwrap();
+    assert!(panel.read("Charge").unwrap() > 495.0);
+
+    // At night the panel generates nothing
+    sim.run_ticks(40).unwrap();
+    let panel = sim.state.get_dev(DevId::DevBuf(1)).unwrap();
+    assert_eq!(panel.read("Charge").unwrap(), 0.0);
+}
+
+#[test]
+fn pump_pressure_control() {
+    // Fill the tank (atmosphere 1) until it is above 100kPa
+    let mut sim = setup(
+        "\
+s d0 Setting 10
+main:
+l r0 d1 Pressure
+slt r1 r0 100
+s d0 On r1
+yield
+j main",
+    )
+    .with_behaviors(Behaviors::builtin().with("Tank", GasSensor { atmosphere: 1 }))
+    .with_environment(
+        Environment::new()
+            .with_atmosphere(Atmosphere::new(10000.0, 2000.0, 300.0))
+            .with_atmosphere(Atmosphere::new(1000.0, 0.0, 300.0)),
+    );
+    sim.state
+        .set_dev(DevId::DevBuf(0), Some(make("VolumePump")))
+        .unwrap();
+    let sensor = make("GasSensor").with_label("Tank");
+    sim.state.set_dev(DevId::DevBuf(1), Some(sensor)).unwrap();
+
+    sim.run_ticks(100).unwrap();
+    let pressure = sim.env.atmospheres[1].pressure();
+    // The sensor reads the pressure of the previous tick, so overshoots by a pump tick or two
+    assert!((100.0..115.0).contains(&pressure), "{}", pressure);
+    assert_eq!(
+        sim.state
+            .get_dev(DevId::DevBuf(0))
+            .unwrap()
+            .read("On")
+            .unwrap(),
+        0.0
+    );
+    // Gas is conserved
+    let moles: f64 = sim.env.atmospheres.iter().map(|a| a.moles).sum();
+    assert!((moles - 2000.0).abs() < 1e-9);
+}
+
+#[test]
+fn pump_custom_atmospheres() {
+    let pump = VolumePump {
+        input: 1,
+        output: 0,
+        maximum: 10.0,
+    };
+    let behaviors = Behaviors::new().with("Drain", pump);
+    let mut env = Environment::new()
+        .with_atmosphere(Atmosphere::new(100.0, 0.0, 300.0))
+        .with_atmosphere(Atmosphere::new(100.0, 10.0, 300.0));
+    let mut dev = make("VolumePump").with_label("Drain");
+    dev.write("On", 1.0).unwrap();
+    dev.write("Setting", 50.0).unwrap();
+    behaviors.tick(&mut dev, &mut env);
+
+    // Clamped to 10 litres of the 100 litres
+    assert_eq!(dev.read("Ratio").unwrap(), 1.0);
+    assert!((env.atmospheres[0].moles - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn logic_devices() {
+    let behaviors = Behaviors::builtin();
+    let mut env = Environment::new();
+
+    let mut switch = make("LogicSwitch");
+    switch.write("Open", 1.0).unwrap();
+    behaviors.tick(&mut switch, &mut env);
+    assert_eq!(switch.read("Setting").unwrap(), 1.0);
+
+    let mut button = make("LogicButton");
+    button.write("Activate", 1.0).unwrap();
+    behaviors.tick(&mut button, &mut env);
+    assert_eq!(button.read("Setting").unwrap(), 1.0);
+    behaviors.tick(&mut button, &mut env);
+    assert_eq!(button.read("Setting").unwrap(), 0.0);
+
+    let mut led = make("ConsoleLED5");
+    led.write("On", 1.0).unwrap();
+    led.write("Setting", 42.0).unwrap();
+    behaviors.tick(&mut led, &mut env);
+    assert_eq!(led.read("Power").unwrap(), 10.0);
+    assert_eq!(led.read("Setting").unwrap(), 42.0);
+
+    let mut memory = make("LogicMemory");
+    memory.write("Setting", 3.0).unwrap();
+    behaviors.tick(&mut memory, &mut env);
+    assert_eq!(memory.read("Setting").unwrap(), 3.0);
+}
+
+#[test]
+fn world_behaviors() {
+    let mut world = WorldDefault::new().with_behaviors(Behaviors::builtin());
+    let switch = world.add_device(make("LogicSwitch"));
+    let ic = world.add_ic(setup(
+        "\
+s d0 Open 1
+yield
+l r0 d0 Setting",
+    ));
+    world.set_pin(ic, 0, Some(switch)).unwrap();
+    world.run_ticks(2).unwrap();
+
+    assert_eq!(world.env.tick, 2);
+    assert_eq!(world.get_sim(ic).unwrap().state.get_mem(0).unwrap(), &1.0);
+}
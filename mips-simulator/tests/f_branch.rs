@@ -0,0 +1,13 @@
+use mips_simulator::test_utils::setup_run_and_test_mem;
+
+#[test]
+fn test_branch_brapz() {
+    setup_run_and_test_mem("brapz 0 0.1 2\nmove r0 1", 0, 0.0);
+    setup_run_and_test_mem("brapz 1 0.1 2\nmove r0 1", 0, 1.0);
+}
+
+#[test]
+fn test_branch_brnaz() {
+    setup_run_and_test_mem("brnaz 1 0.1 2\nmove r0 1", 0, 0.0);
+    setup_run_and_test_mem("brnaz 0 0.1 2\nmove r0 1", 0, 1.0);
+}
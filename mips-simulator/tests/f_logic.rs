@@ -0,0 +1,67 @@
+use mips_simulator::test_utils::setup_run_and_test_mem;
+
+#[test]
+fn test_logic_and() {
+    setup_run_and_test_mem("and r0 1 1",  0,  1.0);
+    setup_run_and_test_mem("and r0 1 0",  0,  0.0);
+    setup_run_and_test_mem("and r0 0 0",  0,  0.0);
+    setup_run_and_test_mem("and r0 12 10", 0, 8.0);
+    setup_run_and_test_mem("and r0 -1 $FF", 0, 255.0);
+    setup_run_and_test_mem("and r0 12.9 10.1", 0, 8.0);
+}
+
+#[test]
+fn test_logic_nor() {
+    setup_run_and_test_mem("nor r0 0 0",   0, -1.0);
+    setup_run_and_test_mem("nor r0 1 0",   0, -2.0);
+    setup_run_and_test_mem("nor r0 12 10", 0, -15.0);
+}
+
+#[test]
+fn test_logic_not() {
+    setup_run_and_test_mem("not r0  0", 0, -1.0);
+    setup_run_and_test_mem("not r0 -1", 0,  0.0);
+    setup_run_and_test_mem("not r0  5", 0, -6.0);
+}
+
+#[test]
+fn test_logic_or() {
+    setup_run_and_test_mem("or r0 1 1",   0,  1.0);
+    setup_run_and_test_mem("or r0 1 0",   0,  1.0);
+    setup_run_and_test_mem("or r0 0 0",   0,  0.0);
+    setup_run_and_test_mem("or r0 12 10", 0, 14.0);
+}
+
+#[test]
+fn test_logic_sla() {
+    setup_run_and_test_mem("sla r0  1 4", 0,  16.0);
+    setup_run_and_test_mem("sla r0 -1 4", 0, -16.0);
+}
+
+#[test]
+fn test_logic_sll() {
+    setup_run_and_test_mem("sll r0 1 4",  0, 16.0);
+    setup_run_and_test_mem("sll r0 3 1",  0,  6.0);
+    setup_run_and_test_mem("sll r0 1 64", 0,  1.0);
+}
+
+#[test]
+fn test_logic_sra() {
+    setup_run_and_test_mem("sra r0  16 4", 0,  1.0);
+    setup_run_and_test_mem("sra r0 -16 4", 0, -1.0);
+    setup_run_and_test_mem("sra r0  -1 8", 0, -1.0);
+}
+
+#[test]
+fn test_logic_srl() {
+    setup_run_and_test_mem("srl r0 16 4", 0, 1.0);
+    setup_run_and_test_mem("srl r0 -1 60", 0, 15.0);
+    setup_run_and_test_mem("srl r0 -16 63", 0, 1.0);
+}
+
+#[test]
+fn test_logic_xor() {
+    setup_run_and_test_mem("xor r0 1 1",   0, 0.0);
+    setup_run_and_test_mem("xor r0 1 0",   0, 1.0);
+    setup_run_and_test_mem("xor r0 12 10", 0, 6.0);
+}
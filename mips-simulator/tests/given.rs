@@ -0,0 +1,98 @@
+use mips_simulator::prelude::*;
+use mips_simulator::scenario::Target;
+use mips_simulator::test_utils::{dev_kinds, Given};
+
+#[test]
+fn given_registers() {
+    Given::program("add r0 r1 r2")
+        .reg(1, 2.0)
+        .reg(2, 3.0)
+        .until_finished()
+        .expect_reg(0, 5.0)
+        .check();
+}
+
+#[test]
+fn given_devices() {
+    let memory = dev_kinds()["LogicMemory"].make();
+    let sim = Given::program(
+        "\
+l r0 d0 Setting
+s d1 On 1
+s db Setting r0
+yield
+s d1 On 0",
+    )
+    .dev(0, memory.clone())
+    .dev(1, dev_kinds()["WallLight"].make())
+    .param(0, "Setting", 7.0)
+    .until_yield()
+    .expect_reg(0, 7.0)
+    .expect_param(1, "On", 1.0)
+    .expect_db("Setting", 7.0)
+    .expect(Target::Reg(0), Cmp::Gt, 6.0)
+    .check();
+    assert_eq!(sim.next_line_index(), 4);
+}
+
+#[test]
+fn given_steps_and_ticks() {
+    let program = "\
+main:
+add r0 r0 1
+yield
+j main";
+    // Labels are lines too
+    Given::program(program).steps(6).expect_reg(0, 2.0).check();
+    Given::program(program).ticks(3).expect_reg(0, 3.0).check();
+}
+
+#[test]
+fn given_tolerance() {
+    let then = Given::program("div r0 1 3").until_finished();
+    assert!(then.clone().expect_reg(0, 0.333).try_check().is_err());
+    then.with_tolerance(1e-3).expect_reg(0, 0.333).check();
+}
+
+#[test]
+fn given_failure_report() {
+    let report = Given::program("move r0 4")
+        .until_finished()
+        .expect_reg(0, 5.0)
+        .expect_param(0, "On", 1.0)
+        .try_check()
+        .unwrap_err();
+    assert!(report.starts_with(
+        "\
+expected r0 == 5, found 4
+expected d0.On == 1, found unset
+state (- expected, + actual):
+  ICState {
+      mem: [
+-         0: 5
++         0: 4
+          1: 0
+"
+    ));
+}
+
+#[test]
+#[should_panic(expected = "expected r0 == 5, found 4")]
+fn given_check_panics() {
+    Given::program("move r0 4")
+        .until_finished()
+        .expect_reg(0, 5.0)
+        .check();
+}
+
+#[test]
+#[should_panic(expected = "program did not end")]
+fn given_until_yield_never() {
+    let _ = Given::program("main:\nj main").until_yield();
+}
+
+#[test]
+#[should_panic(expected = "error on line")]
+fn given_runtime_error() {
+    let _ = Given::program("l r0 d0 Setting").until_finished();
+}
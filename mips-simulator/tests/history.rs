@@ -0,0 +1,204 @@
+use mips_simulator::prelude::*;
+use mips_simulator::test_utils::{dev_kinds, setup};
+
+#[test]
+fn step_back_mem_and_stack() {
+    let mut sim = setup(
+        "\
+move r0 1
+push 5
+move r0 2
+push 6",
+    )
+    .with_history();
+    sim.run_until_finished().unwrap();
+    assert_eq!(sim.steps(), 4);
+
+    sim.step_back(1).unwrap();
+    assert_eq!(sim.next_line_index(), 3);
+    assert_eq!(sim.state.get_stack_buffer()[1], 0.0);
+    assert_eq!(sim.state.get_mem(MEM_SIZE - 2).unwrap(), &1.0);
+
+    sim.step_back(2).unwrap();
+    assert_eq!(sim.next_line_index(), 1);
+    assert_eq!(sim.state.get_mem(0).unwrap(), &1.0);
+    assert_eq!(sim.state.get_stack_buffer()[0], 0.0);
+    assert_eq!(sim.steps(), 1);
+}
+
+#[test]
+fn step_back_jumps_and_aliases() {
+    let mut sim = setup(
+        "\
+alias x r1
+main:
+add x x 1
+blt x 3 main",
+    )
+    .with_history();
+    sim.run_until_finished().unwrap();
+    let end = sim.state.clone();
+
+    sim.goto(0).unwrap();
+    assert_eq!(sim.next_line_index(), 0);
+    assert!(sim.state.get_alias(&"x".into()).is_err());
+    assert!(sim.state.get_alias(&"main".into()).is_err());
+
+    // Replaying gives the same final state
+    sim.run_until_finished().unwrap();
+    assert_eq!(sim.state.to_string(), end.to_string());
+}
+
+#[test]
+fn step_back_devices() {
+    let kinds = dev_kinds();
+    let mut sim = setup(
+        "\
+s d0 Setting 3
+sb -851746783 Setting 4
+s db Setting 5",
+    )
+    .with_history();
+    sim.state
+        .set_dev(DevId::DevBuf(0), Some(kinds["LogicMemory"].make()))
+        .unwrap();
+    sim.state.dev_network_add(kinds["LogicMemory"].make());
+    sim.run_until_finished().unwrap();
+
+    sim.goto(1).unwrap();
+    let read_self = |sim: &ICSimulatorDefault| {
+        sim.state.get_dev(DevId::DevSelf).unwrap().read("Setting").unwrap()
+    };
+    let read_net = |sim: &ICSimulatorDefault| {
+        sim.state.dev_network_read(-851746783, None, None, "Setting", 1.0).unwrap()
+    };
+    assert_eq!(read_self(&sim), 0.0);
+    assert_eq!(read_net(&sim), 0.0);
+    let d0 = sim.state.get_dev(DevId::DevBuf(0)).unwrap();
+    assert_eq!(d0.read("Setting").unwrap(), 3.0);
+
+    // Going forward re-executes the steps
+    sim.goto(3).unwrap();
+    assert_eq!(read_self(&sim), 5.0);
+    assert_eq!(read_net(&sim), 4.0);
+}
+
+#[test]
+fn step_back_too_far() {
+    let mut sim = setup("move r0 1").with_history();
+    sim.step().unwrap();
+    assert!(sim.step_back(2).is_err());
+    assert_eq!(sim.state.get_mem(0).unwrap(), &1.0);
+}
+
+#[test]
+fn step_back_without_history() {
+    let mut sim = setup("move r0 1");
+    sim.step().unwrap();
+    assert_eq!(sim.steps(), 0);
+    assert!(sim.step_back(1).is_err());
+}
+
+#[test]
+fn step_back_over_sleep() {
+    let mut sim = setup(
+        "\
+move r0 1
+sleep 1.5
+move r0 2",
+    )
+    .with_history();
+    sim.run_ticks(10).unwrap();
+    assert_eq!(sim.ticks(), 4);
+
+    // Back into the tick after the sleep, which has not elapsed yet
+    sim.step_back(1).unwrap();
+    assert_eq!(sim.ticks(), 3);
+    assert_eq!(sim.sleep_ticks(), 0);
+
+    // Back before the sleep, which no longer suspends the program
+    sim.step_back(1).unwrap();
+    assert_eq!(sim.next_line_index(), 1);
+    assert_eq!(sim.ticks(), 0);
+    assert_eq!(sim.sleep_ticks(), 0);
+    let report = sim.tick().unwrap();
+    assert_eq!(report.tick, 0);
+    assert_eq!(report.end, TickEnd::Sleep(2));
+    assert_eq!(sim.ticks(), 1);
+}
+
+#[test]
+fn step_back_only_changed_devices() {
+    let kinds = dev_kinds();
+    let mut sim = setup(
+        "\
+div r0 0 0
+sb -851746783 Setting r0
+sb -851746783 Setting r0
+move r1 1",
+    )
+    .with_history();
+    sim.state.dev_network_add(kinds["LogicMemory"].make());
+    sim.state.dev_network_add(kinds["LogicMemory"].make());
+    sim.run_until_finished().unwrap();
+
+    let deltas: Vec<_> = sim.iter_history().collect();
+    assert_eq!(deltas[1].network.len(), 2);
+    // Writing the same NaN again is not a change
+    assert!(deltas[2].network.is_empty());
+    assert!(deltas[3].network.is_empty() && deltas[3].dev.is_empty());
+
+    sim.goto(1).unwrap();
+    let net = sim.state.dev_network_read(-851746783, None, None, "Setting", 1.0);
+    assert_eq!(net.unwrap(), 0.0);
+}
+
+#[test]
+fn step_back_over_behaviors() {
+    let kinds = dev_kinds();
+    let mut sim = setup(
+        "\
+s d1 Setting 10
+s d1 On 1
+yield
+sleep 1
+yield
+move r0 1",
+    )
+    .with_history()
+    .with_behaviors(Behaviors::builtin())
+    .with_environment(
+        Environment::new()
+            .with_day_ticks(8)
+            .with_atmosphere(Atmosphere::new(100.0, 40.0, 300.0))
+            .with_atmosphere(Atmosphere::new(100.0, 0.0, 300.0)),
+    );
+    sim.state
+        .set_dev(DevId::DevBuf(0), Some(kinds["SolarPanel"].make()))
+        .unwrap();
+    sim.state
+        .set_dev(DevId::DevBuf(1), Some(kinds["VolumePump"].make()))
+        .unwrap();
+
+    // State after the last tick ending at each number of steps (behaviors run at the tick end)
+    let snapshot = |sim: &ICSimulatorDefault| {
+        let dev = |i| sim.state.get_dev(DevId::DevBuf(i)).unwrap().clone();
+        (sim.env.clone(), dev(0), dev(1))
+    };
+    let mut snapshots = vec![Some(snapshot(&sim))];
+    while !sim.is_finished() {
+        sim.tick().unwrap();
+        snapshots.resize(sim.steps() + 1, None);
+        snapshots[sim.steps()] = Some(snapshot(&sim));
+    }
+    assert!(sim.env.tick > 4);
+
+    for (steps, expected) in snapshots.iter().enumerate().rev() {
+        if let Some(expected) = expected {
+            sim.goto(steps).unwrap();
+            assert_eq!(&snapshot(&sim), expected, "{}", steps);
+        }
+    }
+    assert_eq!(sim.env.tick, 0);
+    assert_eq!(sim.env.atmospheres[0].moles, 40.0);
+}
@@ -0,0 +1,126 @@
+use mips_simulator::prelude::*;
+use mips_simulator::test_utils::{dev_kinds, setup};
+
+fn memory() -> Device {
+    dev_kinds()["LogicMemory"].make().with_memory(8)
+}
+
+#[test]
+fn device_memory() {
+    let mut dev = memory();
+    dev.write_memory(3, 5.0).unwrap();
+    assert_eq!(dev.read_memory(3).unwrap(), 5.0);
+    assert!(dev.read_memory(8).is_err());
+    assert!(dev.write_memory(8, 1.0).is_err());
+    dev.clear_memory();
+    assert_eq!(dev.read_memory(3).unwrap(), 0.0);
+}
+
+#[test]
+fn get_put() {
+    let mut sim = setup(
+        "\
+put d0 2 10
+get r0 d0 2
+put db 7 3
+get r1 db 7
+push 4
+get r2 db 0
+clr d0
+get r3 d0 2",
+    );
+    sim.state.set_dev(DevId::DevBuf(0), Some(memory())).unwrap();
+    sim.run_until_finished().unwrap();
+
+    assert_eq!(sim.state.get_mem(0).unwrap(), &10.0);
+    // The self device memory is the stack
+    assert_eq!(sim.state.get_mem(1).unwrap(), &3.0);
+    assert_eq!(sim.state.get_stack_buffer()[7], 3.0);
+    assert_eq!(sim.state.get_mem(2).unwrap(), &4.0);
+    assert_eq!(sim.state.get_mem(3).unwrap(), &0.0);
+}
+
+#[test]
+fn direct_id() {
+    let mut sim = setup(
+        "\
+putd 100 1 6
+getd r0 100 1
+sd 101 Setting 9
+ld r1 101 Setting
+clrd 100
+getd r2 100 1",
+    );
+    sim.state
+        .set_dev(DevId::DevBuf(0), Some(memory().with_reference_id(100)))
+        .unwrap();
+    sim.state.dev_network_add(memory().with_reference_id(101));
+    sim.run_until_finished().unwrap();
+
+    assert_eq!(sim.state.get_mem(0).unwrap(), &6.0);
+    assert_eq!(sim.state.get_mem(1).unwrap(), &9.0);
+    assert_eq!(sim.state.get_mem(2).unwrap(), &0.0);
+    assert_eq!(
+        sim.state
+            .get_dev_by_id(101)
+            .unwrap()
+            .read("Setting")
+            .unwrap(),
+        9.0
+    );
+}
+
+#[test]
+fn memory_errors() {
+    for source in [
+        "get r0 d0 8",
+        "put d0 -1 0",
+        "get r0 d1 0",
+        "getd r0 102 0",
+        "ld r0 102 Setting",
+    ]
+    .iter()
+    {
+        let mut sim = setup(source);
+        sim.state.set_dev(DevId::DevBuf(0), Some(memory())).unwrap();
+        assert!(sim.step().is_err(), "{}", source);
+    }
+}
+
+#[test]
+fn world_read_other_stack() {
+    let mut world = WorldDefault::new();
+    let writer = world.add_ic(setup(
+        "\
+push 42
+yield",
+    ));
+    let housing = world.get_ic(writer).unwrap().housing();
+    let reader = world.add_ic(setup(
+        "\
+yield
+getd r0 r15 0
+putd r15 1 7",
+    ));
+    world
+        .get_mut_ic(reader)
+        .unwrap()
+        .sim
+        .state
+        .set_mem(15, housing as f64)
+        .unwrap();
+
+    world.run_ticks(2).unwrap();
+    assert_eq!(
+        world.get_device(housing).unwrap().reference_id,
+        Some(housing)
+    );
+    assert_eq!(
+        world.get_sim(reader).unwrap().state.get_mem(0).unwrap(),
+        &42.0
+    );
+    assert_eq!(
+        world.get_device(housing).unwrap().read_memory(1).unwrap(),
+        7.0
+    );
+}
@@ -0,0 +1,75 @@
+use mips_simulator::prelude::*;
+use mips_simulator::test_utils::{dev_kinds, setup};
+
+const IRON: i64 = -707307845;
+const COPPER: i64 = -404336834;
+
+fn furnace() -> Device {
+    let mut dev = dev_kinds()["LogicMemory"].make();
+    dev.reagents.insert(
+        IRON,
+        Reagent {
+            contents: 10.0,
+            required: 4.0,
+            recipe: 2.0,
+        },
+    );
+    dev
+}
+
+#[test]
+fn load_reagent_modes() {
+    let mut sim = setup(
+        "\
+lr r0 d0 0 -707307845
+lr r1 d0 1 -707307845
+lr r2 d0 2 -707307845
+lr r3 d0 0 -404336834",
+    );
+    sim.state
+        .set_dev(DevId::DevBuf(0), Some(furnace()))
+        .unwrap();
+    sim.run_until_finished().unwrap();
+
+    assert_eq!(sim.state.get_mem(0).unwrap(), &10.0);
+    assert_eq!(sim.state.get_mem(1).unwrap(), &4.0);
+    assert_eq!(sim.state.get_mem(2).unwrap(), &2.0);
+    // Reagents not in the table read as zero
+    assert_eq!(sim.state.get_mem(3).unwrap(), &0.0);
+}
+
+#[test]
+fn load_reagent_errors() {
+    // Unknown mode and unset device
+    for program in ["lr r0 d0 3 -707307845", "lr r0 d1 0 -707307845"] {
+        let mut sim = setup(program);
+        sim.state
+            .set_dev(DevId::DevBuf(0), Some(furnace()))
+            .unwrap();
+        assert!(sim.step().is_err());
+    }
+}
+
+#[test]
+fn reagent_kinds_from_ron() {
+    let kind: DeviceKind = ron::de::from_str(&format!(
+        r#"(
+    name: "Furnace",
+    hash: 545937711,
+    params: [],
+    reagents: {{ {}: (contents: 3.0), {}: (recipe: 1.5) }},
+)"#,
+        IRON, COPPER
+    ))
+    .unwrap();
+    let dev = kind.make();
+    assert_eq!(dev.read_reagent(ReagentMode::Contents, IRON), 3.0);
+    assert_eq!(dev.read_reagent(ReagentMode::Recipe, COPPER), 1.5);
+    assert_eq!(
+        dev.reagents[&COPPER],
+        Reagent {
+            recipe: 1.5,
+            ..Reagent::default()
+        }
+    );
+}
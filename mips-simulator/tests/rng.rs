@@ -0,0 +1,76 @@
+use mips_simulator::prelude::*;
+use mips_simulator::test_utils::setup;
+
+const PROGRAM: &str = "\
+rand r0
+rand r1
+rand r2";
+
+fn run_seeded(seed: u64) -> [f64; 3] {
+    let mut sim = setup(PROGRAM).with_seed(seed);
+    sim.run_until_finished().unwrap();
+    let mem = sim.state.get_mem_buffer();
+    [mem[0], mem[1], mem[2]]
+}
+
+#[test]
+fn seeded_rng() {
+    let mut a = SeededRng::new(7);
+    let mut b = SeededRng::new(7);
+    let xs: Vec<f64> = (0..100).map(|_| a.next_f64()).collect();
+    let ys: Vec<f64> = (0..100).map(|_| b.next_f64()).collect();
+    assert_eq!(xs, ys);
+    assert!(xs.iter().all(|x| (0.0..1.0).contains(x)));
+    assert_eq!(a.seed(), 7);
+
+    a.reset();
+    assert_eq!(a.next_f64(), xs[0]);
+    assert_ne!(SeededRng::new(8).next_f64(), xs[0]);
+}
+
+#[test]
+fn rand_replay() {
+    let a = run_seeded(42);
+    assert_eq!(a, run_seeded(42));
+    assert_ne!(a, run_seeded(43));
+    assert_ne!(a[0], a[1]);
+}
+
+#[test]
+fn rand_snapshot() {
+    let mut sim = setup(PROGRAM).with_seed(42);
+    sim.step().unwrap();
+    let s = ron::ser::to_string(&sim).unwrap();
+    let mut loaded: ICSimulatorDefault = ron::de::from_str(&s).unwrap();
+    assert_eq!(loaded.state.rng().seed(), 42);
+
+    sim.run_until_finished().unwrap();
+    loaded.run_until_finished().unwrap();
+    assert_eq!(sim.state.get_mem_buffer(), loaded.state.get_mem_buffer());
+    assert_eq!(loaded.state.get_mem_buffer()[..3], run_seeded(42));
+}
+
+#[test]
+fn rand_step_back() {
+    let mut sim = setup(PROGRAM).with_seed(42).with_history();
+    sim.step_n(2).unwrap();
+    let r1 = *sim.state.get_mem(1).unwrap();
+    sim.step_back(1).unwrap();
+    sim.step().unwrap();
+    assert_eq!(sim.state.get_mem(1).unwrap(), &r1);
+}
+
+#[test]
+fn scenario_seed() {
+    let scenario = Scenario::from_ron(
+        r#"(
+    program: Some("rand r0"),
+    seed: Some(42),
+    ticks: Some(1),
+)"#,
+    )
+    .unwrap();
+    let mut sim = scenario.simulator(&DeviceKinds::new()).unwrap();
+    sim.run_until_finished().unwrap();
+    assert_eq!(sim.state.get_mem(0).unwrap(), &run_seeded(42)[0]);
+}
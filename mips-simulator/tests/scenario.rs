@@ -0,0 +1,107 @@
+use mips_simulator::prelude::*;
+use mips_simulator::scenario::{AssertionResult, Target};
+use mips_simulator::test_utils::{dev_kinds, setup};
+
+const PATH: &str = "./tests/scenarios/pump-control.ron";
+
+#[test]
+fn scenario_load() {
+    let scenario = Scenario::load(PATH).unwrap();
+    assert_eq!(scenario.name, "Pump control");
+    assert_eq!(scenario.ticks(), 10);
+    assert_eq!(scenario.pins.len(), 2);
+    assert_eq!(scenario.network[0].label.as_deref(), Some("Target"));
+    assert_eq!(
+        scenario.timeline[1].target,
+        Target::Network("Target".into(), "Setting".into())
+    );
+}
+
+#[test]
+fn scenario_run() {
+    let scenario = Scenario::load(PATH).unwrap();
+    let report = scenario.run(&dev_kinds()).unwrap();
+    assert!(report.passed(), "{}", report);
+    assert_eq!(report.results.len(), 6);
+    assert_eq!(report.ticks, 10);
+}
+
+#[test]
+fn scenario_failures() {
+    let scenario = Scenario::from_ron(
+        r#"(
+    name: "Failing",
+    assertions: [
+        (tick: 2, target: Reg(0), cmp: Eq, value: 2.0),
+        (tick: 2, target: Reg(0), cmp: Ge, value: 3.0),
+        (tick: 2, target: Pin(3, "On"), cmp: Eq, value: 0.0),
+    ],
+)"#,
+    )
+    .unwrap();
+    // Run against a simulator given separately
+    let mut sim = setup(
+        "\
+main:
+add r0 r0 1
+yield
+j main",
+    );
+    scenario.setup(&mut sim, &dev_kinds()).unwrap();
+    let report = scenario.run_simulator(&mut sim).unwrap();
+
+    assert!(!report.passed());
+    let failures: Vec<&AssertionResult> = report.failures().collect();
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures[0].actual, Some(2.0));
+    assert_eq!(failures[1].actual, None);
+    assert_eq!(
+        report.to_string(),
+        "\
+PASS tick 2: r0 == 2 (actual 2)
+FAIL tick 2: r0 >= 3 (actual 2)
+FAIL tick 2: d3.On == 0 (actual unset)
+scenario `Failing`: 1 passed, 2 failed (2 ticks)"
+    );
+}
+
+#[test]
+fn scenario_errors() {
+    let kinds = dev_kinds();
+    let no_program = Scenario::from_ron("()").unwrap();
+    assert!(matches!(
+        no_program.run(&kinds),
+        Err(ScenarioError::NoProgram)
+    ));
+
+    let unknown_kind =
+        Scenario::from_ron(r#"(program: Some("yield"), pins: { 0: (kind: "Unknown") })"#).unwrap();
+    assert!(matches!(
+        unknown_kind.run(&kinds),
+        Err(ScenarioError::UnknownKind(_))
+    ));
+
+    let unknown_pin =
+        Scenario::from_ron(r#"(program: Some("yield"), pins: { 6: (kind: "LogicMemory") })"#)
+            .unwrap();
+    assert!(matches!(
+        unknown_pin.run(&kinds),
+        Err(ScenarioError::UnknownPin(6))
+    ));
+
+    let unknown_target = Scenario::from_ron(
+        r#"(
+    program: Some("yield"),
+    pins: { 0: (kind: "LogicMemory") },
+    ticks: Some(2),
+    timeline: [(tick: 1, target: Pin(0, "Unknown"), value: 1.0)],
+)"#,
+    )
+    .unwrap();
+    match unknown_target.run(&kinds) {
+        Err(ScenarioError::UnknownParam(target)) => assert_eq!(target, "d0.Unknown"),
+        res => panic!("{:?}", res),
+    }
+
+    assert!(Scenario::from_ron("(ticks: -1)").is_err());
+}
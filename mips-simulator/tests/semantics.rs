@@ -0,0 +1,254 @@
+use mips_simulator::prelude::*;
+use mips_simulator::test_utils::{dev_kinds, setup};
+
+fn run(source: &'static str, semantics: Semantics) -> Result<ICSimulatorDefault, ICSimulatorError> {
+    let mut sim = setup(source);
+    sim.state.set_semantics(semantics);
+    sim.run_until_finished().map(|_| sim)
+}
+
+fn r0(source: &'static str, semantics: Semantics) -> f64 {
+    *run(source, semantics).unwrap().state.get_mem(0).unwrap()
+}
+
+fn fails(source: &'static str, semantics: Semantics) -> bool {
+    run(source, semantics).is_err()
+}
+
+#[test]
+fn default_is_game() {
+    assert_eq!(ICState::default().semantics(), &Semantics::game());
+}
+
+#[test]
+fn div_by_zero() {
+    let game = Semantics::game();
+    assert_eq!(r0("div r0 1 0", game), f64::INFINITY);
+    assert_eq!(r0("div r0 -1 0", game), f64::NEG_INFINITY);
+    assert!(r0("div r0 0 0", game).is_nan());
+    assert!(r0("mod r0 1 0", game).is_nan());
+
+    let zero = game.with_div_by_zero(DomainMode::Zero);
+    assert_eq!(r0("div r0 1 0", zero), 0.0);
+    assert_eq!(r0("mod r0 1 0", zero), 0.0);
+    assert_eq!(r0("div r0 1 2", zero), 0.5);
+
+    let error = game.with_div_by_zero(DomainMode::Error);
+    assert!(fails("div r0 1 0", error));
+    assert!(fails("mod r0 1 0", error));
+    assert!(!fails("mod r0 5 3", error));
+}
+
+#[test]
+fn domain() {
+    let game = Semantics::game();
+    assert!(r0("sqrt r0 -1", game).is_nan());
+    assert_eq!(r0("log r0 0", game), f64::NEG_INFINITY);
+    assert!(r0("log r0 -1", game).is_nan());
+    assert!(r0("asin r0 2", game).is_nan());
+    assert!(r0("acos r0 -2", game).is_nan());
+
+    let zero = game.with_domain(DomainMode::Zero);
+    assert_eq!(r0("sqrt r0 -1", zero), 0.0);
+    assert_eq!(r0("log r0 0", zero), 0.0);
+    assert_eq!(r0("asin r0 2", zero), 0.0);
+    assert_eq!(r0("acos r0 -2", zero), 0.0);
+    assert_eq!(r0("sqrt r0 4", zero), 2.0);
+
+    let error = game.with_domain(DomainMode::Error);
+    for source in ["sqrt r0 -1", "log r0 0", "asin r0 2", "acos r0 -2"].iter() {
+        assert!(fails(source, error), "{}", source);
+    }
+    for source in ["sqrt r0 0", "log r0 1", "asin r0 1", "acos r0 -1"].iter() {
+        assert!(!fails(source, error), "{}", source);
+    }
+}
+
+#[test]
+fn nan() {
+    let game = Semantics::game();
+    assert!(r0("sqrt r0 -1", game).is_nan());
+    // NaN propagates through further operations
+    assert!(r0("sqrt r1 -1\nadd r0 r1 1", game).is_nan());
+
+    let zero = game.with_nan(NanMode::Zero);
+    assert_eq!(r0("sqrt r0 -1", zero), 0.0);
+
+    let error = game.with_nan(NanMode::Error);
+    assert!(fails("sqrt r0 -1", error));
+    assert!(fails("div r0 0 0", error));
+}
+
+#[test]
+fn eps_compare() {
+    const SOURCE: &str = "\
+add r1 0.1 0.2
+sub r1 r1 0.3
+seqz r0 r1";
+    assert_eq!(r0(SOURCE, Semantics::game()), 0.0);
+
+    let eps = Semantics::game().with_eps_compare(true);
+    assert_eq!(r0("seq r0 1 1", eps), 1.0);
+    assert_eq!(r0("seq r0 1 1.5", eps), 0.0);
+    assert_eq!(r0("seq r0 0 1e-45", eps), 1.0);
+    assert_eq!(r0("sne r0 0 1e-45", eps), 0.0);
+    assert_eq!(r0("seqz r0 1e-45", eps), 1.0);
+    assert_eq!(r0("snez r0 1e-45", eps), 0.0);
+    assert_eq!(r0("select r0 1e-45 2 3", eps), 3.0);
+    assert_eq!(r0("beq 0 1e-45 3\nmove r0 1\nj 4\nmove r0 2", eps), 2.0);
+    assert_eq!(r0("bnez 1e-45 3\nmove r0 1\nj 4\nmove r0 2", eps), 1.0);
+    assert_eq!(r0("breqz 1e-45 3\nmove r0 1\nj 4\nmove r0 2", eps), 2.0);
+
+    let exact = Semantics::game();
+    assert_eq!(r0("seq r0 0 1e-45", exact), 0.0);
+    assert_eq!(r0("select r0 1e-45 2 3", exact), 2.0);
+    assert_eq!(r0("select r0 0 2 3", exact), 3.0);
+}
+
+#[test]
+fn index_rounding() {
+    let round = Semantics::game();
+    let truncate = round.with_index_rounding(IndexRounding::Truncate);
+    let exact = round.with_index_rounding(IndexRounding::Exact);
+
+    // Indirect registers
+    const INDIRECT: &str = "\
+move r2 5
+move r1 1.5
+move r0 rr1";
+    assert_eq!(r0(INDIRECT, round), 5.0);
+    assert_eq!(r0(INDIRECT, truncate), 1.5);
+    assert!(fails(INDIRECT, exact));
+
+    // Half to even
+    assert_eq!(r0("move r3 1\nmove r1 2.5\nmove r0 rr1", round), 0.0);
+    assert_eq!(r0("move r3 1\nmove r1 3.5\nmove r0 rr1", round), 0.0);
+    assert_eq!(r0("move r4 1\nmove r1 3.5\nmove r0 rr1", round), 1.0);
+
+    // Jumps
+    const JUMP: &str = "\
+j 2.6
+move r0 1
+add r0 r0 2
+add r0 r0 3";
+    assert_eq!(r0(JUMP, round), 3.0);
+    assert_eq!(r0(JUMP, truncate), 5.0);
+    assert!(fails(JUMP, exact));
+    assert!(fails("j -1", round));
+
+    // Stack
+    const STACK: &str = "\
+move sp 1.6
+push 7
+get r0 db 2";
+    assert_eq!(r0(STACK, round), 7.0);
+    assert_eq!(r0(STACK, truncate), 0.0);
+    assert!(fails(STACK, exact));
+
+    // Near integers are exact enough
+    assert_eq!(r0("move r1 1\nmove r0 rr1", exact), 1.0);
+}
+
+#[test]
+fn index_rounding_memory() {
+    let kinds = dev_kinds();
+    let round = Semantics::game();
+    let truncate = round.with_index_rounding(IndexRounding::Truncate);
+    for (semantics, ans) in [(round, 2.0), (truncate, 1.0)].iter() {
+        let mut sim = setup("put d0 1.5 1\nput d0 2 2\nget r0 d0 1.5");
+        sim.state.set_semantics(*semantics);
+        let dev = kinds["LogicMemory"].make().with_memory(4);
+        sim.state.set_dev(DevId::DevBuf(0), Some(dev)).unwrap();
+        sim.run_until_finished().unwrap();
+        assert_eq!(sim.state.get_mem(0).unwrap(), ans);
+    }
+}
+
+#[test]
+fn strict() {
+    let strict = Semantics::strict();
+    assert!(fails("div r0 1 0", strict));
+    assert!(fails("sqrt r0 -1", strict));
+    assert!(fails("move r1 0.5\nmove r0 rr1", strict));
+    assert_eq!(r0("div r0 1 2", strict), 0.5);
+}
+
+// Semantics of each mode
+fn modes() -> Vec<Semantics> {
+    let game = Semantics::game();
+    vec![
+        game,
+        game.with_eps_compare(true),
+        game.with_index_rounding(IndexRounding::Truncate),
+        game.with_index_rounding(IndexRounding::Exact),
+        game.with_div_by_zero(DomainMode::Zero),
+        game.with_domain(DomainMode::Error),
+        game.with_nan(NanMode::Zero),
+        Semantics::strict(),
+    ]
+}
+
+#[test]
+fn select() {
+    // `a != 0 ? b : c` as in the game, where it used to be `a == 0 ? b : c`
+    for semantics in modes() {
+        assert_eq!(r0("select r0 1 2 3", semantics), 2.0);
+        assert_eq!(r0("select r0 -0.5 2 3", semantics), 2.0);
+        assert_eq!(r0("select r0 0 2 3", semantics), 3.0);
+    }
+}
+
+#[test]
+fn approx_set() {
+    for semantics in modes() {
+        assert_eq!(r0("sap r0 100 101 0.01", semantics), 1.0);
+        assert_eq!(r0("sap r0 100 102 0.01", semantics), 0.0);
+        assert_eq!(r0("sap r0 -100 -101 0.01", semantics), 1.0);
+        assert_eq!(r0("sapz r0 0 0.1", semantics), 1.0);
+        assert_eq!(r0("sapz r0 1 0.1", semantics), 0.0);
+        assert_eq!(r0("sna r0 100 101 0.01", semantics), 0.0);
+        assert_eq!(r0("sna r0 100 102 0.01", semantics), 1.0);
+        assert_eq!(r0("snaz r0 0 0.1", semantics), 0.0);
+        assert_eq!(r0("snaz r0 1 0.1", semantics), 1.0);
+    }
+}
+
+#[test]
+fn approx_branch() {
+    for semantics in modes() {
+        assert_eq!(r0("bap 100 101 0.01 3\nmove r0 1\nj 4\nmove r0 2", semantics), 2.0);
+        assert_eq!(r0("bap 100 102 0.01 3\nmove r0 1\nj 4\nmove r0 2", semantics), 1.0);
+        assert_eq!(r0("bapz 0 0.1 3\nmove r0 1\nj 4\nmove r0 2", semantics), 2.0);
+        assert_eq!(r0("bapz 1 0.1 3\nmove r0 1\nj 4\nmove r0 2", semantics), 1.0);
+        assert_eq!(r0("bapal 100 101 0.01 3\nmove r0 1\nj 4\nmove r0 2", semantics), 2.0);
+        assert_eq!(r0("bapzal 1 0.1 3\nmove r0 1\nj 4\nmove r0 2", semantics), 1.0);
+        assert_eq!(r0("bna 100 102 0.01 3\nmove r0 1\nj 4\nmove r0 2", semantics), 2.0);
+        assert_eq!(r0("bnaz 0 0.1 3\nmove r0 1\nj 4\nmove r0 2", semantics), 1.0);
+        assert_eq!(r0("brap 100 101 0.01 2\nmove r0 1\nadd r0 r0 2", semantics), 2.0);
+        assert_eq!(r0("brap 100 102 0.01 2\nmove r0 1\nadd r0 r0 2", semantics), 3.0);
+        assert_eq!(r0("brapz 0 0.1 2\nmove r0 1\nadd r0 r0 2", semantics), 2.0);
+        assert_eq!(r0("brapz 1 0.1 2\nmove r0 1\nadd r0 r0 2", semantics), 3.0);
+        assert_eq!(r0("brna 100 102 0.01 2\nmove r0 1\nadd r0 r0 2", semantics), 2.0);
+        assert_eq!(r0("brnaz 0 0.1 2\nmove r0 1\nadd r0 r0 2", semantics), 3.0);
+    }
+
+    // Relative offsets are rounded like other indices
+    const OFFSET: &str = "brap 1 1 0 1.5\nmove r0 1\nadd r0 r0 2";
+    assert_eq!(r0(OFFSET, Semantics::game()), 2.0);
+    assert_eq!(r0(OFFSET, Semantics::game().with_index_rounding(IndexRounding::Truncate)), 3.0);
+    assert!(fails(OFFSET, Semantics::strict()));
+}
+
+#[test]
+fn mod_negative() {
+    for semantics in modes() {
+        // Euclidean remainder, which is never negative
+        assert_eq!(r0("mod r0 -7 3", semantics), 2.0);
+        assert_eq!(r0("mod r0 7 -3", semantics), 1.0);
+        assert_eq!(r0("mod r0 -7 -3", semantics), 2.0);
+        assert_eq!(r0("mod r0 -1.5 1", semantics), 0.5);
+    }
+    assert!(r0("mod r0 -1 0", Semantics::game()).is_nan());
+    assert_eq!(r0("mod r0 -1 0", Semantics::game().with_div_by_zero(DomainMode::Zero)), 0.0);
+    assert!(fails("mod r0 -1 0", Semantics::strict()));
+}
@@ -0,0 +1,118 @@
+use mips_simulator::prelude::*;
+use mips_simulator::test_utils::{dev_kinds, setup};
+
+type ICStateDefault = ICState<MEM_SIZE, DEV_SIZE, STACK_SIZE>;
+
+const PROGRAM: &str = "\
+alias counter r0
+define step 2
+main:
+add counter counter step
+push counter
+s d0 Setting counter
+sb HASH(\"StructureVolumePump\") On 1
+yield
+j main";
+
+fn setup_paused() -> ICSimulatorDefault {
+    let kinds = dev_kinds();
+    let mut sim = setup(PROGRAM);
+    sim.state
+        .set_dev(DevId::DevBuf(0), Some(kinds["LogicMemory"].make()))
+        .unwrap();
+    sim.state.dev_network_add(kinds["VolumePump"].make());
+    sim.run_ticks(3).unwrap();
+    sim
+}
+
+fn assert_same_state(a: &ICStateDefault, b: &ICStateDefault) {
+    assert_eq!(a.get_mem_buffer(), b.get_mem_buffer());
+    assert!(a.iter_dev().eq(b.iter_dev()));
+    assert_eq!(
+        a.get_dev(DevId::DevSelf).ok(),
+        b.get_dev(DevId::DevSelf).ok()
+    );
+    for alias in ["sp", "ra", "db", "counter", "step", "main", "x"].iter() {
+        let alias = alias.to_string();
+        assert_eq!(a.get_alias(&alias).ok(), b.get_alias(&alias).ok());
+    }
+}
+
+fn assert_same(a: &ICSimulatorDefault, b: &ICSimulatorDefault) {
+    assert_same_state(&a.state, &b.state);
+    assert_eq!(a.state.network_devices(), b.state.network_devices());
+    assert_eq!(
+        a.state.get_stack_buffer()[..],
+        b.state.get_stack_buffer()[..]
+    );
+    assert_eq!(a.lines, b.lines);
+    assert_eq!(a.ticks(), b.ticks());
+    assert_eq!(a.next_line_index(), b.next_line_index());
+}
+
+#[test]
+fn ron_round_trip() {
+    let sim = setup_paused();
+    let s = ron::ser::to_string(&sim).unwrap();
+    let loaded: ICSimulatorDefault = ron::de::from_str(&s).unwrap();
+    assert_same(&sim, &loaded);
+    assert_eq!(
+        loaded.state.get_alias(&"counter".into()).unwrap(),
+        &AliasKind::MemId(0)
+    );
+    assert_eq!(
+        loaded.state.get_alias(&"step".into()).unwrap(),
+        &AliasKind::Def(2.0)
+    );
+}
+
+#[test]
+fn json_round_trip() {
+    let sim = setup_paused();
+    let s = serde_json::to_string(&sim).unwrap();
+    let loaded: ICSimulatorDefault = serde_json::from_str(&s).unwrap();
+    assert_same(&sim, &loaded);
+}
+
+#[test]
+fn resume_after_load() {
+    let mut sim = setup_paused();
+    let s = ron::ser::to_string(&sim).unwrap();
+    let mut loaded: ICSimulatorDefault = ron::de::from_str(&s).unwrap();
+
+    sim.run_ticks(2).unwrap();
+    loaded.run_ticks(2).unwrap();
+    assert_same(&sim, &loaded);
+    assert_eq!(loaded.state.get_mem(0).unwrap(), &10.0);
+    assert_eq!(loaded.state.get_stack_buffer()[4], 10.0);
+    let pump = &loaded.state.network_devices().values().next().unwrap()[0];
+    assert_eq!(pump.read("On").unwrap(), 1.0);
+}
+
+#[test]
+fn state_round_trip() {
+    let state = ICState::default()
+        .with_mem(3, 1.5)
+        .with_dev(1, dev_kinds()["LogicMemory"].make())
+        .with_alias("x", AliasKind::DevId(DevId::DevBuf(1)));
+    let s = serde_json::to_string(&state).unwrap();
+    let loaded: ICStateDefault = serde_json::from_str(&s).unwrap();
+    assert_same_state(&state, &loaded);
+    assert!(loaded.is_dev_set(DevId::DevBuf(1)).unwrap());
+}
+
+#[test]
+fn state_wrong_size() {
+    let s = ron::ser::to_string(&ICState::default()).unwrap();
+    let res = ron::de::from_str::<ICState<MEM_SIZE, DEV_SIZE, 16>>(&s);
+    assert!(res.is_err());
+}
+
+#[test]
+fn line_as_source() {
+    let line = Line::Blank(1);
+    assert_eq!(ron::ser::to_string(&line).unwrap(), "Blank(1)");
+    let line: Line = ron::de::from_str(r#"Expr(0, "add r0 r1 2")"#).unwrap();
+    assert_eq!(line.to_string(), r#"(0) "add r0 r1 2""#);
+    assert!(ron::de::from_str::<Line>(r#"Expr(0, "add r0")"#).is_err());
+}
@@ -66,7 +66,7 @@ blt x 5 main
 
     run_until_finished!(sim);
     const LOGIC_DATA: i64 = -851746783;
-    let read = |mode| sim.state.dev_network_read(LOGIC_DATA, &"Setting", mode);
+    let read = |mode| sim.state.dev_network_read(LOGIC_DATA, None, None, "Setting", mode);
 
     // Should be on the 6th line (a.k.a. index 5)
     assert_eq!(sim.next_line_index(), 5);
@@ -83,3 +83,20 @@ blt x 5 main
     // LogicMemory(Setting) max should be 5
     assert_eq!(read(3.0).unwrap(), 5.0);
 }
+
+#[test]
+fn simulate_const_literals() {
+    const PROGRAM: &str = "\
+define MEMORY HASH(\"StructureLogicMemory\")
+sb MEMORY Setting $FF
+lb r0 HASH(\"StructureLogicMemory\") Setting 1
+move r1 %1010
+";
+    let (mut sim, kinds) = setup!(PROGRAM);
+    sim.state.dev_network_add(kinds["LogicMemory"].make());
+    run_until_finished!(sim);
+
+    assert_alias!(sim.state, "MEMORY", AliasKind::Def(-851746783.0));
+    assert_mem!(sim.state, 0, 255.0);
+    assert_mem!(sim.state, 1, 10.0);
+}
@@ -0,0 +1,82 @@
+use mips_simulator::device::ParamKind;
+use mips_simulator::prelude::*;
+use mips_simulator::test_utils::{dev_kinds, setup};
+
+fn sorter() -> Device {
+    let mut kind = dev_kinds()["LogicMemory"].clone();
+    let mut output = SlotKind::new("Export");
+    output.params.push(ParamKind::ReadWrite("Lock".into()));
+    kind.slots = vec![SlotKind::new("Import"), output];
+    kind.make()
+}
+
+#[test]
+fn load_slot() {
+    let mut sim = setup(
+        "\
+ls r0 d0 0 Occupied
+ls r1 d0 1 Quantity
+ls r2 db 0 Occupied",
+    );
+    let mut dev = sorter();
+    dev.try_get_mut_slot(0)
+        .unwrap()
+        .set("Occupied", 1.0)
+        .unwrap();
+    dev.try_get_mut_slot(1)
+        .unwrap()
+        .set("Quantity", 20.0)
+        .unwrap();
+    sim.state.set_dev(DevId::DevBuf(0), Some(dev)).unwrap();
+    sim.run_until_finished().unwrap();
+
+    assert_eq!(sim.state.get_mem(0).unwrap(), &1.0);
+    assert_eq!(sim.state.get_mem(1).unwrap(), &20.0);
+    // The housing has a chip slot
+    assert_eq!(sim.state.get_mem(2).unwrap(), &0.0);
+}
+
+#[test]
+fn store_slot() {
+    let mut sim = setup("ss d0 1 Lock 1");
+    sim.state.set_dev(DevId::DevBuf(0), Some(sorter())).unwrap();
+    sim.run_until_finished().unwrap();
+
+    let dev = sim.state.get_dev(DevId::DevBuf(0)).unwrap();
+    assert_eq!(dev.read_slot(1, "Lock").unwrap(), 1.0);
+}
+
+#[test]
+fn slot_errors() {
+    let mut dev = sorter();
+    // Unknown slot, unknown slot param and read only slot param
+    assert!(dev.read_slot(2, "Occupied").is_err());
+    assert!(dev.read_slot(0, "Lock").is_err());
+    assert!(dev.write_slot(0, "Occupied", 1.0).is_err());
+
+    for program in [
+        "ls r0 d0 5 Occupied",
+        "ls r0 d0 -1 Occupied",
+        "ss d0 0 Quantity 1",
+    ] {
+        let mut sim = setup(program);
+        sim.state.set_dev(DevId::DevBuf(0), Some(sorter())).unwrap();
+        assert!(sim.step().is_err());
+    }
+}
+
+#[test]
+fn slot_kinds_from_ron() {
+    let kind: DeviceKind = ron::de::from_str(
+        r#"(
+    name: "Sorter",
+    hash: -1009150565,
+    params: [ReadWrite("On")],
+    slots: [(name: "Import", params: [Read("Occupied"), Read("Quantity")])],
+)"#,
+    )
+    .unwrap();
+    let dev = kind.make();
+    assert_eq!(dev.slots.len(), 1);
+    assert_eq!(dev.read_slot(0, "Quantity").unwrap(), 0.0);
+}
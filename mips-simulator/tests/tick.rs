@@ -0,0 +1,111 @@
+use mips_simulator::prelude::*;
+use mips_simulator::test_utils::setup;
+
+#[test]
+fn tick_budget() {
+    let mut sim = setup(
+        "\
+main:
+add r0 r0 1
+j main",
+    )
+    .with_instructions_per_tick(10);
+
+    let report = sim.tick().unwrap();
+    assert_eq!(report, TickReport { tick: 0, steps: 10, end: TickEnd::Budget });
+    let report = sim.tick().unwrap();
+    assert_eq!(report, TickReport { tick: 1, steps: 10, end: TickEnd::Budget });
+    assert_eq!(sim.ticks(), 2);
+    // 20 lines alternating label, add and jump
+    assert_eq!(sim.state.get_mem(0).unwrap(), &7.0);
+}
+
+#[test]
+fn tick_default_budget() {
+    let mut sim = setup(
+        "\
+main:
+add r0 r0 1
+j main",
+    );
+    let report = sim.tick().unwrap();
+    assert_eq!(report.steps, INSTRUCTIONS_PER_TICK);
+    assert_eq!(report.end, TickEnd::Budget);
+}
+
+#[test]
+fn tick_yield() {
+    let mut sim = setup(
+        "\
+main:
+add r0 r0 1
+yield
+j main",
+    );
+    let reports = sim.run_ticks(3).unwrap();
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports[0], TickReport { tick: 0, steps: 3, end: TickEnd::Yield });
+    // Resumes after the yield
+    assert_eq!(reports[1], TickReport { tick: 1, steps: 4, end: TickEnd::Yield });
+    assert_eq!(reports[2], TickReport { tick: 2, steps: 4, end: TickEnd::Yield });
+    assert_eq!(sim.state.get_mem(0).unwrap(), &3.0);
+}
+
+#[test]
+fn tick_sleep() {
+    let mut sim = setup(
+        "\
+move r0 1
+sleep 1.5
+move r0 2",
+    );
+    let reports = sim.run_ticks(10).unwrap();
+    let ends: Vec<TickEnd> = reports.iter().map(|r| r.end).collect();
+    // 1.5 seconds is 3 ticks, including the one in which `sleep` executed
+    assert_eq!(
+        ends,
+        vec![
+            TickEnd::Sleep(2),
+            TickEnd::Sleeping,
+            TickEnd::Sleeping,
+            TickEnd::Finished,
+        ]
+    );
+    assert_eq!(reports[3].steps, 1);
+    assert_eq!(sim.state.get_mem(0).unwrap(), &2.0);
+}
+
+#[test]
+fn tick_sleep_zero() {
+    let mut sim = setup(
+        "\
+sleep 0
+move r0 2",
+    );
+    let reports = sim.run_ticks(10).unwrap();
+    assert_eq!(reports[0].end, TickEnd::Sleep(0));
+    assert_eq!(reports[1].end, TickEnd::Finished);
+    assert_eq!(reports.len(), 2);
+}
+
+#[test]
+fn step_past_yield() {
+    let mut sim = setup(
+        "\
+yield
+move r0 1",
+    );
+    sim.step().unwrap();
+    assert_eq!(sim.next_line_index(), 1);
+    sim.run_until_finished().unwrap();
+    assert_eq!(sim.state.get_mem(0).unwrap(), &1.0);
+}
+
+#[test]
+fn run_ticks_huge() {
+    // Ends when the program finishes, without reserving a report for each requested tick
+    let mut sim = setup("yield\nmove r0 1");
+    let reports = sim.run_ticks(usize::MAX).unwrap();
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[1].end, TickEnd::Finished);
+}
@@ -0,0 +1,193 @@
+use mips_simulator::prelude::*;
+use mips_simulator::simulator::SimStatus;
+use mips_simulator::test_utils::{dev_kinds, setup};
+use mips_simulator::watcher::{Change, WatchValue};
+
+#[test]
+fn watch_mem() {
+    let mut sim = setup(
+        "\
+move r1 5
+move r0 1
+move r0 1
+move r0 2",
+    );
+    sim.watcher.watch(Watch::Mem(0));
+
+    assert!(matches!(sim.run_until_break().unwrap(), SimStatus::Stopped(2)));
+    let report = sim.watcher.report().unwrap();
+    assert_eq!(report.line, 1);
+    assert_eq!(
+        report.changes,
+        vec![Change {
+            watch: Watch::Mem(0),
+            old: WatchValue::Num(0.0),
+            new: WatchValue::Num(1.0),
+        }]
+    );
+    assert_eq!(report.breakpoint, None);
+
+    // Writing the same value is not a change
+    assert!(matches!(sim.run_until_break().unwrap(), SimStatus::Stopped(4)));
+    assert_eq!(sim.watcher.report().unwrap().line, 3);
+    assert!(sim.is_finished());
+}
+
+#[test]
+fn watch_stack_and_alias() {
+    let mut sim = setup(
+        "\
+push 7
+alias x r3",
+    )
+    .with_watcher(
+        Watcher::new()
+            .with_watch(Watch::Stack(0))
+            .with_watch(Watch::Alias("x".into())),
+    );
+
+    sim.run_until_break().unwrap();
+    let report = sim.watcher.report().unwrap();
+    assert_eq!(report.changes[0].watch, Watch::Stack(0));
+    assert_eq!(report.changes[0].new, WatchValue::Num(7.0));
+
+    sim.run_until_break().unwrap();
+    let report = sim.watcher.report().unwrap();
+    assert_eq!(report.changes[0].old, WatchValue::Unset);
+    assert_eq!(report.changes[0].new, WatchValue::Alias(AliasKind::MemId(3)));
+}
+
+#[test]
+fn watch_dev_param() {
+    let kinds = dev_kinds();
+    let mut sim = setup(
+        "\
+s d0 Setting 3
+s db Setting 4",
+    );
+    sim.state
+        .set_dev(DevId::DevBuf(0), Some(kinds["LogicMemory"].make()))
+        .unwrap();
+    sim.watcher.watch(Watch::DevParam(DevId::DevSelf, "Setting".into()));
+
+    sim.run_until_break().unwrap();
+    let report = sim.watcher.report().unwrap();
+    assert_eq!(report.line, 1);
+    assert_eq!(report.to_string(), "line 1\n    changed db.Setting from 0 to 4");
+}
+
+#[test]
+fn breakpoint_line() {
+    let mut sim = setup(
+        "\
+main:
+add r0 r0 1
+j main",
+    );
+    sim.watcher.add_breakpoint(Breakpoint::line(1));
+
+    for n in 1..4 {
+        assert!(matches!(sim.run_until_break().unwrap(), SimStatus::Stopped(1)));
+        assert_eq!(sim.state.get_mem(0).unwrap(), &(n as f64 - 1.0));
+    }
+}
+
+#[test]
+fn breakpoint_conditional() {
+    let mut sim = setup(
+        "\
+main:
+add r0 r0 1
+j main",
+    );
+    let condition = Condition::new(Watch::Mem(0), Cmp::Ge, 5.0);
+    sim.watcher.add_breakpoint(Breakpoint::conditional(2, condition));
+
+    sim.run_until_break().unwrap();
+    let report = sim.watcher.report().unwrap();
+    assert_eq!(report.line, 1);
+    assert_eq!(sim.state.get_mem(0).unwrap(), &5.0);
+    assert_eq!(
+        report.breakpoint.as_ref().unwrap().to_string(),
+        "breakpoint at line 2 if r0 >= 5"
+    );
+}
+
+#[test]
+fn breakpoint_ends_tick() {
+    let mut sim = setup(
+        "\
+main:
+add r0 r0 1
+j main",
+    );
+    sim.watcher.add_breakpoint(Breakpoint::line(2));
+
+    let report = sim.tick().unwrap();
+    assert_eq!(report.end, TickEnd::Break);
+    assert_eq!(report.steps, 2);
+}
+
+#[test]
+fn watch_nan_unchanged() {
+    let mut sim = setup(
+        "\
+div r0 0 0
+move r1 1
+move r0 2",
+    );
+    sim.watcher.watch(Watch::Mem(0));
+
+    assert!(matches!(sim.run_until_break().unwrap(), SimStatus::Stopped(1)));
+    assert!(matches!(
+        sim.watcher.report().unwrap().changes[0].new,
+        WatchValue::Num(x) if x.is_nan()
+    ));
+    // NaN staying NaN is not a change
+    assert!(matches!(sim.run_until_break().unwrap(), SimStatus::Stopped(3)));
+    assert_eq!(sim.watcher.report().unwrap().line, 2);
+}
+
+#[test]
+fn breakpoint_first_line() {
+    let mut sim = setup(
+        "\
+main:
+add r0 r0 1
+j main",
+    );
+    sim.watcher.add_breakpoint(Breakpoint::line(0));
+
+    // Stops before running any line, then continues past the breakpoint
+    assert!(matches!(sim.run_until_break().unwrap(), SimStatus::Stopped(0)));
+    assert_eq!(sim.watcher.report().unwrap().line, 0);
+    assert_eq!(sim.state.get_mem(0).unwrap(), &0.0);
+    assert!(matches!(sim.run_until_break().unwrap(), SimStatus::Stopped(0)));
+    assert_eq!(sim.state.get_mem(0).unwrap(), &1.0);
+
+    let mut sim = setup("yield").with_watcher(Watcher::new().with_breakpoint(Breakpoint::line(0)));
+    let report = sim.tick().unwrap();
+    assert_eq!(report.end, TickEnd::Break);
+    assert_eq!(report.steps, 0);
+    assert_eq!(sim.tick().unwrap().end, TickEnd::Yield);
+}
+
+#[test]
+fn watch_ends_yield_and_sleep_ticks() {
+    let mut sim = setup(
+        "\
+yield
+sleep 1",
+    );
+    sim.watcher.add_breakpoint(Breakpoint::line(1));
+    sim.watcher.add_breakpoint(Breakpoint::line(2));
+
+    let report = sim.tick().unwrap();
+    assert_eq!(report.end, TickEnd::Break);
+    assert_eq!(report.steps, 1);
+    let report = sim.tick().unwrap();
+    assert_eq!(report.end, TickEnd::Break);
+    // The sleep still suspends the program
+    assert!(sim.sleep_ticks() > 0);
+    assert_eq!(sim.tick().unwrap().end, TickEnd::Sleeping);
+}
@@ -0,0 +1,149 @@
+use mips_simulator::prelude::*;
+use mips_simulator::test_utils::{dev_kinds, setup};
+
+const LOGIC_MEMORY: i64 = -851746783;
+
+#[test]
+fn world_read_other_housing() {
+    let mut world = WorldDefault::new();
+    let writer = world.add_ic(setup(
+        "\
+main:
+add r0 r0 1
+s db Setting r0
+yield
+j main",
+    ));
+    let reader = world.add_ic(setup(
+        "\
+main:
+l r0 d0 Setting
+yield
+j main",
+    ));
+    let housing = world.get_ic(writer).unwrap().housing();
+    world.set_pin(reader, 0, Some(housing)).unwrap();
+
+    world.run_ticks(3).unwrap();
+    assert_eq!(world.ticks(), 3);
+    assert_eq!(world.get_device(housing).unwrap().read("Setting").unwrap(), 3.0);
+    // The writer ticks before the reader, so the reader sees the current tick's value
+    let reader_state = &world.get_sim(reader).unwrap().state;
+    assert_eq!(reader_state.get_mem(0).unwrap(), &3.0);
+}
+
+#[test]
+fn world_shared_pin_device() {
+    let kinds = dev_kinds();
+    let mut world = WorldDefault::new();
+    let memory = world.add_device(kinds["LogicMemory"].make());
+
+    let a = world.add_ic(setup("s d0 Setting 7"));
+    let b = world.add_ic(setup("l r1 d0 Setting"));
+    world.set_pin(a, 0, Some(memory)).unwrap();
+    world.set_pin(b, 0, Some(memory)).unwrap();
+
+    let reports = world.tick().unwrap();
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].end, TickEnd::Finished);
+    assert_eq!(world.get_sim(b).unwrap().state.get_mem(1).unwrap(), &7.0);
+    assert_eq!(world.get_device(memory).unwrap().read("Setting").unwrap(), 7.0);
+}
+
+#[test]
+fn world_device_by_two_paths() {
+    let kinds = dev_kinds();
+    let mut world = WorldDefault::new();
+    let memory = world.add_network_device(kinds["LogicMemory"].make());
+
+    let ic = world.add_ic(setup(
+        "\
+s d0 Setting 5
+lb r0 -851746783 Setting 1
+sb -851746783 Setting 9
+l r1 d0 Setting
+s db Setting 2
+l r2 d1 Setting
+push 3",
+    ));
+    let housing = world.get_ic(ic).unwrap().housing();
+    world.set_pin(ic, 0, Some(memory)).unwrap();
+    world.set_pin(ic, 1, Some(housing)).unwrap();
+    world.tick().unwrap();
+
+    // Writes through a pin are seen through the network within the tick, and vice versa
+    let state = &world.get_sim(ic).unwrap().state;
+    assert_eq!(state.get_mem(0).unwrap(), &5.0);
+    assert_eq!(state.get_mem(1).unwrap(), &9.0);
+    assert_eq!(state.get_mem(2).unwrap(), &2.0);
+    assert_eq!(world.get_device(memory).unwrap().read("Setting").unwrap(), 9.0);
+    // The housing is on the network too, without its stale copy overwriting the stack
+    let housing = world.get_device(housing).unwrap();
+    assert_eq!(housing.read("Setting").unwrap(), 2.0);
+    assert_eq!(housing.memory[0], 3.0);
+}
+
+#[test]
+fn world_device_by_two_paths_nan() {
+    let kinds = dev_kinds();
+    let mut world = WorldDefault::new();
+    let memory = world.add_network_device(kinds["LogicMemory"].make());
+
+    let ic = world.add_ic(setup(
+        "\
+div r9 0 0
+s d0 Setting r9
+sb -851746783 Setting 7
+l r0 d0 Setting",
+    ));
+    world.set_pin(ic, 0, Some(memory)).unwrap();
+    world.tick().unwrap();
+
+    // NaN left through the pin is not a change overwriting the write through the network
+    let state = &world.get_sim(ic).unwrap().state;
+    assert_eq!(state.get_mem(0).unwrap(), &7.0);
+    assert_eq!(world.get_device(memory).unwrap().read("Setting").unwrap(), 7.0);
+}
+
+#[test]
+fn world_shared_network() {
+    let kinds = dev_kinds();
+    let mut world = WorldDefault::new();
+    for _ in 0..2 {
+        world.add_network_device(kinds["LogicMemory"].make());
+    }
+
+    let a = world.add_ic(setup("sb -851746783 Setting 4"));
+    let b = world.add_ic(setup("lb r0 -851746783 Setting 1"));
+    world.tick().unwrap();
+
+    // Both memories written by `a` are summed by `b`
+    assert_eq!(world.get_sim(b).unwrap().state.get_mem(0).unwrap(), &8.0);
+    let setting_sum: f64 = world
+        .iter_devices()
+        .filter(|(_, dev)| dev.hash == LOGIC_MEMORY)
+        .map(|(_, dev)| dev.read("Setting").unwrap())
+        .sum();
+    assert_eq!(setting_sum, 8.0);
+    assert!(world.get_ic(a).is_ok());
+}
+
+#[test]
+fn world_add_ic_moves_devices() {
+    let kinds = dev_kinds();
+    let mut sim = setup("s d2 Setting 1");
+    sim.state
+        .set_dev(DevId::DevBuf(2), Some(kinds["LogicMemory"].make()))
+        .unwrap();
+    sim.state.dev_network_add(kinds["LogicMemory"].make());
+
+    let mut world = WorldDefault::new();
+    let ic = world.add_ic(sim);
+    // Housing, pinned device and network device
+    assert_eq!(world.iter_devices().count(), 3);
+    assert_eq!(world.iter_network().count(), 2);
+
+    let pinned = world.get_ic(ic).unwrap().pins()[2].unwrap();
+    world.tick().unwrap();
+    assert_eq!(world.get_device(pinned).unwrap().read("Setting").unwrap(), 1.0);
+}
@@ -0,0 +1,422 @@
+//! Analysis of IC10 MIPS documents.
+//!
+//! A [`Document`] answers the queries of the language server (diagnostics, hover, go-to
+//! definition, completion and rename) from its source alone, except for completion of logic
+//! types, which are taken from the [`DeviceKinds`] the server was started with (see
+//! [`LogicTypes`]).
+//!
+//! Positions are zero-based `(line, character)` pairs, with characters counted as chars (like the
+//! columns of [`Diagnostic`]s). The language server protocol counts UTF-16 code units instead,
+//! which the server converts from and to with [`Document::from_utf16`] and [`Document::to_utf16`].
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use mips_parser::ast::nodes::{Arg, Dev, Expr, Func, Mem, Val};
+use mips_parser::prelude::{Category, Checker, Cst, CstLine, CstToken, Diagnostic, Limits};
+use mips_simulator::device::{DeviceKinds, ParamKind};
+
+/// Column span of a single line.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Span {
+    pub line: usize,
+    pub range: Range<usize>,
+}
+
+/// Kind of a declared name.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum NameKind {
+    /// Memory register alias.
+    Mem,
+    /// Device register alias.
+    Dev,
+    Define,
+    Label,
+}
+
+/// Declaration of a name.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Declaration {
+    pub name: String,
+    pub kind: NameKind,
+    /// Span of the name within the declaration.
+    pub span: Span,
+    /// Code of the declaring line (e.g. `alias x r0`).
+    pub code: String,
+}
+
+/// Kind of a completion item.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CompletionKind {
+    Instruction,
+    Name(NameKind),
+    LogicType,
+}
+
+/// Completion item.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub detail: Option<String>,
+    pub documentation: Option<String>,
+}
+
+/// Text edit replacing a span.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Edit {
+    pub span: Span,
+    pub text: String,
+}
+
+/// Logic type names to complete.
+#[derive(Clone, Default, Debug)]
+pub struct LogicTypes {
+    /// Device logic types.
+    pub params: BTreeSet<String>,
+    /// Slot logic types.
+    pub slot_params: BTreeSet<String>,
+}
+
+impl LogicTypes {
+    /// Logic types of all device kinds.
+    pub fn from_kinds(kinds: &DeviceKinds) -> Self {
+        let key = |param: &ParamKind| match param {
+            ParamKind::Read(key) | ParamKind::Write(key) | ParamKind::ReadWrite(key) => key.clone(),
+        };
+        let mut logic_types = Self::default();
+        for kind in kinds.values() {
+            logic_types.params.extend(kind.params.iter().map(key));
+            for slot in kind.slots.iter() {
+                logic_types.slot_params.extend(slot.params.iter().map(key));
+            }
+        }
+        logic_types
+    }
+}
+
+/// Does a function take a slot logic type (rather than a device logic type).
+fn takes_slot_logic_type(func: &Func) -> bool {
+    matches!(
+        func,
+        Func::Lbns | Func::Lbs | Func::Ls | Func::Sbs | Func::Ss
+    )
+}
+
+/// Name used by an argument, if any.
+fn arg_name(arg: &Arg) -> Option<&str> {
+    match arg {
+        Arg::ArgMem(Mem::MemAlias(name))
+        | Arg::ArgDev(Dev::DevAlias(name))
+        | Arg::ArgVal(Val::ValMem(Mem::MemAlias(name))) => Some(name),
+        _ => None,
+    }
+}
+
+/// Is a string a valid name for an alias, define or label.
+fn is_valid_name(name: &str) -> bool {
+    let register = |prefix: char| {
+        let digits = name.trim_start_matches('r').trim_start_matches(prefix);
+        name.starts_with(prefix) && !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+    };
+    !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+        && !register('r')
+        && !register('d')
+        && !["sp", "ra", "db"].contains(&name)
+}
+
+/// Signature of a function, e.g. `add mem val val`.
+fn signature(func: &Func) -> String {
+    std::iter::once(func.to_string())
+        .chain(func.signature().iter().map(ToString::to_string))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Markdown documentation of a function.
+fn func_docs(func: &Func) -> String {
+    format!(
+        "```mips\n{}\n```\n{}\n\n_{}_",
+        signature(func),
+        func.description(),
+        func.category()
+    )
+}
+
+/// Language server document.
+#[derive(Clone, Debug)]
+pub struct Document {
+    cst: Cst,
+    declarations: Vec<Declaration>,
+}
+
+impl Document {
+    pub fn new<S: AsRef<str>>(source: &S) -> Self {
+        let cst = Cst::parse(source);
+        let mut declarations = Vec::new();
+        for line in cst.iter() {
+            let kind = match &line.expr {
+                Some(Expr(Func::Label, _)) => NameKind::Label,
+                Some(Expr(Func::Define, _)) => NameKind::Define,
+                Some(Expr(Func::Alias, args)) if matches!(args.get(1), Some(Arg::ArgDev(_))) => {
+                    NameKind::Dev
+                }
+                Some(Expr(Func::Alias, _)) => NameKind::Mem,
+                _ => continue,
+            };
+            let (token, range) = match kind {
+                NameKind::Label => {
+                    let range = char_range(line, &line.tokens[0]);
+                    (&line.tokens[0], range.start..range.end - 1)
+                }
+                _ => (&line.tokens[1], char_range(line, &line.tokens[1])),
+            };
+            declarations.push(Declaration {
+                name: token.text.trim_end_matches(':').to_string(),
+                kind,
+                span: Span {
+                    line: line.index,
+                    range,
+                },
+                code: line.code(),
+            });
+        }
+        Self { cst, declarations }
+    }
+
+    /// Source of the document.
+    pub fn source(&self) -> String {
+        self.cst.to_string()
+    }
+
+    /// UTF-16 code unit offset of a character column of a line.
+    ///
+    /// Columns past the end of the line (e.g. of a missing argument) count one unit each.
+    pub fn to_utf16(&self, line: usize, character: usize) -> usize {
+        let text = self.line_text(line);
+        let mut chars = text.chars();
+        (0..character)
+            .map(|_| chars.next().map_or(1, char::len_utf16))
+            .sum()
+    }
+
+    /// Character column of a UTF-16 code unit offset of a line.
+    pub fn from_utf16(&self, line: usize, utf16: usize) -> usize {
+        let text = self.line_text(line);
+        let mut chars = text.chars();
+        let (mut units, mut character) = (0, 0);
+        while units < utf16 {
+            units += chars.next().map_or(1, char::len_utf16);
+            character += 1;
+        }
+        character
+    }
+
+    fn line_text(&self, line: usize) -> String {
+        self.cst
+            .lines
+            .get(line)
+            .map(|line| line.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Declarations of all names.
+    pub fn declarations(&self) -> &[Declaration] {
+        &self.declarations
+    }
+
+    /// Parse errors, semantic errors and limit violations.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let source = self.source();
+        let mut diagnostics = self.cst.diagnostics();
+        diagnostics.extend(Checker::new().check_source(&source));
+        diagnostics.extend(Limits::new().check(&source));
+        diagnostics.sort_by_key(|d| d.line);
+        diagnostics
+    }
+
+    /// Token at a position, with its index within its line.
+    fn token_at(&self, line: usize, character: usize) -> Option<(&CstLine, usize, &CstToken)> {
+        let line = self.cst.lines.get(line)?;
+        line.tokens.iter().enumerate().find_map(|(i, token)| {
+            let range = char_range(line, token);
+            (range.start <= character && character <= range.end).then_some((line, i, token))
+        })
+    }
+
+    /// Declaration of a name as seen from a line
+    /// (the closest one before the line, for names declared more than once).
+    pub fn declaration(&self, name: &str, line: usize) -> Option<&Declaration> {
+        let declarations = self.declarations.iter().filter(|d| d.name == name);
+        let first = declarations.clone().next();
+        declarations
+            .rev()
+            .find(|d| d.span.line <= line)
+            .or(first)
+    }
+
+    /// Name referred to by the token at a position (declared or used), if any.
+    fn name_at(&self, line: usize, character: usize) -> Option<&Declaration> {
+        let (line, i, token) = self.token_at(line, character)?;
+        let name = match (&line.expr, i) {
+            (Some(Expr(Func::Label, _)), 0) => token.text.trim_end_matches(':'),
+            (Some(Expr(Func::Alias, _)), 1) | (Some(Expr(Func::Define, _)), 1) => &token.text,
+            (Some(Expr(_, args)), i) if i > 0 => arg_name(args.get(i - 1)?)?,
+            _ => return None,
+        };
+        self.declaration(name, line.index)
+    }
+
+    /// Markdown documentation of the token at a position.
+    pub fn hover(&self, line: usize, character: usize) -> Option<String> {
+        let (cst_line, i, token) = self.token_at(line, character)?;
+        if i == 0 {
+            if let Some(func) = Func::try_from_name(&token.text) {
+                return Some(func_docs(&func));
+            }
+        }
+        let declaration = self.name_at(line, character)?;
+        if cst_line.index == declaration.span.line {
+            return None;
+        }
+        let code = match declaration.kind {
+            NameKind::Label => format!("{} (line {})", declaration.code, declaration.span.line),
+            _ => declaration.code.clone(),
+        };
+        Some(format!("```mips\n{}\n```", code))
+    }
+
+    /// Span of the declaration of the name at a position.
+    pub fn definition(&self, line: usize, character: usize) -> Option<Span> {
+        self.name_at(line, character).map(|d| d.span.clone())
+    }
+
+    /// Completions at a position.
+    pub fn completion(
+        &self,
+        line: usize,
+        character: usize,
+        logic_types: &LogicTypes,
+    ) -> Vec<Completion> {
+        let text = match self.cst.lines.get(line) {
+            Some(line) => line.to_string(),
+            None => String::new(),
+        };
+        let before: String = text.chars().take(character).collect();
+        if before.contains('#') {
+            return Vec::new();
+        }
+        let tokens: Vec<&str> = before.split_whitespace().collect();
+        let index = if before.ends_with(|c: char| c.is_whitespace()) || tokens.is_empty() {
+            tokens.len()
+        } else {
+            tokens.len() - 1
+        };
+
+        if index == 0 {
+            return Func::ALL
+                .iter()
+                .filter(|func| **func != Func::Label)
+                .map(|func| Completion {
+                    label: func.to_string(),
+                    kind: CompletionKind::Instruction,
+                    detail: Some(signature(func)),
+                    documentation: Some(func.description().to_string()),
+                })
+                .collect();
+        }
+
+        let func = match Func::try_from_name(tokens[0]) {
+            Some(func) => func,
+            None => return Vec::new(),
+        };
+        let kind = match func.signature().get(index - 1) {
+            Some(kind) => kind.to_string(),
+            None => return Vec::new(),
+        };
+        let names = |kinds: &[NameKind]| {
+            let mut seen = BTreeSet::new();
+            self.declarations
+                .iter()
+                .filter(|d| kinds.contains(&d.kind) && seen.insert(d.name.clone()))
+                .map(|d| Completion {
+                    label: d.name.clone(),
+                    kind: CompletionKind::Name(d.kind),
+                    detail: Some(d.code.clone()),
+                    documentation: None,
+                })
+                .collect::<Vec<_>>()
+        };
+        match kind.as_str() {
+            "mem" => names(&[NameKind::Mem]),
+            "dev" => names(&[NameKind::Dev]),
+            "reg" => names(&[NameKind::Mem, NameKind::Dev]),
+            "val" => names(&[NameKind::Mem, NameKind::Define, NameKind::Label]),
+            "tkn" if func.category() == Category::Io => {
+                let params = if takes_slot_logic_type(&func) {
+                    &logic_types.slot_params
+                } else {
+                    &logic_types.params
+                };
+                params
+                    .iter()
+                    .map(|param| Completion {
+                        label: param.clone(),
+                        kind: CompletionKind::LogicType,
+                        detail: None,
+                        documentation: None,
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Edits renaming the name at a position (its declarations and uses).
+    ///
+    /// `None` if there is no name at the position, or the new name is not valid.
+    pub fn rename(&self, line: usize, character: usize, new_name: &str) -> Option<Vec<Edit>> {
+        if !is_valid_name(new_name) {
+            return None;
+        }
+        let declaration = self.name_at(line, character)?;
+        let (name, kind) = (declaration.name.clone(), declaration.kind);
+        // Aliases may be declared more than once, but a name is never shared between kinds
+        let mut edits: Vec<Edit> = self
+            .declarations
+            .iter()
+            .filter(|d| d.name == name && d.kind == kind)
+            .map(|d| Edit {
+                span: d.span.clone(),
+                text: new_name.to_string(),
+            })
+            .collect();
+        for line in self.cst.iter() {
+            let args = match &line.expr {
+                Some(Expr(Func::Label, _)) | Some(Expr(Func::Alias, _)) | None => continue,
+                Some(Expr(Func::Define, _)) => continue,
+                Some(Expr(_, args)) => args,
+            };
+            for (arg, token) in args.iter().zip(line.tokens.iter().skip(1)) {
+                if arg_name(arg) == Some(&name) {
+                    edits.push(Edit {
+                        span: Span {
+                            line: line.index,
+                            range: char_range(line, token),
+                        },
+                        text: new_name.to_string(),
+                    });
+                }
+            }
+        }
+        edits.sort_by_key(|edit| (edit.span.line, edit.span.range.start));
+        Some(edits)
+    }
+}
+
+/// Character range of a token within its line.
+fn char_range(line: &CstLine, token: &CstToken) -> Range<usize> {
+    let text = line.to_string();
+    let start = text[..token.span.start - line.span.start].chars().count();
+    start..start + token.text.chars().count()
+}
@@ -0,0 +1,24 @@
+//! Language server for [Stationeers][stationeers] IC10 MIPS, built on the [MIPS parser].
+//!
+//! The server speaks the [language server protocol][lsp] over stdin/stdout JSON-RPC, and offers:
+//!
+//! - diagnostics (parse errors, semantic errors and program limits),
+//! - hover documentation of instructions (from the [`Func`] metadata) and names,
+//! - go-to definition of labels, aliases and defines,
+//! - completion of instructions, names and device logic types, and
+//! - rename of labels, aliases and defines.
+//!
+//! [stationeers]: https://store.steampowered.com/app/544550/Stationeers/
+//! [MIPS parser]: ../mips_parser/index.html
+//! [lsp]: https://microsoft.github.io/language-server-protocol/
+//! [`Func`]: mips_parser::prelude::Func
+pub mod document;
+pub mod server;
+
+/// All-in-one module.
+pub mod prelude {
+    pub use crate::document::{
+        Completion, CompletionKind, Declaration, Document, Edit, LogicTypes, NameKind, Span,
+    };
+    pub use crate::server::{capabilities, LspError, Server};
+}
@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Error as IOError;
+use std::path::Path;
+
+use clap::{load_yaml, App};
+use lsp_server::Connection;
+use ron::{de::from_reader, Error as RonError};
+
+use mips_lsp::prelude::{capabilities, LogicTypes, LspError, Server};
+use mips_simulator::prelude::DeviceKinds;
+use util::impl_from_error;
+
+#[derive(Debug)]
+enum CliError {
+    IOError(IOError),
+    RonError(RonError),
+    LspError(LspError),
+}
+
+impl_from_error!(CliError, IOError, RonError, LspError);
+
+fn main() -> Result<(), CliError> {
+    let yaml = load_yaml!("./clap.yaml");
+    let matches = App::from_yaml(yaml).get_matches();
+
+    // Stdout is the protocol channel, so warnings go to stderr
+    let kinds_path = Path::new(matches.value_of("kind-file").unwrap());
+    let kinds: DeviceKinds = if kinds_path.exists() {
+        from_reader(File::open(kinds_path)?)?
+    } else {
+        eprintln!("Warning: No device kinds file loaded, logic types will not be completed");
+        HashMap::new()
+    };
+
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = serde_json::to_value(capabilities()).map_err(LspError::from)?;
+    connection
+        .initialize(capabilities)
+        .map_err(LspError::from)?;
+    Server::new(&connection, LogicTypes::from_kinds(&kinds)).run()?;
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
@@ -0,0 +1,285 @@
+//! Language server protocol message loop.
+use std::collections::HashMap;
+
+use lsp_server::{Connection, Message, Notification, ProtocolError, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as NotificationTrait, PublishDiagnostics,
+};
+use lsp_types::request::Request as RequestTrait;
+use lsp_types::request::{Completion as CompletionRequest, GotoDefinition, HoverRequest, Rename};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    Diagnostic as LspDiagnostic, DiagnosticSeverity, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, Documentation, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability, Location,
+    MarkupContent, MarkupKind, OneOf, Position, PublishDiagnosticsParams, Range, RenameParams,
+    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url,
+    WorkspaceEdit,
+};
+use serde_json::Error as JsonError;
+
+use mips_parser::prelude::{Diagnostic, Severity};
+use util::impl_from_error;
+
+use crate::document::{Completion, CompletionKind, Document, LogicTypes, NameKind, Span};
+
+type SendError = crossbeam_channel::SendError<Message>;
+
+/// Language server error type.
+#[derive(Debug)]
+pub enum LspError {
+    ProtocolError(ProtocolError),
+    JsonError(JsonError),
+    SendError(SendError),
+}
+
+impl_from_error!(LspError, ProtocolError, JsonError, SendError);
+
+/// Capabilities of the server.
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![" ".to_string()]),
+            ..CompletionOptions::default()
+        }),
+        rename_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// Protocol range of a span of a document, in UTF-16 code units.
+fn to_range(document: &Document, span: &Span) -> Range {
+    let position = |character| {
+        let character = document.to_utf16(span.line, character);
+        Position::new(span.line as u32, character as u32)
+    };
+    Range::new(position(span.range.start), position(span.range.end))
+}
+
+fn to_diagnostic(document: &Document, diagnostic: &Diagnostic) -> LspDiagnostic {
+    let span = Span {
+        line: diagnostic.line.unwrap_or(0),
+        range: diagnostic.span.clone(),
+    };
+    let severity = match diagnostic.severity {
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+    };
+    let message = match &diagnostic.expected {
+        Some(expected) => format!("{} ({})", diagnostic.message, expected),
+        None => diagnostic.message.clone(),
+    };
+    LspDiagnostic {
+        range: to_range(document, &span),
+        severity: Some(severity),
+        source: Some("mips".to_string()),
+        message,
+        ..LspDiagnostic::default()
+    }
+}
+
+fn to_completion_item(completion: Completion) -> CompletionItem {
+    let kind = match completion.kind {
+        CompletionKind::Instruction => CompletionItemKind::KEYWORD,
+        CompletionKind::Name(NameKind::Mem) | CompletionKind::Name(NameKind::Dev) => {
+            CompletionItemKind::VARIABLE
+        }
+        CompletionKind::Name(NameKind::Define) => CompletionItemKind::CONSTANT,
+        CompletionKind::Name(NameKind::Label) => CompletionItemKind::REFERENCE,
+        CompletionKind::LogicType => CompletionItemKind::PROPERTY,
+    };
+    CompletionItem {
+        label: completion.label,
+        kind: Some(kind),
+        detail: completion.detail,
+        documentation: completion.documentation.map(Documentation::String),
+        ..CompletionItem::default()
+    }
+}
+
+/// Language server, holding the open documents.
+pub struct Server<'a> {
+    connection: &'a Connection,
+    logic_types: LogicTypes,
+    documents: HashMap<Url, Document>,
+}
+
+impl<'a> Server<'a> {
+    /// New server over an initialized connection.
+    pub fn new(connection: &'a Connection, logic_types: LogicTypes) -> Self {
+        Self {
+            connection,
+            logic_types,
+            documents: HashMap::new(),
+        }
+    }
+
+    /// Handle messages until shutdown.
+    pub fn run(&mut self) -> Result<(), LspError> {
+        for message in &self.connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let id = request.id.clone();
+                    let response = match self.handle_request(request) {
+                        Err(LspError::JsonError(e)) => Response::new_err(
+                            id,
+                            lsp_server::ErrorCode::InvalidParams as i32,
+                            format!("invalid params: {}", e),
+                        ),
+                        response => response?,
+                    };
+                    self.connection.sender.send(response.into())?;
+                }
+                // Malformed notifications are dropped, as there is no response to report them in
+                Message::Notification(notification) => {
+                    match self.handle_notification(notification) {
+                        Err(LspError::JsonError(e)) => eprintln!("invalid notification: {}", e),
+                        result => result?,
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<Response, LspError> {
+        let id = request.id.clone();
+        let response = match request.method.as_str() {
+            HoverRequest::METHOD => {
+                let params: HoverParams = serde_json::from_value(request.params)?;
+                let doc = &params.text_document_position_params;
+                let hover = self.position(&doc.text_document.uri, doc.position, |d, l, c| {
+                    d.hover(l, c)
+                });
+                let hover = hover.map(|value| Hover {
+                    contents: HoverContents::Markup(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value,
+                    }),
+                    range: None,
+                });
+                Response::new_ok(id, hover)
+            }
+            GotoDefinition::METHOD => {
+                let params: GotoDefinitionParams = serde_json::from_value(request.params)?;
+                let doc = params.text_document_position_params;
+                let uri = doc.text_document.uri;
+                let location = self.position(&uri, doc.position, |d, l, c| {
+                    let range = to_range(d, &d.definition(l, c)?);
+                    Some(GotoDefinitionResponse::Scalar(Location::new(uri.clone(), range)))
+                });
+                Response::new_ok(id, location)
+            }
+            CompletionRequest::METHOD => {
+                let params: CompletionParams = serde_json::from_value(request.params)?;
+                let doc = &params.text_document_position;
+                let logic_types = &self.logic_types;
+                let completions = self
+                    .position(&doc.text_document.uri, doc.position, |d, l, c| {
+                        Some(d.completion(l, c, logic_types))
+                    })
+                    .unwrap_or_default();
+                let items = completions.into_iter().map(to_completion_item).collect();
+                Response::new_ok(id, CompletionResponse::Array(items))
+            }
+            Rename::METHOD => {
+                let params: RenameParams = serde_json::from_value(request.params)?;
+                let doc = params.text_document_position;
+                let uri = doc.text_document.uri;
+                let new_name = params.new_name;
+                let edits = self.position(&uri, doc.position, |d, l, c| {
+                    let edits = d.rename(l, c, &new_name)?;
+                    let edits = edits
+                        .iter()
+                        .map(|edit| TextEdit::new(to_range(d, &edit.span), edit.text.clone()))
+                        .collect::<Vec<_>>();
+                    Some(edits)
+                });
+                match edits {
+                    Some(edits) => {
+                        let mut changes = HashMap::new();
+                        changes.insert(uri, edits);
+                        Response::new_ok(id, WorkspaceEdit::new(changes))
+                    }
+                    None => Response::new_err(
+                        id,
+                        lsp_server::ErrorCode::InvalidParams as i32,
+                        format!("cannot rename to `{}`", new_name),
+                    ),
+                }
+            }
+            method => Response::new_err(
+                id,
+                lsp_server::ErrorCode::MethodNotFound as i32,
+                format!("unsupported method `{}`", method),
+            ),
+        };
+        Ok(response)
+    }
+
+    /// Query the document at a protocol position, converted to a line and a character column.
+    fn position<T, F>(&self, uri: &Url, position: Position, f: F) -> Option<T>
+    where
+        F: FnOnce(&Document, usize, usize) -> Option<T>,
+    {
+        let document = self.documents.get(uri)?;
+        let line = position.line as usize;
+        let character = document.from_utf16(line, position.character as usize);
+        f(document, line, character)
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<(), LspError> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let doc = params.text_document;
+                self.open(doc.uri, &doc.text)?;
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                // Documents are synchronized in full, so the last change is the whole document
+                if let Some(change) = params.content_changes.last() {
+                    self.open(params.text_document.uri, &change.text)?;
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                self.publish(uri, Vec::new())?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Open (or update) a document, publishing its diagnostics.
+    fn open(&mut self, uri: Url, source: &str) -> Result<(), LspError> {
+        let document = Document::new(&source);
+        let diagnostics = document
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| to_diagnostic(&document, diagnostic))
+            .collect();
+        self.documents.insert(uri.clone(), document);
+        self.publish(uri, diagnostics)
+    }
+
+    fn publish(&self, uri: Url, diagnostics: Vec<LspDiagnostic>) -> Result<(), LspError> {
+        let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+        self.connection.sender.send(notification.into())?;
+        Ok(())
+    }
+}
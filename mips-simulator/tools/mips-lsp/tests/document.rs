@@ -0,0 +1,185 @@
+use mips_lsp::prelude::*;
+use mips_simulator::device::ParamKind;
+use mips_simulator::prelude::{DeviceKind, DeviceKinds, SlotKind};
+
+const SOURCE: &str = "\
+alias sensor d0 # the sensor
+alias x r0
+define Offset 90
+start:
+yield
+l x sensor Horizontal
+sub x Offset x
+jal start
+";
+
+fn span(line: usize, range: std::ops::Range<usize>) -> Span {
+    Span { line, range }
+}
+
+fn logic_types() -> LogicTypes {
+    let mut kinds = DeviceKinds::new();
+    kinds.insert(
+        "Sensor".into(),
+        DeviceKind {
+            name: "Sensor".into(),
+            hash: 1,
+            params: vec![
+                ParamKind::Read("Horizontal".into()),
+                ParamKind::ReadWrite("On".into()),
+            ],
+            slots: vec![SlotKind::new("Slot")],
+            reagents: Default::default(),
+            memory: 0,
+        },
+    );
+    LogicTypes::from_kinds(&kinds)
+}
+
+fn labels(completions: &[Completion]) -> Vec<&str> {
+    completions.iter().map(|c| c.label.as_str()).collect()
+}
+
+#[test]
+fn document_declarations() {
+    let document = Document::new(&SOURCE);
+    let names: Vec<(&str, NameKind)> = document
+        .declarations()
+        .iter()
+        .map(|d| (d.name.as_str(), d.kind))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("sensor", NameKind::Dev),
+            ("x", NameKind::Mem),
+            ("Offset", NameKind::Define),
+            ("start", NameKind::Label),
+        ]
+    );
+    assert_eq!(document.declarations()[3].span, span(3, 0..5));
+}
+
+#[test]
+fn document_diagnostics() {
+    assert!(Document::new(&SOURCE).diagnostics().is_empty());
+
+    let document = Document::new(&"add r0 d0 1\nj nowhere\nmove r0 1 1\n");
+    let diagnostics = document.diagnostics();
+    let lines: Vec<Option<usize>> = diagnostics.iter().map(|d| d.line).collect();
+    assert_eq!(lines, vec![Some(0), Some(1), Some(2)]);
+    assert_eq!(diagnostics[0].span, 7..9);
+    assert!(diagnostics[1].message.contains("`nowhere` is not defined"));
+}
+
+#[test]
+fn document_utf16() {
+    // `😀` is two UTF-16 code units, and `é` one
+    let document = Document::new(&"add x HASH(\"😀é\") x\n");
+    assert_eq!(document.to_utf16(0, 12), 12);
+    assert_eq!(document.to_utf16(0, 13), 14);
+    assert_eq!(document.to_utf16(0, 17), 18);
+    assert_eq!(document.from_utf16(0, 18), 17);
+    assert_eq!(document.from_utf16(0, 14), 13);
+    // Past the end of the line
+    assert_eq!(document.to_utf16(0, 20), 21);
+    assert_eq!(document.from_utf16(0, 21), 20);
+    assert_eq!(document.to_utf16(1, 2), 2);
+}
+
+#[test]
+fn document_hover() {
+    let document = Document::new(&SOURCE);
+    let hover = document.hover(6, 1).unwrap();
+    assert!(hover.contains("sub mem val val"));
+    assert!(hover.contains("Register a = b - c"));
+    assert!(hover.contains("Mathematical Operations"));
+
+    assert_eq!(
+        document.hover(6, 8).unwrap(),
+        "```mips\ndefine Offset 90\n```"
+    );
+    assert_eq!(
+        document.hover(7, 5).unwrap(),
+        "```mips\nstart: (line 3)\n```"
+    );
+    // Not on declarations themselves, whitespace or logic types
+    assert_eq!(document.hover(1, 6), None);
+    assert_eq!(document.hover(5, 15), None);
+    assert_eq!(document.hover(5, 20), None);
+}
+
+#[test]
+fn document_definition() {
+    let document = Document::new(&SOURCE);
+    assert_eq!(document.definition(5, 4), Some(span(0, 6..12)));
+    assert_eq!(document.definition(6, 4), Some(span(1, 6..7)));
+    assert_eq!(document.definition(6, 6), Some(span(2, 7..13)));
+    assert_eq!(document.definition(7, 4), Some(span(3, 0..5)));
+    assert_eq!(document.definition(4, 0), None);
+
+    // The closest declaration before a use
+    let document = Document::new(&"alias x r0\nmove x 1\nalias x r1\nmove x 2\n");
+    assert_eq!(document.definition(1, 5), Some(span(0, 6..7)));
+    assert_eq!(document.definition(3, 5), Some(span(2, 6..7)));
+}
+
+#[test]
+fn document_completion() {
+    let document = Document::new(&format!("{}l x sensor \nsb 1 \nls x sensor 0 \n", SOURCE));
+    let logic_types = logic_types();
+
+    let instructions = document.completion(8, 0, &logic_types);
+    assert!(labels(&instructions).contains(&"add"));
+    assert!(!labels(&instructions).contains(&"label"));
+    let add = instructions.iter().find(|c| c.label == "add").unwrap();
+    assert_eq!(add.detail.as_deref(), Some("add mem val val"));
+
+    // Partial instruction
+    assert!(!document.completion(8, 1, &logic_types).is_empty());
+
+    assert_eq!(labels(&document.completion(8, 2, &logic_types)), vec!["x"]);
+    assert_eq!(
+        labels(&document.completion(8, 4, &logic_types)),
+        vec!["sensor"]
+    );
+    assert_eq!(
+        labels(&document.completion(8, 11, &logic_types)),
+        vec!["Horizontal", "On"]
+    );
+    assert_eq!(
+        labels(&document.completion(9, 5, &logic_types)),
+        vec!["Horizontal", "On"]
+    );
+    assert!(labels(&document.completion(10, 14, &logic_types)).contains(&"OccupantHash"));
+    assert_eq!(
+        labels(&document.completion(6, 6, &logic_types)),
+        vec!["x", "Offset", "start"]
+    );
+    // Nothing in comments
+    assert!(document.completion(0, 28, &logic_types).is_empty());
+}
+
+#[test]
+fn document_rename() {
+    let document = Document::new(&SOURCE);
+    let edits = document.rename(6, 4, "y").unwrap();
+    let spans: Vec<Span> = edits.iter().map(|e| e.span.clone()).collect();
+    assert_eq!(
+        spans,
+        vec![span(1, 6..7), span(5, 2..3), span(6, 4..5), span(6, 13..14)]
+    );
+    assert!(edits.iter().all(|e| e.text == "y"));
+
+    let edits = document.rename(3, 2, "loop").unwrap();
+    let spans: Vec<Span> = edits.iter().map(|e| e.span.clone()).collect();
+    assert_eq!(spans, vec![span(3, 0..5), span(7, 4..9)]);
+
+    // Invalid names
+    assert_eq!(document.rename(6, 4, "r1"), None);
+    assert_eq!(document.rename(6, 4, "d0"), None);
+    assert_eq!(document.rename(6, 4, "a b"), None);
+    assert_eq!(document.rename(6, 4, "ra"), None);
+    // Nothing to rename
+    assert_eq!(document.rename(4, 0, "y"), None);
+}
@@ -0,0 +1,219 @@
+use std::thread;
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use serde_json::{json, Value};
+
+use mips_lsp::prelude::*;
+
+const URI: &str = "file:///test.mips";
+
+/// Client side of a running server.
+struct Client {
+    connection: Connection,
+    server: thread::JoinHandle<()>,
+    id: i32,
+}
+
+impl Client {
+    fn new() -> Self {
+        let (server, connection) = Connection::memory();
+        let server = thread::spawn(move || {
+            Server::new(&server, LogicTypes::default()).run().unwrap();
+        });
+        Self {
+            connection,
+            server,
+            id: 0,
+        }
+    }
+
+    fn notify(&self, method: &str, params: Value) {
+        let notification = Notification::new(method.to_string(), params);
+        self.connection.sender.send(notification.into()).unwrap();
+    }
+
+    fn receive(&self) -> Message {
+        self.connection.receiver.recv().unwrap()
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Response {
+        self.id += 1;
+        let request = Request::new(RequestId::from(self.id), method.to_string(), params);
+        self.connection.sender.send(request.into()).unwrap();
+        match self.receive() {
+            Message::Response(response) => response,
+            message => panic!("expected a response, found {:?}", message),
+        }
+    }
+
+    fn diagnostics(&self) -> Value {
+        match self.receive() {
+            Message::Notification(n) if n.method == "textDocument/publishDiagnostics" => {
+                n.params["diagnostics"].clone()
+            }
+            message => panic!("expected diagnostics, found {:?}", message),
+        }
+    }
+
+    fn shutdown(mut self) {
+        self.request("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        self.server.join().unwrap();
+    }
+}
+
+fn position(line: u32, character: u32) -> Value {
+    json!({
+        "textDocument": { "uri": URI },
+        "position": { "line": line, "character": character },
+    })
+}
+
+#[test]
+fn server_session() {
+    let mut client = Client::new();
+
+    client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": {
+                "uri": URI,
+                "languageId": "mips",
+                "version": 1,
+                "text": "alias x r0\nadd x x d0\n",
+            }
+        }),
+    );
+    let diagnostics = client.diagnostics();
+    assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+    assert_eq!(
+        diagnostics[0]["range"]["start"],
+        json!({ "line": 1, "character": 8 })
+    );
+    assert_eq!(diagnostics[0]["severity"], json!(1));
+
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 2 },
+            "contentChanges": [{ "text": "alias x r0\nadd x x 1\nj x\n" }],
+        }),
+    );
+    assert_eq!(client.diagnostics(), json!([]));
+
+    let hover = client.request("textDocument/hover", position(1, 1));
+    let value = hover.result.unwrap()["contents"]["value"].clone();
+    assert!(value.as_str().unwrap().contains("Register a = b + c"));
+
+    let definition = client.request("textDocument/definition", position(2, 2));
+    assert_eq!(
+        definition.result.unwrap()["range"],
+        json!({
+            "start": { "line": 0, "character": 6 },
+            "end": { "line": 0, "character": 7 },
+        })
+    );
+
+    let completion = client.request("textDocument/completion", position(1, 4));
+    assert_eq!(completion.result.unwrap()[0]["label"], json!("x"));
+
+    let mut params = position(1, 4);
+    params["newName"] = json!("y");
+    let rename = client.request("textDocument/rename", params);
+    let edits = rename.result.unwrap()["changes"][URI].clone();
+    assert_eq!(edits.as_array().unwrap().len(), 4);
+
+    let mut params = position(1, 4);
+    params["newName"] = json!("r1");
+    assert!(client
+        .request("textDocument/rename", params)
+        .error
+        .is_some());
+
+    assert!(client
+        .request("textDocument/formatting", json!({}))
+        .error
+        .is_some());
+
+    client.notify(
+        "textDocument/didClose",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    assert_eq!(client.diagnostics(), json!([]));
+
+    client.shutdown();
+}
+
+#[test]
+fn server_utf16_positions() {
+    let mut client = Client::new();
+
+    // `😀` is one character, but two UTF-16 code units
+    client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": {
+                "uri": URI,
+                "languageId": "mips",
+                "version": 1,
+                "text": "alias x r0\nadd x HASH(\"😀\") d0\nadd x HASH(\"😀\") x\n",
+            }
+        }),
+    );
+    let diagnostics = client.diagnostics();
+    assert_eq!(
+        diagnostics[0]["range"],
+        json!({
+            "start": { "line": 1, "character": 17 },
+            "end": { "line": 1, "character": 19 },
+        })
+    );
+
+    let definition = client.request("textDocument/definition", position(2, 18));
+    assert_eq!(
+        definition.result.unwrap()["range"]["start"],
+        json!({ "line": 0, "character": 6 })
+    );
+
+    client.shutdown();
+}
+
+#[test]
+fn server_capabilities() {
+    let capabilities = serde_json::to_value(capabilities()).unwrap();
+    assert_eq!(capabilities["hoverProvider"], json!(true));
+    assert_eq!(capabilities["definitionProvider"], json!(true));
+    assert_eq!(capabilities["renameProvider"], json!(true));
+    assert_eq!(capabilities["textDocumentSync"], json!(1));
+}
+
+#[test]
+fn server_invalid_params() {
+    let mut client = Client::new();
+
+    client.notify("textDocument/didOpen", json!({ "textDocument": 1 }));
+    let response = client.request("textDocument/hover", json!({ "position": "start" }));
+    assert_eq!(
+        response.error.unwrap().code,
+        lsp_server::ErrorCode::InvalidParams as i32
+    );
+
+    // The server keeps serving
+    client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": {
+                "uri": URI,
+                "languageId": "mips",
+                "version": 1,
+                "text": "add r0 r0 1\n",
+            }
+        }),
+    );
+    assert_eq!(client.diagnostics(), json!([]));
+    let hover = client.request("textDocument/hover", position(0, 1));
+    let value = hover.result.unwrap()["contents"]["value"].clone();
+    assert!(value.as_str().unwrap().contains("Register a = b + c"));
+
+    client.shutdown();
+}
@@ -13,9 +13,16 @@ use clap::{load_yaml, App, ArgMatches};
 use regex::Regex;
 use ron::{de::from_reader, Error as RonError};
 use rustyline::error::ReadlineError;
+use serde_json::Error as JsonError;
 
-use mips_parser::prelude::{Expr, MipsParserError, Node, Program};
-use mips_simulator::prelude::{DevId, DeviceKind, ICSimulator, ICState, ICStateError, Line};
+use mips_parser::diagnostic::diagnose_line;
+use mips_parser::prelude::{
+    Checker, Expr, Limits, MipsParserError, Node, Program, Severity,
+};
+use mips_simulator::prelude::{
+    DevId, DeviceKind, ICSimulator, ICState, ICStateError, Line, Reagent, Scenario,
+    ScenarioError,
+};
 use util::impl_from_error;
 
 type Editor = rustyline::Editor<()>;
@@ -27,7 +34,9 @@ enum CliError {
     MipsParserError(MipsParserError),
     IOError(IOError),
     RonError(RonError),
+    JsonError(JsonError),
     ICStateError(ICStateError),
+    ScenarioError(ScenarioError),
 }
 
 impl_from_error!(
@@ -36,7 +45,9 @@ impl_from_error!(
     MipsParserError,
     IOError,
     RonError,
-    ICStateError
+    JsonError,
+    ICStateError,
+    ScenarioError
 );
 
 const WARN_DEVICE_KINDS: &'static str =
@@ -52,6 +63,11 @@ const HELP_PROGRAM: &'static str = "    \"EOL\"             - finish
     \"program\"         - display the program
     \"status\"          - display state details
     \"<n>\"             - step <n> times
+    \"tick [<n>]\"      - run one (or <n>) game ticks
+    \"back [<n>]\"      - step back once (or <n> times)
+    \"goto <n>\"        - go to the state after step <n>
+    \"save <file>\"     - save the simulation (as JSON if <file> ends in .json, else RON)
+    \"load <file>\"     - load a saved simulation
     \"\"                - step once";
 
 const HELP_DEVICE: &'static str = "    \"EOL\"             - finish
@@ -59,6 +75,8 @@ const HELP_DEVICE: &'static str = "    \"EOL\"             - finish
     \"add <id> <kind>\" - assign device of <kind> to register <id>
     \"add n    <kind>\" - assign device of <kind> to the network
     \"rm  <id>\"        - remove device at register <id>
+    \"reagent <id> <hash> <amount>\"
+                      - set reagent contents of device at register <id> (or db)
     \"list\"            - list device kinds
     \"status\"          - display currently set devices";
 
@@ -79,6 +97,12 @@ fn main() -> Result<(), CliError> {
         HashMap::new()
     };
 
+    let seed = get_seed(&matches);
+
+    if let Some(path) = matches.value_of("scenario") {
+        return run_scenario(&matches, path, &kinds, seed, &mut rl);
+    }
+
     // Get program
     let program = get_program(&matches, &mut rl)?;
 
@@ -87,7 +111,11 @@ fn main() -> Result<(), CliError> {
     // Configure devices
     configure_devices(&mut state, &kinds, matches.value_of("device-conf"), &mut rl)?;
 
-    let sim = ICSimulator::new(state, program);
+    let mut sim = ICSimulator::new(state, program).with_history();
+    if let Some(seed) = seed {
+        sim = sim.with_seed(seed);
+    }
+    println!("Random seed: {}", sim.state.rng().seed());
 
     // Run the simulation
     run_program(sim, &mut rl)?;
@@ -96,10 +124,69 @@ fn main() -> Result<(), CliError> {
     Ok(())
 }
 
+// Run a scenario against the program file (or the scenario program), exiting on failure.
+fn run_scenario(
+    matches: &ArgMatches,
+    path: &str,
+    kinds: &DeviceKinds,
+    seed: Option<u64>,
+    rl: &mut Editor,
+) -> Result<(), CliError> {
+    let mut scenario = Scenario::load(path)?;
+    if seed.is_some() {
+        scenario.seed = seed;
+    }
+    let report = if matches.is_present("file") || scenario.program.is_none() {
+        let program = get_program(matches, rl)?;
+        let mut sim = ICSimulator::new(ICState::default(), program);
+        scenario.setup(&mut sim, kinds)?;
+        scenario.run_simulator(&mut sim)?
+    } else {
+        scenario.run(kinds)?
+    };
+    println!("{}", report);
+    if !report.passed() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+// Get the random seed option, exiting if invalid.
+fn get_seed(matches: &ArgMatches) -> Option<u64> {
+    matches.value_of("seed").map(|seed| match seed.parse::<u64>() {
+        Ok(seed) => seed,
+        Err(_) => {
+            eprintln!("Error: invalid seed `{}`", seed);
+            std::process::exit(1);
+        }
+    })
+}
+
 // Get program, from file or from standard input.
 fn get_program(matches: &ArgMatches, rl: &mut Editor) -> Result<Program, CliError> {
     let program = if let Some(path) = matches.value_of("file") {
-        Program::try_from_file(path)?
+        let source = std::fs::read_to_string(path)?;
+        match Program::try_from_str(&source) {
+            Ok(program) => {
+                // Report semantic problems before simulating
+                for diagnostic in Checker::new().check_source(&source).iter() {
+                    eprintln!("{}\n", diagnostic.render(path, &source));
+                }
+                check_limits(matches.value_of("limits").unwrap(), path, &source);
+                program
+            }
+            Err(e) => {
+                // Report every invalid line
+                let mut diagnostics = Program::parse_lossy(&source).diagnostics;
+                if diagnostics.is_empty() {
+                    diagnostics.push(e.diagnostic(&source));
+                }
+                for diagnostic in diagnostics.iter() {
+                    eprintln!("{}\n", diagnostic.render(path, &source));
+                }
+                std::process::exit(1);
+            }
+        }
     } else {
         // Try to build the program
         println!("Build program from stdin...");
@@ -113,7 +200,11 @@ fn get_program(matches: &ArgMatches, rl: &mut Editor) -> Result<Program, CliErro
                             rl.add_history_entry(source);
                             program.push(expr)
                         }
-                        _ => println!("Error: Parse error"),
+                        Err(_) => {
+                            if let Some(diagnostic) = diagnose_line(0, &source) {
+                                println!("{}", diagnostic.render("<stdin>", &source));
+                            }
+                        }
                     };
                 }
                 Err(ReadlineError::Eof) => break,
@@ -125,6 +216,23 @@ fn get_program(matches: &ArgMatches, rl: &mut Editor) -> Result<Program, CliErro
     Ok(program)
 }
 
+// Check source against the IC10 limits, warning on or failing for (or ignoring) violations.
+fn check_limits(mode: &str, path: &str, source: &str) {
+    if mode == "off" {
+        return;
+    }
+    let mut diagnostics = Limits::new().check(&source);
+    for diagnostic in diagnostics.iter_mut() {
+        if mode == "warn" {
+            diagnostic.severity = Severity::Warning;
+        }
+        eprintln!("{}\n", diagnostic.render(path, source));
+    }
+    if mode == "fail" && !diagnostics.is_empty() {
+        std::process::exit(1);
+    }
+}
+
 // Configure state devices, either via a configuration file or through standard input.
 fn configure_devices<const MS: usize, const DS: usize, const SS: usize>(
     state: &mut ICState<MS, DS, SS>,
@@ -133,6 +241,7 @@ fn configure_devices<const MS: usize, const DS: usize, const SS: usize>(
     rl: &mut Editor,
 ) -> Result<(), CliError> {
     if let Some(conf) = conf {
+        let r_pattern = Regex::new(r"\s*r\s+(\d+|db)\s+(-?\d+)\s+(\S+)").unwrap();
         let n_pattern = Regex::new(r"\s*n\s*(\d+)\s*(\w+)").unwrap();
         let i_pattern = Regex::new(r"\s*(\d+)\s*(\w+)").unwrap();
 
@@ -140,7 +249,9 @@ fn configure_devices<const MS: usize, const DS: usize, const SS: usize>(
         let reader = BufReader::new(file);
         for (i, line) in reader.lines().enumerate() {
             let line = line?;
-            if let Some(groups) = n_pattern.captures(&line) {
+            if let Some(groups) = r_pattern.captures(&line) {
+                set_reagent(state, &groups);
+            } else if let Some(groups) = n_pattern.captures(&line) {
                 let n = groups.get(1).unwrap().as_str().parse::<usize>().unwrap();
                 let key = groups.get(2).unwrap().as_str();
                 if let Some(kind) = kinds.get(key) {
@@ -169,6 +280,7 @@ fn configure_devices<const MS: usize, const DS: usize, const SS: usize>(
         let add_i_pattern = Regex::new(r"add\s+(\d+)\s+(\w+)").unwrap();
         let add_n_pattern = Regex::new(r"add\s+n\s+(\w+)").unwrap();
         let rm_pattern = Regex::new(r"rm\s+(\d+)").unwrap();
+        let reagent_pattern = Regex::new(r"reagent\s+(\d+|db)\s+(-?\d+)\s+(\S+)").unwrap();
         loop {
             match rl.readline(">> ") {
                 Ok(line) => {
@@ -206,6 +318,10 @@ fn configure_devices<const MS: usize, const DS: usize, const SS: usize>(
                             println!("Error: device kind {} not-found, skipping", key);
                         }
                         rl.add_history_entry(line);
+                    } else if let Some(groups) = reagent_pattern.captures(line) {
+                        // Set device reagent contents
+                        set_reagent(state, &groups);
+                        rl.add_history_entry(line);
                     } else if let Some(groups) = rm_pattern.captures(line) {
                         // Remove device
                         let i = groups.get(1).unwrap().as_str().parse::<usize>().unwrap();
@@ -223,12 +339,60 @@ fn configure_devices<const MS: usize, const DS: usize, const SS: usize>(
     Ok(())
 }
 
+// Set the reagent contents of a device from captured (device, reagent hash, amount) groups,
+// reporting invalid values and missing devices without failing.
+fn set_reagent<const MS: usize, const DS: usize, const SS: usize>(
+    state: &mut ICState<MS, DS, SS>,
+    groups: &regex::Captures,
+) {
+    let id = groups.get(1).unwrap().as_str();
+    let dev_id = if id == "db" {
+        DevId::DevSelf
+    } else {
+        match id.parse::<usize>() {
+            Ok(i) => DevId::DevBuf(i),
+            Err(_) => {
+                println!("Error: invalid device index, skipping");
+                return;
+            }
+        }
+    };
+    let hash = match groups.get(2).unwrap().as_str().parse::<i64>() {
+        Ok(hash) => hash,
+        Err(_) => {
+            println!("Error: invalid reagent hash, skipping");
+            return;
+        }
+    };
+    let amount = match groups.get(3).unwrap().as_str().parse::<f64>() {
+        Ok(amount) => amount,
+        Err(_) => {
+            println!("Error: invalid reagent amount, skipping");
+            return;
+        }
+    };
+    match state.get_mut_dev(dev_id) {
+        Ok(dev) => {
+            dev.reagents
+                .entry(hash)
+                .or_insert_with(Reagent::default)
+                .contents = amount;
+        }
+        Err(e) => println!("Error: {:?}, skipping", e),
+    }
+}
+
 fn run_program<const MS: usize, const DS: usize, const SS: usize>(
     sim_init: ICSimulator<MS, DS, SS>,
     rl: &mut Editor,
 ) -> Result<(), ReadlineError> {
     let mut sim = sim_init.clone();
     let mut i = 1_usize;
+    let tick_pattern = Regex::new(r"^tick(?:\s+(\d+))?$").unwrap();
+    let back_pattern = Regex::new(r"^back(?:\s+(\d+))?$").unwrap();
+    let goto_pattern = Regex::new(r"^goto\s+(\d+)$").unwrap();
+    let save_pattern = Regex::new(r"^save\s+(.+)$").unwrap();
+    let load_pattern = Regex::new(r"^load\s+(.+)$").unwrap();
 
     println!(
         "Running simulation:\n{}\n0: {}",
@@ -263,6 +427,54 @@ fn run_program<const MS: usize, const DS: usize, const SS: usize>(
                         step(&mut i, &mut sim);
                     }
                     rl.add_history_entry(line);
+                } else if let Some(groups) = tick_pattern.captures(line) {
+                    match groups.get(1).map_or(Ok(1), |n| n.as_str().parse::<usize>()) {
+                        Ok(n) => tick(n, &mut i, &mut sim),
+                        Err(e) => println!("Error: invalid number of ticks ({})", e),
+                    }
+                    rl.add_history_entry(line);
+                } else if let Some(groups) = back_pattern.captures(line) {
+                    match groups.get(1).map_or(Ok(1), |n| n.as_str().parse::<usize>()) {
+                        Ok(n) => match sim.step_back(n) {
+                            Ok(_) => {
+                                i = sim.steps() + 1;
+                                println!("{}: {}", sim.steps(), format_next_line(&sim));
+                            }
+                            Err(_) => println!("Error: only {} steps to go back", sim.steps()),
+                        },
+                        Err(e) => println!("Error: invalid number of steps ({})", e),
+                    }
+                    rl.add_history_entry(line);
+                } else if let Some(groups) = goto_pattern.captures(line) {
+                    match groups.get(1).unwrap().as_str().parse::<usize>() {
+                        Ok(n) => {
+                            if let Err(e) = sim.goto(n) {
+                                println!("Error: {:?}", e);
+                            }
+                            i = sim.steps() + 1;
+                            println!("{}: {}", sim.steps(), format_next_line(&sim));
+                        }
+                        Err(e) => println!("Error: invalid step ({})", e),
+                    }
+                    rl.add_history_entry(line);
+                } else if let Some(groups) = save_pattern.captures(line) {
+                    let path = groups.get(1).unwrap().as_str();
+                    match save_simulator(&sim, path) {
+                        Ok(_) => println!("Saved to {}", path),
+                        Err(e) => println!("Error: {:?}", e),
+                    }
+                    rl.add_history_entry(line);
+                } else if let Some(groups) = load_pattern.captures(line) {
+                    let path = groups.get(1).unwrap().as_str();
+                    match load_simulator(path) {
+                        Ok(loaded) => {
+                            sim = loaded;
+                            i = sim.steps() + 1;
+                            println!("{}: {}", sim.steps(), format_next_line(&sim));
+                        }
+                        Err(e) => println!("Error: {:?}", e),
+                    }
+                    rl.add_history_entry(line);
                 } else {
                     println!("Error: unknown command");
                 }
@@ -273,6 +485,38 @@ fn run_program<const MS: usize, const DS: usize, const SS: usize>(
     }
 }
 
+// Is a path to a JSON file (otherwise RON).
+fn is_json(path: &str) -> bool {
+    Path::new(path).extension().is_some_and(|ext| ext == "json")
+}
+
+// Save a simulator snapshot to a RON (or JSON) file.
+fn save_simulator<const MS: usize, const DS: usize, const SS: usize>(
+    sim: &ICSimulator<MS, DS, SS>,
+    path: &str,
+) -> Result<(), CliError> {
+    let s = if is_json(path) {
+        serde_json::to_string_pretty(sim)?
+    } else {
+        ron::ser::to_string_pretty(sim, ron::ser::PrettyConfig::new())?
+    };
+    std::fs::write(path, s)?;
+    Ok(())
+}
+
+// Load a simulator snapshot from a RON (or JSON) file.
+fn load_simulator<const MS: usize, const DS: usize, const SS: usize>(
+    path: &str,
+) -> Result<ICSimulator<MS, DS, SS>, CliError> {
+    let file = File::open(path)?;
+    let sim = if is_json(path) {
+        serde_json::from_reader(file)?
+    } else {
+        from_reader(file)?
+    };
+    Ok(sim)
+}
+
 fn format_next_line<const MS: usize, const DS: usize, const SS: usize>(
     sim: &ICSimulator<MS, DS, SS>,
 ) -> String {
@@ -289,3 +533,23 @@ fn step<const MS: usize, const DS: usize, const SS: usize>(
     println!("{}: {} -> {} ", i, l1, l2);
     *i += 1;
 }
+
+fn tick<const MS: usize, const DS: usize, const SS: usize>(
+    n: usize,
+    i: &mut usize,
+    sim: &mut ICSimulator<MS, DS, SS>,
+) {
+    match sim.run_ticks(n) {
+        Ok(reports) => {
+            for report in reports.iter() {
+                *i += report.steps;
+                println!(
+                    "tick {}: {} steps, {:?}",
+                    report.tick, report.steps, report.end
+                );
+            }
+            println!("{}: {}", i, format_next_line(sim));
+        }
+        Err(e) => println!("Error: {:?}", e),
+    }
+}
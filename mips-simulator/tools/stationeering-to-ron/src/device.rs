@@ -11,7 +11,19 @@ pub enum ParamKind {
     ReadWrite(String),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Slot logic types common to all slots, used when the things JSON does not list any.
+const SLOT_PARAMS: [&str; 8] = [
+    "Occupied",
+    "OccupantHash",
+    "Quantity",
+    "Damage",
+    "Class",
+    "MaxQuantity",
+    "PrefabHash",
+    "SortingClass",
+];
+
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct AnonymousParams {
     read: Vec<String>,
     write: Vec<String>,
@@ -28,6 +40,10 @@ impl Into<(Vec<String>, Vec<String>)> for AnonymousParams {
 pub struct AnonymousDeviceKind {
     pub hash: i64,
     pub logicTypes: AnonymousParams,
+    #[serde(default)]
+    pub slots: Vec<String>,
+    #[serde(default)]
+    pub slotLogicTypes: Option<AnonymousParams>,
 }
 
 impl Into<(i64, (Vec<String>, Vec<String>))> for AnonymousDeviceKind {
@@ -36,11 +52,40 @@ impl Into<(i64, (Vec<String>, Vec<String>))> for AnonymousDeviceKind {
     }
 }
 
+/// Collect read and write marked params into param kinds.
+fn param_kinds(read: Vec<String>, write: Vec<String>) -> Vec<ParamKind> {
+    let mut params: HashMap<String, ParamKind> = HashMap::new();
+    // First collect the Read marked params...
+    for s in read.into_iter() {
+        let param_kind = ParamKind::Read(s.clone());
+        params.insert(s, param_kind);
+    }
+    // Then the Write marked...
+    for s in write.into_iter() {
+        let param_kind = if params.contains_key(&s) {
+            // ReadWrite if already marked Read...
+            ParamKind::ReadWrite(s.clone())
+        } else {
+            // else Write.
+            ParamKind::Write(s.clone())
+        };
+        params.insert(s, param_kind);
+    }
+    params.into_values().collect()
+}
+
+#[derive(Serialize, Debug)]
+pub struct SlotKind {
+    pub name: String,
+    pub params: Vec<ParamKind>,
+}
+
 #[derive(Serialize, Debug)]
 pub struct DeviceKind {
     pub name: String,
     pub hash: i64,
     pub params: Vec<ParamKind>,
+    pub slots: Vec<SlotKind>,
 }
 
 #[derive(Serialize, Debug)]
@@ -63,7 +108,7 @@ impl<'de> Deserialize<'de> for DeviceKinds {
 
                 let prefix = regex::Regex::new(r"Structure|Item").unwrap();
                 while let Some(name) = map.next_key::<String>()? {
-                    if let Ok(annonymous_dk) = map.next_value::<AnonymousDeviceKind>() {
+                    if let Ok(mut annonymous_dk) = map.next_value::<AnonymousDeviceKind>() {
                         let name = if let Some(re_match) = prefix.find(&name) {
                             let e = re_match.end();
                             name[e..].to_string()
@@ -71,30 +116,26 @@ impl<'de> Deserialize<'de> for DeviceKinds {
                             name.to_string()
                         };
 
+                        // Each slot has the same slot logic types
+                        let (slot_read, slot_write) = match annonymous_dk.slotLogicTypes.take() {
+                            Some(slot_params) => slot_params.into(),
+                            None => (SLOT_PARAMS.iter().map(|s| s.to_string()).collect(), Vec::new()),
+                        };
+                        let slots = std::mem::take(&mut annonymous_dk.slots)
+                            .into_iter()
+                            .map(|name| SlotKind {
+                                name,
+                                params: param_kinds(slot_read.clone(), slot_write.clone()),
+                            })
+                            .collect();
+
                         let (hash, (read, write)) = annonymous_dk.into();
-                        let mut params: HashMap<String, ParamKind> = HashMap::new();
-                        // First collect the Read marked params...
-                        for s in read.into_iter() {
-                            let param_kind = ParamKind::Read(s.clone());
-                            params.insert(s, param_kind);
-                        }
-                        // Then the Write marked...
-                        for s in write.into_iter() {
-                            let param_kind = if params.contains_key(&s) {
-                                // ReadWrite if already marked Read...
-                                ParamKind::ReadWrite(s.clone())
-                            } else {
-                                // else Write.
-                                ParamKind::Write(s.clone())
-                            };
-                            params.insert(s, param_kind);
-                        }
-
-                        let params = params.into_values().collect();
+                        let params = param_kinds(read, write);
                         let device_kind = DeviceKind {
                             name: name.clone(),
                             hash,
                             params,
+                            slots,
                         };
                         device_kinds.push((name, device_kind));
                     }
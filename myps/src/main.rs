@@ -8,6 +8,14 @@
 use std::io::Read;
 
 use myps::superprelude::*;
+use mips_parser::limits::Limits;
+use util::diagnostic::{Diagnostic, Severity};
+
+// Print a diagnostic and exit with failure.
+fn fail(diagnostic: Diagnostic, path: &str, source: &str) -> ! {
+    eprintln!("{}", diagnostic.render(path, source));
+    std::process::exit(1);
+}
 
 fn print_inteference_graph(translator: &Translator) {
     print!("    ");
@@ -68,12 +76,14 @@ fn main() {
     // let rule = Rule::expr_line;
     // let rule = Rule::rv_expr_line;
     // let rule = Rule::stmt_assign_value_line;
-    let peg = MypsParser::parse(rule, &source).unwrap();
+    let peg = MypsParser::parse(rule, &source)
+        .unwrap_or_else(|e| fail(Diagnostic::from_pest(&e), path, &source));
     // println!("{:#?}", peg);
 
     // LEXER TEST
     let program_pair = peg.only_inner().unwrap();
-    let (program_item, functions) = lex_program_pair(program_pair).unwrap();
+    let (program_item, functions) = lex_program_pair(program_pair)
+        .unwrap_or_else(|e| fail(Diagnostic::error(e.to_string()), path, &source));
 
     // // TRANSLATOR TEST
     let conf_path = "translator.ron";
@@ -97,9 +107,24 @@ fn main() {
     // print_inteference_graph(&translator);
     // println!("# ==========================");
     translator.optimize_registers();
+    let mut output = String::new();
     for (i, unit) in translator.units.iter().enumerate() {
         // println!("{:?}", unit);
         // println!("{}: {}", i, unit);
-        println!("{}", unit);
+        output += &format!("{}\n", unit);
+    }
+
+    // Check the output against the IC10 limits, failing with `--strict-limits` else warning
+    let strict = args.iter().any(|arg| arg == "--strict-limits");
+    let mut diagnostics = Limits::new().check(&output);
+    for diagnostic in diagnostics.iter_mut() {
+        if !strict {
+            diagnostic.severity = Severity::Warning;
+        }
+        eprintln!("{}\n", diagnostic.render("<output>", &output));
+    }
+    if strict && !diagnostics.is_empty() {
+        std::process::exit(1);
     }
+    print!("{}", output);
 }
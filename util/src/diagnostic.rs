@@ -0,0 +1,170 @@
+//! Source-located diagnostics with a codespan-style renderer.
+//!
+//! A [`Diagnostic`] points at a column span of a single source line, e.g.
+//!
+//! ```text
+//! error: `add` expects mem, val, val; found device register d0
+//!  --> example.mips:2:5
+//!   |
+//! 2 | add d0 1 2
+//!   |     ^^ expected mem
+//! ```
+use std::ops::Range;
+use std::{fmt, fmt::Display};
+
+use pest::error::{Error as PegError, ErrorVariant, LineColLocation};
+use pest::RuleType;
+
+/// Diagnostic severity.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Source-located diagnostic.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Line index (zero-based), if the diagnostic has a location.
+    pub line: Option<usize>,
+    /// Column span within the line (zero-based, in characters rather than bytes).
+    pub span: Range<usize>,
+    /// Offending token.
+    pub token: Option<String>,
+    /// Description of what was expected at the span (e.g. `"expected mem"`).
+    pub expected: Option<String>,
+}
+
+impl Diagnostic {
+    /// New error diagnostic without a location.
+    pub fn error<S: Into<String>>(message: S) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    /// New warning diagnostic without a location.
+    pub fn warning<S: Into<String>>(message: S) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    fn new<S: Into<String>>(severity: Severity, message: S) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            line: None,
+            span: 0..0,
+            token: None,
+            expected: None,
+        }
+    }
+
+    /// Builder helper to set the location.
+    pub fn with_span(mut self, line: usize, span: Range<usize>) -> Self {
+        self.line = Some(line);
+        self.span = span;
+        self
+    }
+
+    /// Builder helper to set the offending token.
+    pub fn with_token<S: Into<String>>(mut self, token: S) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Builder helper to set the expected description.
+    pub fn with_expected<S: Into<String>>(mut self, expected: S) -> Self {
+        self.expected = Some(expected.into());
+        self
+    }
+
+    /// Is this an error diagnostic.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Diagnostic from a Pest parser error.
+    pub fn from_pest<R: RuleType>(err: &PegError<R>) -> Self {
+        let message = match &err.variant {
+            ErrorVariant::ParsingError {
+                positives,
+                negatives,
+            } => {
+                let list = |rules: &Vec<R>| {
+                    rules
+                        .iter()
+                        .map(|r| format!("{:?}", r))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                match (positives.is_empty(), negatives.is_empty()) {
+                    (false, false) => format!(
+                        "unexpected {}; expected {}",
+                        list(negatives),
+                        list(positives)
+                    ),
+                    (false, true) => format!("expected {}", list(positives)),
+                    (true, false) => format!("unexpected {}", list(negatives)),
+                    (true, true) => "unknown parsing error".to_string(),
+                }
+            }
+            ErrorVariant::CustomError { message } => message.clone(),
+        };
+        let (line, span) = match err.line_col {
+            LineColLocation::Pos((l, c)) => (l - 1, (c - 1)..c),
+            LineColLocation::Span((l, c), (l_end, c_end)) => {
+                let c_end = if l == l_end { c_end } else { c + 1 };
+                (l - 1, (c - 1)..(c_end - 1).max(c))
+            }
+        };
+        Self::error(message).with_span(line, span)
+    }
+
+    /// Render this diagnostic as a codespan-style snippet of the source.
+    ///
+    /// The `path` is only used for display (e.g. `"<stdin>"`).
+    pub fn render(&self, path: &str, source: &str) -> String {
+        let mut s = format!("{}: {}", self.severity, self.message);
+        let line = match self.line {
+            Some(line) => line,
+            None => return s,
+        };
+        let number = (line + 1).to_string();
+        let pad = " ".repeat(number.len());
+        s += &format!("\n{}--> {}:{}:{}", pad, path, line + 1, self.span.start + 1);
+        if let Some(text) = source.lines().nth(line) {
+            let width = (self.span.end.saturating_sub(self.span.start)).max(1);
+            s += &format!("\n{} |", pad);
+            s += &format!("\n{} | {}", number, text);
+            s += &format!(
+                "\n{} | {}{}",
+                pad,
+                " ".repeat(self.span.start),
+                "^".repeat(width)
+            );
+            if let Some(expected) = &self.expected {
+                s += &format!(" {}", expected);
+            }
+        }
+        s
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)?;
+        if let Some(line) = self.line {
+            write!(f, " (line {}, column {})", line + 1, self.span.start + 1)?;
+        }
+        Ok(())
+    }
+}
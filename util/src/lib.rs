@@ -1,6 +1,13 @@
 #![feature(trait_alias)]
 pub mod traits;
 pub mod test_utils;
+pub mod diagnostic;
+
+/// Round a value used as an index (register, stack, slot or line) as the game does,
+/// i.e. half to even.
+pub fn round_index(v: f64) -> f64 {
+    v.round_ties_even()
+}
 
 #[macro_export]
 macro_rules! is_as_inner {